@@ -38,7 +38,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Modify an element near the middle of the data.
     println!("Modifying an element near the middle of the data...");
-    view_mut[[500, 1000, 2000]] = 3.14;
+    view_mut[[500, 1000, 2000]] = 2.5;
     print_file_sizes(&file)?;
 
     Ok(())
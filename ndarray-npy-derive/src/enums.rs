@@ -0,0 +1,160 @@
+//! Implementation of `#[derive(NpyEnum)]`.
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Ident};
+
+/// Integer types which may be used as the representation of the enum.
+const REPR_TYPES: &[&str] = &["i8", "i16", "i32", "i64", "u8", "u16", "u32", "u64"];
+
+/// Returns the integer type in the enum's `#[repr(...)]` attribute.
+fn parse_repr(input: &DeriveInput) -> syn::Result<Ident> {
+    let mut repr = None;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if let Some(ident) = meta.path.get_ident() {
+                if REPR_TYPES.iter().any(|ty| ident == ty) {
+                    repr = Some(ident.clone());
+                }
+            }
+            Ok(())
+        })?;
+    }
+    repr.ok_or_else(|| {
+        syn::Error::new_spanned(
+            &input.ident,
+            "NpyEnum requires a #[repr(...)] attribute with a fixed-size integer type, \
+             e.g. #[repr(i32)]",
+        )
+    })
+}
+
+fn parse_variants(input: &DeriveInput) -> syn::Result<Vec<&Ident>> {
+    let data = match &input.data {
+        Data::Enum(data) => data,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "NpyEnum can only be derived for enums",
+            ))
+        }
+    };
+    data.variants
+        .iter()
+        .map(|variant| match variant.fields {
+            Fields::Unit => Ok(&variant.ident),
+            _ => Err(syn::Error::new_spanned(
+                variant,
+                "NpyEnum can only be derived for enums without fields",
+            )),
+        })
+        .collect()
+}
+
+pub fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "NpyEnum cannot be derived for generic enums",
+        ));
+    }
+    let variants = parse_variants(input)?;
+    let repr = parse_repr(input)?;
+    let ident = &input.ident;
+    let type_name = ident.to_string();
+
+    let from_repr = quote! {
+        |value: #repr| -> ::std::option::Option<Self> {
+            #(
+                if value == Self::#variants as #repr {
+                    return ::std::option::Option::Some(Self::#variants);
+                }
+            )*
+            ::std::option::Option::None
+        }
+    };
+
+    // Safety (for all of the `unsafe` blocks): The enum is fieldless and has
+    // `#[repr(#repr)]`, and `from_repr` accepts only its discriminants.
+    Ok(quote! {
+        impl ::ndarray_npy::WritableElement for #ident {
+            fn type_descriptor() -> ::ndarray_npy::enums::PyValue {
+                <#repr as ::ndarray_npy::WritableElement>::type_descriptor()
+            }
+
+            fn write<W: ::std::io::Write>(
+                &self,
+                writer: W,
+            ) -> ::std::result::Result<(), ::ndarray_npy::WriteDataError> {
+                unsafe {
+                    ::ndarray_npy::enums::write_enum_slice::<Self, #repr, W>(
+                        ::std::slice::from_ref(self),
+                        writer,
+                    )
+                }
+            }
+
+            fn write_slice<W: ::std::io::Write>(
+                slice: &[Self],
+                writer: W,
+            ) -> ::std::result::Result<(), ::ndarray_npy::WriteDataError> {
+                unsafe { ::ndarray_npy::enums::write_enum_slice::<Self, #repr, W>(slice, writer) }
+            }
+        }
+
+        impl ::ndarray_npy::ReadableElement for #ident {
+            fn read_to_end_exact_vec<R: ::std::io::Read>(
+                reader: R,
+                type_desc: &::ndarray_npy::enums::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<::std::vec::Vec<Self>, ::ndarray_npy::ReadDataError> {
+                ::ndarray_npy::enums::read_enum::<Self, #repr, R>(
+                    reader,
+                    type_desc,
+                    len,
+                    #type_name,
+                    #from_repr,
+                )
+            }
+        }
+
+        impl ::ndarray_npy::ViewElement for #ident {
+            fn bytes_as_slice<'a>(
+                bytes: &'a [u8],
+                type_desc: &::ndarray_npy::enums::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<&'a [Self], ::ndarray_npy::ViewDataError> {
+                unsafe {
+                    ::ndarray_npy::enums::view_enum_slice::<Self, #repr>(
+                        bytes,
+                        type_desc,
+                        len,
+                        #type_name,
+                        #from_repr,
+                    )
+                }
+            }
+        }
+
+        impl ::ndarray_npy::ViewMutElement for #ident {
+            fn bytes_as_mut_slice<'a>(
+                bytes: &'a mut [u8],
+                type_desc: &::ndarray_npy::enums::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<&'a mut [Self], ::ndarray_npy::ViewDataError> {
+                unsafe {
+                    ::ndarray_npy::enums::view_enum_mut_slice::<Self, #repr>(
+                        bytes,
+                        type_desc,
+                        len,
+                        #type_name,
+                        #from_repr,
+                    )
+                }
+            }
+        }
+    })
+}
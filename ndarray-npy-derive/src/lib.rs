@@ -0,0 +1,36 @@
+//! Derive macros for [`ndarray-npy`](https://docs.rs/ndarray-npy).
+//!
+//! Use the macros through the re-exports in `ndarray-npy` (with its `derive`
+//! feature) rather than depending on this crate directly.
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod enums;
+mod record;
+
+/// Derives `WritableElement`, `ReadableElement`, and (for `#[repr(C)]`
+/// structs) `ViewElement` for a struct with named fields, mapping it to a
+/// NumPy structured dtype.
+///
+/// See the re-export in `ndarray-npy` for details.
+#[proc_macro_derive(NpyRecord, attributes(npy))]
+pub fn derive_npy_record(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    record::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Derives `WritableElement`, `ReadableElement`, `ViewElement`, and
+/// `ViewMutElement` for a fieldless enum with an integer representation
+/// (e.g. `#[repr(i32)]`), mapping it to an array of integer codes.
+///
+/// See the re-export in `ndarray-npy` for details.
+#[proc_macro_derive(NpyEnum)]
+pub fn derive_npy_enum(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    enums::derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
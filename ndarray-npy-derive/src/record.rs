@@ -0,0 +1,308 @@
+//! Implementation of `#[derive(NpyRecord)]`.
+
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, Ident, LitStr, Type};
+
+/// A field of the struct.
+struct Field<'a> {
+    ident: &'a Ident,
+    ty: &'a Type,
+    /// Name of the field in the structured dtype.
+    name: String,
+}
+
+/// The layout-related parts of the struct's `#[repr(...)]` attribute.
+#[derive(Default)]
+struct Repr {
+    c: bool,
+    packed: bool,
+}
+
+impl Repr {
+    fn parse(input: &DeriveInput) -> syn::Result<Repr> {
+        let mut repr = Repr::default();
+        for attr in &input.attrs {
+            if !attr.path().is_ident("repr") {
+                continue;
+            }
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("C") {
+                    repr.c = true;
+                } else if meta.path.is_ident("packed") {
+                    repr.packed = true;
+                }
+                // Skip the arguments of e.g. `packed(2)` and `align(8)`.
+                if meta.input.peek(syn::token::Paren) {
+                    let _content;
+                    syn::parenthesized!(_content in meta.input);
+                }
+                Ok(())
+            })?;
+        }
+        Ok(repr)
+    }
+}
+
+fn parse_fields(input: &DeriveInput) -> syn::Result<Vec<Field<'_>>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "NpyRecord can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "NpyRecord can only be derived for structs",
+            ))
+        }
+    };
+    fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.as_ref().unwrap();
+            let mut name = ident.to_string();
+            for attr in &field.attrs {
+                if !attr.path().is_ident("npy") {
+                    continue;
+                }
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        name = meta.value()?.parse::<LitStr>()?.value();
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported npy attribute"))
+                    }
+                })?;
+            }
+            Ok(Field {
+                ident,
+                ty: &field.ty,
+                name,
+            })
+        })
+        .collect()
+}
+
+pub fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            "NpyRecord cannot be derived for generic structs",
+        ));
+    }
+    let repr = Repr::parse(input)?;
+    let fields = parse_fields(input)?;
+    let ident = &input.ident;
+
+    let writable = derive_writable(ident, &fields, &repr);
+    let readable = derive_readable(ident, &fields);
+    let view = if repr.c {
+        derive_view(ident, &fields)
+    } else {
+        TokenStream::new()
+    };
+    Ok(quote! {
+        #writable
+        #readable
+        #view
+    })
+}
+
+fn derive_writable(ident: &Ident, fields: &[Field<'_>], repr: &Repr) -> TokenStream {
+    let names: Vec<_> = fields.iter().map(|f| &f.name).collect();
+    let idents: Vec<_> = fields.iter().map(|f| f.ident).collect();
+    let types: Vec<_> = fields.iter().map(|f| f.ty).collect();
+
+    // `#[repr(C)]` structs are written with the same layout as in memory (so
+    // that they can be viewed), while other structs are written without
+    // padding in the order of their fields.
+    let type_descriptor = if repr.c {
+        quote! {
+            ::ndarray_npy::record::RecordLayout::with_offsets(
+                [#((
+                    #names,
+                    <#types as ::ndarray_npy::WritableElement>::type_descriptor(),
+                    ::std::mem::offset_of!(Self, #idents),
+                )),*],
+                ::std::mem::size_of::<Self>(),
+            )
+        }
+    } else {
+        quote! {
+            ::ndarray_npy::record::RecordLayout::packed([#((
+                #names,
+                <#types as ::ndarray_npy::WritableElement>::type_descriptor(),
+            )),*])
+        }
+    };
+    let array_type_descriptor = if repr.c {
+        quote! { <Self as ::ndarray_npy::WritableElement>::type_descriptor() }
+    } else {
+        quote! {
+            let elems: ::std::vec::Vec<&Self> = ::std::iter::IntoIterator::into_iter(elems).collect();
+            ::ndarray_npy::record::RecordLayout::packed([#((
+                #names,
+                <#types as ::ndarray_npy::WritableElement>::array_type_descriptor(
+                    elems.iter().map(|elem| &elem.#idents),
+                ),
+            )),*])
+            .expect("the sizes of all field types of a record must be known")
+            .to_type_descriptor()
+        }
+    };
+    // Fields of packed structs can't be borrowed, so they're copied.
+    let values: Vec<_> = fields
+        .iter()
+        .map(|f| {
+            let field_ident = f.ident;
+            if repr.packed {
+                quote! { &{ elem.#field_ident } }
+            } else {
+                quote! { &elem.#field_ident }
+            }
+        })
+        .collect();
+
+    quote! {
+        impl ::ndarray_npy::WritableElement for #ident {
+            fn type_descriptor() -> ::ndarray_npy::record::PyValue {
+                #type_descriptor
+                    .expect("the sizes of all field types of a record must be known")
+                    .to_type_descriptor()
+            }
+
+            fn write<W: ::std::io::Write>(
+                &self,
+                writer: W,
+            ) -> ::std::result::Result<(), ::ndarray_npy::WriteDataError> {
+                let type_desc = <Self as ::ndarray_npy::WritableElement>::array_type_descriptor(
+                    ::std::slice::from_ref(self),
+                );
+                <Self as ::ndarray_npy::WritableElement>::write_with_descriptor(self, &type_desc, writer)
+            }
+
+            fn write_slice<W: ::std::io::Write>(
+                slice: &[Self],
+                writer: W,
+            ) -> ::std::result::Result<(), ::ndarray_npy::WriteDataError> {
+                let type_desc = <Self as ::ndarray_npy::WritableElement>::array_type_descriptor(slice);
+                <Self as ::ndarray_npy::WritableElement>::write_slice_with_descriptor(slice, &type_desc, writer)
+            }
+
+            fn array_type_descriptor<'a, I>(elems: I) -> ::ndarray_npy::record::PyValue
+            where
+                I: ::std::iter::IntoIterator<Item = &'a Self>,
+                Self: 'a,
+            {
+                #array_type_descriptor
+            }
+
+            fn write_with_descriptor<W: ::std::io::Write>(
+                &self,
+                type_desc: &::ndarray_npy::record::PyValue,
+                writer: W,
+            ) -> ::std::result::Result<(), ::ndarray_npy::WriteDataError> {
+                <Self as ::ndarray_npy::WritableElement>::write_slice_with_descriptor(
+                    ::std::slice::from_ref(self),
+                    type_desc,
+                    writer,
+                )
+            }
+
+            fn write_slice_with_descriptor<W: ::std::io::Write>(
+                slice: &[Self],
+                type_desc: &::ndarray_npy::record::PyValue,
+                writer: W,
+            ) -> ::std::result::Result<(), ::ndarray_npy::WriteDataError> {
+                ::ndarray_npy::record::RecordWriter::with_type_descriptor(type_desc, writer, |writer| {
+                    for elem in slice {
+                        #(writer.write_field::<#types>(#names, #values)?;)*
+                        writer.finish_record()?;
+                    }
+                    ::std::result::Result::Ok(())
+                })
+            }
+        }
+    }
+}
+
+fn derive_readable(ident: &Ident, fields: &[Field<'_>]) -> TokenStream {
+    let names: Vec<_> = fields.iter().map(|f| &f.name).collect();
+    let idents: Vec<_> = fields.iter().map(|f| f.ident).collect();
+    let types: Vec<_> = fields.iter().map(|f| f.ty).collect();
+    let columns: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("__column_{}", i))
+        .collect();
+
+    quote! {
+        impl ::ndarray_npy::ReadableElement for #ident {
+            fn read_to_end_exact_vec<R: ::std::io::Read>(
+                reader: R,
+                type_desc: &::ndarray_npy::record::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<::std::vec::Vec<Self>, ::ndarray_npy::ReadDataError> {
+                let data = ::ndarray_npy::record::RecordData::read(
+                    reader,
+                    type_desc,
+                    len,
+                    &[#(#names),*],
+                )?;
+                #(
+                    let mut #columns = ::std::iter::IntoIterator::into_iter(
+                        data.field::<#types>(#names)?,
+                    );
+                )*
+                ::std::result::Result::Ok(
+                    (0..len)
+                        .map(|_| Self {
+                            #(#idents: #columns.next().unwrap(),)*
+                        })
+                        .collect(),
+                )
+            }
+        }
+    }
+}
+
+fn derive_view(ident: &Ident, fields: &[Field<'_>]) -> TokenStream {
+    let names: Vec<_> = fields.iter().map(|f| &f.name).collect();
+    let idents: Vec<_> = fields.iter().map(|f| f.ident).collect();
+    let types: Vec<_> = fields.iter().map(|f| f.ty).collect();
+
+    let check_fields = quote! {
+        let layout = ::ndarray_npy::record::view_layout::<Self>(
+            type_desc,
+            &[#((#names, ::std::mem::offset_of!(Self, #idents))),*],
+        )?;
+        #(
+            ::ndarray_npy::record::check_view_field::<#types>(
+                &bytes[..],
+                &layout,
+                type_desc,
+                #names,
+                len,
+            )?;
+        )*
+    };
+
+    quote! {
+        impl ::ndarray_npy::ViewElement for #ident {
+            fn bytes_as_slice<'a>(
+                bytes: &'a [u8],
+                type_desc: &::ndarray_npy::record::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<&'a [Self], ::ndarray_npy::ViewDataError> {
+                #check_fields
+                // Safety: The layout of the data matches the layout of `Self`,
+                // and every field has been checked to be valid.
+                unsafe { ::ndarray_npy::record::bytes_as_record_slice(bytes, len) }
+            }
+        }
+    }
+}
@@ -16,14 +16,33 @@
 //! - Reading
 //!   - [`ReadNpyExt`] extension trait
 //!   - [`read_npy`] convenience function
+//!   - [`read_npy_dyn`] for element types determined at runtime ([`DynNpyArray`])
+//!   - [`read_npy_as`] to convert the element type while reading ([`cast`] module)
+//!   - [`read_npy_raw`] for the header and undecoded data
+//!   - [`read_npy_slice`] to read only a region selected with [`s!`](ndarray::s)
+//!   - [`gather_rows`] to read only the rows with the given indices
+//!   - [`NpyChunks`] iterator to read a file in chunks of rows, for files
+//!     which don't fit in memory
+//!   - [`ReadNpyExt::read_npy_partial`] and [`NpyStreamReader`] iterator to
+//!     read several arrays written one after another to the same stream
+//!   - [`NpyFile`] to parse the header once and then read the data in various
+//!     ways, including subranges of rows and (with the `memmap2` feature)
+//!     memory-mapped views
 //! - Writing
 //!   - [`WriteNpyExt`] extension trait
+//!   - [`DynWriteNpy`] object-safe trait for collections of arrays of different types
 //!   - [`write_npy`] and [`create_new_npy`] convenience functions
 //!   - [`write_zeroed_npy`] to write an `.npy` file (sparse if possible) of zeroed data
+//!   - [`write_npy_as`] to convert the element type while writing ([`cast`] module)
+//!   - [`write_npy_raw`] for a header and undecoded data
+//!   - [`NpyStreamWriter`] to write several arrays one after another to the
+//!     same stream
 //! - Readonly viewing (primarily for use with memory-mapped files)
 //!   - [`ViewNpyExt`] extension trait
 //! - Mutable viewing (primarily for use with memory-mapped files)
 //!   - [`ViewMutNpyExt`] extension trait
+//! - Bit-packed `bool` arrays compatible with `np.packbits`/`np.unpackbits`
+//!   - [`packbits`] module
 //!
 //! It's possible to create `.npy` files larger than the available memory with
 //! [`write_zeroed_npy`] and then modify them by memory-mapping and using
@@ -36,17 +55,35 @@
 //!
 //! # Limitations
 //!
-//! * Parsing of `.npy` files is currently limited to files where the `descr`
-//!   field of the [header dictionary] is a Python string literal of the form
-//!   `'string'`, `"string"`, `'''string'''`, or `"""string"""`.
+//! * Reading `.npy` files is currently limited to files where the `descr`
+//!   field of the [header dictionary] is a string (e.g. `'<f4'`), a list of
+//!   fields for a structured dtype (e.g. `[('x', '<f4'), ('y', '<f4')]`), or a
+//!   tuple for a subarray dtype (e.g. `('<f4', (3,))`). The dict form of
+//!   structured descriptors (e.g. `{'names': [...], 'formats': [...]}`) isn't
+//!   supported.
 //!
 //! * The element traits ([`WritableElement`], [`ReadableElement`],
 //!   [`ViewElement`], and [`ViewMutElement`]) are currently implemented only
 //!   for fixed-size integers up to 64 bits, floating point numbers, complex
-//!   floating point numbers (if enabled with the crate feature), and [`bool`].
+//!   floating point numbers (if enabled with the crate feature),
+//!   half-precision floating point numbers (if enabled with the `half`
+//!   feature), the `OrderedFloat` and `NotNan` float wrappers (if enabled with
+//!   the `ordered-float` feature), [`bool`], fixed-width byte strings
+//!   ([`FixedBytes`] and `Vec<u8>`), raw void data ([`Void`]), unicode strings
+//!   ([`String`]), `datetime64`/`timedelta64` values (see the [`datetime`]
+//!   module), x87 `long double` values (for reading only; see the
+//!   [`longdouble`] module), the bfloat16 and float8 formats of the
+//!   `ml_dtypes` package (see the [`ml_dtypes`] module), arrays (`[T; N]`) of
+//!   these types as subarrays, structs mapped to structured dtypes (see the
+//!   [`record`] module and `#[derive(NpyRecord)]`), fieldless enums mapped to
+//!   integer codes (see the [`enums`] module and `#[derive(NpyEnum)]`), and,
+//!   for reading only, object arrays of simple Python objects (`PyObject`, if
+//!   enabled with the `pickle` feature).
 //!
-//! The plan is to add support for more element types (including custom
-//! user-defined structs) in the future.
+//! The element traits can be implemented for newtypes wrapping any of these
+//! types with the [`impl_newtype_element!`] macro.
+//!
+//! The plan is to add support for more element types in the future.
 //!
 //! [header dictionary]: https://docs.scipy.org/doc/numpy/reference/generated/numpy.lib.format.html#format-version-1-0
 
@@ -54,10 +91,121 @@ pub mod npy;
 #[cfg(feature = "npz")]
 mod npz;
 
+pub use crate::npy::cast;
+pub use crate::npy::cast::{read_npy_as, write_npy_as};
+pub use crate::npy::datetime;
+pub use crate::npy::dtype;
+pub use crate::npy::enums;
+pub use crate::npy::longdouble;
+pub use crate::npy::ml_dtypes;
+pub use crate::npy::packbits;
+pub use crate::npy::record;
+#[cfg(feature = "memmap2")]
+pub use crate::npy::NpyMmap;
+#[cfg(feature = "pickle")]
+pub use crate::npy::PyObject;
 pub use crate::npy::{
-    create_new_npy, read_npy, write_npy, write_zeroed_npy, ReadDataError, ReadNpyError, ReadNpyExt,
-    ReadableElement, ViewDataError, ViewElement, ViewMutElement, ViewMutNpyExt, ViewNpyError,
-    ViewNpyExt, WritableElement, WriteDataError, WriteNpyError, WriteNpyExt,
+    create_new_npy, gather_rows, read_npy, read_npy_dyn, read_npy_raw, read_npy_slice, write_npy,
+    write_npy_raw, write_zeroed_npy, ByteStringTooLongError, DynNpyArray, DynNpyArrayVisitor,
+    DynWriteNpy, FixedBytes, NpyChunks, NpyFile, NpyStreamReader, NpyStreamWriter, RawDataError,
+    ReadDataError, ReadNpyError, ReadNpyExt, ReadableElement, ViewDataError, ViewElement,
+    ViewMutElement, ViewMutNpyExt, ViewNpyError, ViewNpyExt, Void, WritableElement, WriteDataError,
+    WriteNpyError, WriteNpyExt,
 };
 #[cfg(feature = "npz")]
 pub use crate::npz::{NpzReader, NpzWriter, ReadNpzError, WriteNpzError};
+/// Derives the element traits for a struct, mapping it to a NumPy structured
+/// dtype (requires the `derive` feature).
+///
+/// Each field of the struct becomes a field of the structured dtype with the
+/// same name (which can be changed with `#[npy(rename = "name")]`). The type
+/// of each field must implement the element traits itself; this includes
+/// other structs with `#[derive(NpyRecord)]`.
+///
+/// * [`WritableElement`] writes the struct as a record with a list-form
+///   descriptor, e.g. `[('x', '<f4'), ('y', '<f4'), ('id', '<u8')]`. For
+///   `#[repr(C)]` structs, the fields are placed at the same offsets as in
+///   memory, and the padding is described with `('', '|V<n>')` fields, like
+///   NumPy does for aligned structured dtypes. Other structs are written
+///   without padding, in the order of their fields.
+///
+/// * [`ReadableElement`] reads data with a list-form descriptor whose named
+///   fields are exactly the fields of the struct (in any order and at any
+///   offsets). The type of each field is checked with its own descriptor, so
+///   fields may have different byte orders.
+///
+/// * For `#[repr(C)]` structs (including `#[repr(C, packed)]` ones),
+///   [`ViewElement`] is also derived. Viewing requires the layout of the
+///   data to match the layout of the struct exactly, and the types of all of
+///   the fields must implement [`ViewElement`]. [`ViewMutElement`] isn't
+///   derived, since assigning a struct through a mutable view would copy its
+///   uninitialized padding bytes into the data.
+///
+/// Generic structs are not supported.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{NpyRecord, ReadNpyExt, WriteNpyExt};
+/// use ndarray::Array1;
+///
+/// #[derive(Clone, Debug, PartialEq, NpyRecord)]
+/// #[repr(C)]
+/// struct Point {
+///     x: f32,
+///     y: f32,
+///     id: u64,
+/// }
+///
+/// let arr = array![Point { x: 1., y: 2., id: 3 }, Point { x: 4., y: 5., id: 6 }];
+/// let mut buf = Vec::new();
+/// arr.write_npy(&mut buf)?;
+/// assert_eq!(Array1::<Point>::read_npy(&buf[..])?, arr);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "derive")]
+pub use ndarray_npy_derive::NpyRecord;
+
+/// Derives the element traits for a fieldless enum, mapping it to integer
+/// codes (requires the `derive` feature).
+///
+/// The enum must have a `#[repr(...)]` attribute with a fixed-size integer
+/// type (`i8`, `i16`, `i32`, `i64`, `u8`, `u16`, `u32`, or `u64`), and each
+/// value is stored as its discriminant, using the descriptor of that integer
+/// type (e.g. `<i4` for `#[repr(i32)]` or `|u1` for `#[repr(u8)]`). This
+/// implements [`WritableElement`], [`ReadableElement`], [`ViewElement`], and
+/// [`ViewMutElement`].
+///
+/// Reading and viewing check that every code is the discriminant of a
+/// variant; otherwise, they fail with an error which includes the invalid
+/// code and its index. Like for the integer types, reading accepts either
+/// byte order, while viewing requires the native byte order.
+///
+/// Generic enums are not supported. See the [`enums`] module for the
+/// functions used by the derived implementations.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{NpyEnum, ReadNpyExt, WriteNpyExt};
+/// use ndarray::Array1;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, NpyEnum)]
+/// #[repr(u8)]
+/// enum Color {
+///     Red = 1,
+///     Green = 2,
+///     Blue = 4,
+/// }
+///
+/// let arr = array![Color::Red, Color::Blue, Color::Green];
+/// let mut buf = Vec::new();
+/// arr.write_npy(&mut buf)?;
+/// assert_eq!(Array1::<Color>::read_npy(&buf[..])?, arr);
+/// assert_eq!(Array1::<u8>::read_npy(&buf[..])?, array![1, 4, 2]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[cfg(feature = "derive")]
+pub use ndarray_npy_derive::NpyEnum;
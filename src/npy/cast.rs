@@ -0,0 +1,842 @@
+//! Reading and writing arrays with conversion of the element type.
+//!
+//! [`read_npy_as`] reads an `.npy` file with any numeric (or `bool`)
+//! descriptor and converts the elements to the requested element type. The
+//! [`CastPolicy`] determines which conversions are allowed, like the
+//! `casting` argument of NumPy's `can_cast`. Whether a conversion is allowed
+//! depends only on the types, not on the values in the file.
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::{array, Array1, Ix1};
+//! use ndarray_npy::cast::{read_npy_as, CastPolicy};
+//! use ndarray_npy::WriteNpyExt;
+//!
+//! let mut buf = Vec::new();
+//! array![1i32, -2, 3].write_npy(&mut buf)?;
+//!
+//! // `int32` can be converted to `float64` without loss.
+//! let arr: Array1<f64> = read_npy_as(&buf[..], CastPolicy::Lossless)?;
+//! assert_eq!(arr, array![1., -2., 3.]);
+//!
+//! // `int32` to `float32` is lossy, so it requires `SameKind` or `Unsafe`.
+//! assert!(read_npy_as::<f32, _, Ix1>(&buf[..], CastPolicy::Lossless).is_err());
+//! let arr: Array1<f32> = read_npy_as(&buf[..], CastPolicy::SameKind)?;
+//! assert_eq!(arr, array![1., -2., 3.]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`write_npy_as`] goes the other way: it writes an array with a different
+//! element type than the one in memory, converting the elements while
+//! writing them, e.g. to store `f64` data as `float32`. Since the element
+//! types are known at compile time, every numeric conversion is allowed; the
+//! [`OverflowPolicy`] determines what happens to values which are out of the
+//! range of the target type.
+//!
+//! ```
+//! use ndarray::array;
+//! use ndarray_npy::cast::{write_npy_as, OverflowPolicy};
+//! use ndarray_npy::ReadNpyExt;
+//! # use ndarray::Array1;
+//!
+//! let arr = array![1.5f64, -2., 1e300];
+//! let mut buf = Vec::new();
+//! write_npy_as::<f32>(&mut buf, &arr, OverflowPolicy::Saturate)?;
+//! assert_eq!(
+//!     Array1::<f32>::read_npy(&buf[..])?,
+//!     array![1.5, -2., f32::MAX],
+//! );
+//!
+//! // `1e300` is out of the range of `float32`.
+//! assert!(write_npy_as::<f32>(Vec::new(), &arr, OverflowPolicy::Error).is_err());
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use super::dtype::{DType, Kind};
+use super::header::Header;
+use super::{shape_length_checked, write_array_data, DataShape, DynNpyArray};
+use crate::{
+    ReadNpyError, ReadableElement, WritableElement, WriteDataError, WriteNpyError, WriteNpyExt,
+};
+#[cfg(feature = "half")]
+use half::f16;
+use ndarray::{Array, ArrayBase, ArrayD, ArrayRef, Data, Dimension};
+#[cfg(feature = "num-complex-0_4")]
+use num_complex_0_4::Complex;
+use py_literal::Value as PyValue;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+/// Which conversions of the element type are allowed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum CastPolicy {
+    /// Only conversions which preserve every value are allowed, like
+    /// NumPy's `'safe'` casting. For example, `int32` can be converted to
+    /// `int64` or `float64`, but not to `float32`.
+    ///
+    /// Like in NumPy, `int64` and `uint64` can be converted to `float64`,
+    /// even though values with a magnitude greater than 2<sup>53</sup> are
+    /// rounded.
+    Lossless,
+    /// Lossless conversions and conversions within the same kind or to a
+    /// "higher" kind are allowed, like NumPy's `'same_kind'` casting. The
+    /// kinds are ordered `bool` < unsigned integer < signed integer < float
+    /// < complex. For example, `float64` can be converted to `float32`, but
+    /// not to `int64`.
+    SameKind,
+    /// All conversions between numeric types are allowed, like NumPy's
+    /// `'unsafe'` casting. Floats are truncated toward zero when converted
+    /// to integers (saturating at the bounds of the integer type, with NaN
+    /// becoming zero), integers wrap around when converted to narrower
+    /// integers, and complex numbers are converted to real numbers by
+    /// discarding the imaginary part.
+    Unsafe,
+}
+
+impl fmt::Display for CastPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CastPolicy::Lossless => write!(f, "lossless"),
+            CastPolicy::SameKind => write!(f, "same-kind"),
+            CastPolicy::Unsafe => write!(f, "unsafe"),
+        }
+    }
+}
+
+/// An error converting an array to another element type, because the
+/// conversion isn't allowed by the [`CastPolicy`].
+#[derive(Clone, Debug)]
+pub struct CastError {
+    from: &'static str,
+    to: &'static str,
+    policy: CastPolicy,
+}
+
+impl CastError {
+    /// Returns the NumPy name of the element type of the source array, e.g.
+    /// `"int64"`.
+    pub fn from_type(&self) -> &'static str {
+        self.from
+    }
+
+    /// Returns the NumPy name of the requested element type, e.g.
+    /// `"float32"`.
+    pub fn to_type(&self) -> &'static str {
+        self.to
+    }
+
+    /// Returns the policy which didn't allow the conversion.
+    pub fn policy(&self) -> CastPolicy {
+        self.policy
+    }
+}
+
+impl Error for CastError {}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "cannot convert from {} to {} with the {} casting policy",
+            self.from, self.to, self.policy
+        )
+    }
+}
+
+impl From<CastError> for ReadNpyError {
+    fn from(err: CastError) -> ReadNpyError {
+        ReadNpyError::ParseData(Box::new(err))
+    }
+}
+
+/// What to do with values which are out of the range of the target type
+/// when writing an array with [`write_npy_as`].
+///
+/// Floats are always truncated toward zero when converted to integers, and
+/// complex numbers are converted to real numbers by discarding the imaginary
+/// part; these aren't considered overflow. Rounding to a narrower float type
+/// isn't considered overflow either, but finite values which would become
+/// infinite are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum OverflowPolicy {
+    /// Return an [`OverflowError`] for the first value which is out of range.
+    ///
+    /// The array is checked before anything is written, so the writer is
+    /// left untouched on error. NaN is considered out of range for integer
+    /// types.
+    Error,
+    /// Replace values which are out of range with the closest value of the
+    /// target type. NaN becomes zero for integer types.
+    Saturate,
+    /// Wrap integers around modulo 2<sup>N</sup>, where N is the number of
+    /// bits of the target type, like `as` conversions between integer types
+    /// in Rust and C. For float targets, values which are out of range become
+    /// infinite. NaN and infinity become zero for integer types.
+    Wrap,
+}
+
+/// An error writing an array with [`write_npy_as`], because a value is out
+/// of the range of the target type.
+#[derive(Clone, Debug)]
+pub struct OverflowError {
+    value: String,
+    index: usize,
+    to: &'static str,
+}
+
+impl OverflowError {
+    /// Returns the out-of-range value, formatted as a string.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    /// Returns the index of the value in the array, counting the elements
+    /// in standard (row-major) order.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the NumPy name of the target type, e.g. `"float32"`.
+    pub fn to_type(&self) -> &'static str {
+        self.to
+    }
+}
+
+impl Error for OverflowError {}
+
+impl fmt::Display for OverflowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "value {} at index {} is out of range for {}",
+            self.value, self.index, self.to
+        )
+    }
+}
+
+impl From<OverflowError> for WriteNpyError {
+    fn from(err: OverflowError) -> WriteNpyError {
+        WriteNpyError::FormatData(Box::new(err))
+    }
+}
+
+mod private {
+    use super::OverflowPolicy;
+    use crate::dtype::Kind;
+
+    /// A numeric value of any of the supported types.
+    #[derive(Clone, Copy)]
+    pub enum Value {
+        Int(i128),
+        Float(f64),
+        Complex(f64, f64),
+    }
+
+    impl std::fmt::Display for Value {
+        fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+            match self {
+                Value::Int(x) => write!(f, "{}", x),
+                Value::Float(x) => write!(f, "{:?}", x),
+                Value::Complex(re, im) => write!(f, "({:?}{:+?}j)", re, im),
+            }
+        }
+    }
+
+    /// A type which arrays can be converted to.
+    pub trait CastTarget {
+        /// The kind of the type.
+        const KIND: Kind;
+        /// The size of the type in bytes.
+        const SIZE: usize;
+
+        fn from_value(value: Value) -> Self;
+
+        /// Converts the value, handling values which are out of range
+        /// according to `policy`. Returns `None` if the value is out of range
+        /// and `policy` is [`OverflowPolicy::Error`].
+        fn from_value_checked(value: Value, policy: OverflowPolicy) -> Option<Self>
+        where
+            Self: Sized;
+    }
+
+    /// Conversion to a [`Value`].
+    pub trait ToValue: Copy {
+        fn to_value(self) -> Value;
+    }
+}
+
+use self::private::{CastTarget, ToValue, Value};
+
+/// An element type which arrays can be converted to with [`read_npy_as`],
+/// and converted to and from with [`write_npy_as`].
+///
+/// This is implemented for `bool`, the primitive integer and float types,
+/// `f16` (with the `half` feature), and `Complex<f32>` and `Complex<f64>`
+/// (with the `num-complex-0_4` feature). It can't be implemented outside of
+/// this crate.
+pub trait CastElement: ReadableElement + CastTarget + ToValue {
+    /// The NumPy name of the type, e.g. `"float64"`.
+    const TYPE_NAME: &'static str;
+}
+
+/// Returns the rank of a kind in the ordering used by
+/// [`CastPolicy::SameKind`].
+fn kind_rank(kind: Kind) -> u8 {
+    match kind {
+        Kind::Bool => 0,
+        Kind::UInt => 1,
+        Kind::Int => 2,
+        Kind::Float => 3,
+        Kind::Complex => 4,
+        _ => unreachable!(),
+    }
+}
+
+/// Returns `true` iff every value of the type `(from_kind, from_size)` can be
+/// represented by the type `(to_kind, to_size)`, following NumPy's rules.
+fn can_cast_losslessly(from_kind: Kind, from_size: usize, to_kind: Kind, to_size: usize) -> bool {
+    match (from_kind, to_kind) {
+        (Kind::Bool, _) => true,
+        (Kind::UInt, Kind::UInt) | (Kind::Int, Kind::Int) => to_size >= from_size,
+        (Kind::UInt, Kind::Int) => to_size > from_size,
+        (Kind::UInt | Kind::Int, Kind::Float) => to_size > from_size || to_size >= 8,
+        (Kind::UInt | Kind::Int, Kind::Complex) => to_size / 2 > from_size || to_size / 2 >= 8,
+        (Kind::Float, Kind::Float) | (Kind::Complex, Kind::Complex) => to_size >= from_size,
+        (Kind::Float, Kind::Complex) => to_size / 2 >= from_size,
+        _ => false,
+    }
+}
+
+/// Returns `true` iff the conversion is allowed by `policy`.
+fn can_cast(
+    from_kind: Kind,
+    from_size: usize,
+    to_kind: Kind,
+    to_size: usize,
+    policy: CastPolicy,
+) -> bool {
+    match policy {
+        CastPolicy::Lossless => can_cast_losslessly(from_kind, from_size, to_kind, to_size),
+        CastPolicy::SameKind => {
+            can_cast_losslessly(from_kind, from_size, to_kind, to_size)
+                || kind_rank(to_kind) >= kind_rank(from_kind)
+        }
+        CastPolicy::Unsafe => true,
+    }
+}
+
+/// Returns an error if the conversion from the type `(from, from_kind,
+/// from_size)` to `T` isn't allowed by `policy`.
+fn check_cast<T: CastElement>(
+    from: (&'static str, Kind, usize),
+    policy: CastPolicy,
+) -> Result<(), CastError> {
+    let (from, from_kind, from_size) = from;
+    if can_cast(from_kind, from_size, T::KIND, T::SIZE, policy) {
+        Ok(())
+    } else {
+        Err(CastError {
+            from,
+            to: T::TYPE_NAME,
+            policy,
+        })
+    }
+}
+
+/// Converts an array with element type `S` to element type `T`.
+fn cast_array<S, T>(
+    array: &ArrayD<S>,
+    from: (&'static str, Kind, usize),
+    policy: CastPolicy,
+) -> Result<ArrayD<T>, CastError>
+where
+    S: ToValue,
+    T: CastElement,
+{
+    check_cast::<T>(from, policy)?;
+    Ok(array.mapv(|elem| T::from_value(elem.to_value())))
+}
+
+impl DynNpyArray {
+    /// Converts the array to element type `T`, if the conversion is allowed
+    /// by `policy`.
+    ///
+    /// Arrays of strings can't be converted to any of the numeric types.
+    pub fn cast<T: CastElement>(&self, policy: CastPolicy) -> Result<ArrayD<T>, CastError> {
+        macro_rules! cast {
+            ($array:expr, $kind:expr, $size:expr) => {
+                cast_array($array, (self.type_name(), $kind, $size), policy)
+            };
+        }
+        match self {
+            DynNpyArray::Bool(array) => cast!(array, Kind::Bool, 1),
+            DynNpyArray::I8(array) => cast!(array, Kind::Int, 1),
+            DynNpyArray::I16(array) => cast!(array, Kind::Int, 2),
+            DynNpyArray::I32(array) => cast!(array, Kind::Int, 4),
+            DynNpyArray::I64(array) => cast!(array, Kind::Int, 8),
+            DynNpyArray::U8(array) => cast!(array, Kind::UInt, 1),
+            DynNpyArray::U16(array) => cast!(array, Kind::UInt, 2),
+            DynNpyArray::U32(array) => cast!(array, Kind::UInt, 4),
+            DynNpyArray::U64(array) => cast!(array, Kind::UInt, 8),
+            #[cfg(feature = "half")]
+            DynNpyArray::F16(array) => cast!(array, Kind::Float, 2),
+            DynNpyArray::F32(array) => cast!(array, Kind::Float, 4),
+            DynNpyArray::F64(array) => cast!(array, Kind::Float, 8),
+            #[cfg(feature = "num-complex-0_4")]
+            DynNpyArray::Complex32(array) => cast!(array, Kind::Complex, 8),
+            #[cfg(feature = "num-complex-0_4")]
+            DynNpyArray::Complex64(array) => cast!(array, Kind::Complex, 16),
+            DynNpyArray::Bytes(_) | DynNpyArray::String(_) => Err(CastError {
+                from: self.type_name(),
+                to: T::TYPE_NAME,
+                policy,
+            }),
+        }
+    }
+}
+
+/// Reads `len` elements of type `S` from `reader` and converts them to type
+/// `T` one chunk at a time, so that the elements of type `S` are never all in
+/// memory at once.
+fn read_converted<S, T, R>(
+    mut reader: R,
+    type_desc: &PyValue,
+    len: usize,
+) -> Result<Vec<T>, ReadNpyError>
+where
+    S: CastElement,
+    T: CastElement,
+    R: io::Read,
+{
+    const CHUNK_LEN: usize = 4096;
+    let mut data = Vec::with_capacity(len);
+    while data.len() < len {
+        let chunk_len = CHUNK_LEN.min(len - data.len());
+        let chunk_reader = (&mut reader).take((chunk_len * S::SIZE) as u64);
+        let chunk = S::read_to_end_exact_vec(chunk_reader, type_desc, chunk_len)?;
+        data.extend(chunk.into_iter().map(|elem| T::from_value(elem.to_value())));
+    }
+    let num_extra_bytes = reader.read_to_end(&mut Vec::new())?;
+    if num_extra_bytes != 0 {
+        return Err(ReadNpyError::ExtraBytes(num_extra_bytes));
+    }
+    Ok(data)
+}
+
+/// Reads the data following `header` from `reader`, converting the elements
+/// to type `T` if the conversion is allowed by `policy`.
+pub(crate) fn read_array_data_as<T, R, D>(
+    reader: R,
+    header: &Header,
+    policy: CastPolicy,
+) -> Result<Array<T, D>, ReadNpyError>
+where
+    T: CastElement,
+    R: io::Read,
+    D: Dimension,
+{
+    let data_shape = DataShape::new(header, None);
+    let type_desc = &*data_shape.type_descriptor;
+    let (kind, size) = match DType::from_type_descriptor(type_desc) {
+        Ok(DType::Scalar(scalar)) => (scalar.kind, scalar.item_size),
+        _ => return Err(ReadNpyError::WrongDescriptor(type_desc.clone())),
+    };
+    let ndim = data_shape.shape.ndim();
+    let len = shape_length_checked::<T>(&data_shape.shape).ok_or(ReadNpyError::LengthOverflow)?;
+    macro_rules! read {
+        ($elem:ty) => {{
+            check_cast::<T>((<$elem>::TYPE_NAME, <$elem>::KIND, <$elem>::SIZE), policy)?;
+            read_converted::<$elem, T, R>(reader, type_desc, len)?
+        }};
+    }
+    let data = match (kind, size) {
+        (Kind::Bool, 1) => read!(bool),
+        (Kind::Int, 1) => read!(i8),
+        (Kind::Int, 2) => read!(i16),
+        (Kind::Int, 4) => read!(i32),
+        (Kind::Int, 8) => read!(i64),
+        (Kind::UInt, 1) => read!(u8),
+        (Kind::UInt, 2) => read!(u16),
+        (Kind::UInt, 4) => read!(u32),
+        (Kind::UInt, 8) => read!(u64),
+        #[cfg(feature = "half")]
+        (Kind::Float, 2) => read!(f16),
+        (Kind::Float, 4) => read!(f32),
+        (Kind::Float, 8) => read!(f64),
+        #[cfg(feature = "num-complex-0_4")]
+        (Kind::Complex, 8) => read!(Complex<f32>),
+        #[cfg(feature = "num-complex-0_4")]
+        (Kind::Complex, 16) => read!(Complex<f64>),
+        (Kind::Bytes | Kind::Unicode, _) => {
+            let from = if kind == Kind::Bytes { "bytes" } else { "str" };
+            return Err(CastError {
+                from,
+                to: T::TYPE_NAME,
+                policy,
+            }
+            .into());
+        }
+        _ => return Err(ReadNpyError::WrongDescriptor(type_desc.clone())),
+    };
+    Array::from_shape_vec(data_shape.stride_shape(), data)
+        .unwrap()
+        .into_dimensionality()
+        .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, ndim))
+}
+
+/// Reads an `.npy` file from `reader`, converting the elements to type `T`
+/// if the conversion from the type in the file is allowed by `policy`.
+///
+/// This supports files with the descriptors supported by [`read_npy_dyn`],
+/// except for strings. If the conversion isn't allowed, this returns a
+/// [`ReadNpyError::ParseData`] error containing a [`CastError`], which names
+/// both types. The conversion is checked before reading the data.
+///
+/// The elements are converted one chunk at a time while they're read, so
+/// this doesn't allocate an array of the type in the file in addition to the
+/// converted array.
+///
+/// See the [module-level documentation](self) for an example.
+///
+/// [`read_npy_dyn`]: crate::read_npy_dyn
+pub fn read_npy_as<T, R, D>(mut reader: R, policy: CastPolicy) -> Result<Array<T, D>, ReadNpyError>
+where
+    T: CastElement,
+    R: io::Read,
+    D: Dimension,
+{
+    let header = Header::from_reader(&mut reader)?;
+    read_array_data_as(reader, &header, policy)
+}
+
+/// An array which is converted to element type `T` while it's written.
+pub(crate) struct CastArray<'a, T, A, D> {
+    array: &'a ArrayRef<A, D>,
+    policy: OverflowPolicy,
+    target: PhantomData<T>,
+}
+
+impl<T, A, D> CastArray<'_, T, A, D>
+where
+    T: CastElement + WritableElement,
+    A: CastElement,
+    D: Dimension,
+{
+    /// Returns an error for the first value which is out of range, if the
+    /// policy is [`OverflowPolicy::Error`].
+    ///
+    /// This must be called before writing the array.
+    pub(crate) fn check(&self) -> Result<(), OverflowError> {
+        if self.policy != OverflowPolicy::Error {
+            return Ok(());
+        }
+        for (index, elem) in self.array.iter().enumerate() {
+            let value = elem.to_value();
+            if T::from_value_checked(value, OverflowPolicy::Error).is_none() {
+                return Err(OverflowError {
+                    value: value.to_string(),
+                    index,
+                    to: T::TYPE_NAME,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Converts the elements to type `T` and writes them in chunks.
+fn write_converted<'a, T, A, W>(
+    elems: impl Iterator<Item = &'a A>,
+    policy: OverflowPolicy,
+    writer: &mut W,
+) -> Result<(), WriteDataError>
+where
+    T: CastElement + WritableElement,
+    A: CastElement + 'a,
+    W: io::Write,
+{
+    const CHUNK_LEN: usize = 4096;
+    let mut chunk = Vec::with_capacity(CHUNK_LEN);
+    for elem in elems {
+        chunk.push(
+            T::from_value_checked(elem.to_value(), policy)
+                .expect("values should be checked before writing"),
+        );
+        if chunk.len() == CHUNK_LEN {
+            T::write_slice(&chunk, &mut *writer)?;
+            chunk.clear();
+        }
+    }
+    T::write_slice(&chunk, writer)
+}
+
+impl<T, A, D> WriteNpyExt for CastArray<'_, T, A, D>
+where
+    T: CastElement + WritableElement,
+    A: CastElement,
+    D: Dimension,
+{
+    fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+        write_array_data(
+            self.array,
+            &T::type_descriptor(),
+            writer,
+            |slice, writer| write_converted::<T, A, W>(slice.iter(), self.policy, writer),
+            |elems, writer| write_converted::<T, A, W>(elems, self.policy, writer),
+        )
+    }
+}
+
+/// Returns the array wrapped so that its elements are converted to type `T`
+/// while it's written.
+pub(crate) fn cast_for_writing<T, A, S, D>(
+    array: &ArrayBase<S, D>,
+    policy: OverflowPolicy,
+) -> CastArray<'_, T, A, D>
+where
+    T: CastElement + WritableElement,
+    A: CastElement,
+    S: Data<Elem = A>,
+    D: Dimension,
+{
+    CastArray {
+        array,
+        policy,
+        target: PhantomData::<T>,
+    }
+}
+
+/// Writes an array to `writer` in `.npy` format, converting the elements to
+/// type `T`.
+///
+/// The elements are converted one chunk at a time while they're written, so
+/// this doesn't allocate a converted copy of the array. Values which are out
+/// of the range of `T` are handled according to `policy`; with
+/// [`OverflowPolicy::Error`], this returns a [`WriteNpyError::FormatData`]
+/// error containing an [`OverflowError`], which reports the first
+/// out-of-range value.
+///
+/// See the [module-level documentation](self) for an example.
+pub fn write_npy_as<T>(
+    writer: impl io::Write,
+    array: &ArrayBase<impl Data<Elem = impl CastElement>, impl Dimension>,
+    policy: OverflowPolicy,
+) -> Result<(), WriteNpyError>
+where
+    T: CastElement + WritableElement,
+{
+    let array = cast_for_writing::<T, _, _, _>(array, policy);
+    array.check()?;
+    array.write_npy(writer)
+}
+
+impl ToValue for bool {
+    fn to_value(self) -> Value {
+        Value::Int(self.into())
+    }
+}
+
+impl CastTarget for bool {
+    const KIND: Kind = Kind::Bool;
+    const SIZE: usize = 1;
+
+    fn from_value(value: Value) -> bool {
+        match value {
+            Value::Int(x) => x != 0,
+            Value::Float(x) => x != 0.,
+            Value::Complex(re, im) => re != 0. || im != 0.,
+        }
+    }
+
+    fn from_value_checked(value: Value, _policy: OverflowPolicy) -> Option<bool> {
+        Some(bool::from_value(value))
+    }
+}
+
+/// Truncates a float toward zero for conversion to an integer type,
+/// handling NaN and values which are out of the range of `i128` according to
+/// `policy`.
+fn truncate_float(x: f64, policy: OverflowPolicy) -> Option<i128> {
+    if x.is_nan() || x.abs() >= -(i128::MIN as f64) {
+        match policy {
+            OverflowPolicy::Error => None,
+            // `as` saturates, converting NaN to zero.
+            OverflowPolicy::Saturate => Some(x as i128),
+            // Every float of this magnitude is a multiple of 2^64.
+            OverflowPolicy::Wrap => Some(0),
+        }
+    } else {
+        Some(x as i128)
+    }
+}
+
+impl CastElement for bool {
+    const TYPE_NAME: &'static str = "bool";
+}
+
+macro_rules! impl_cast_int {
+    ($($int:ty, $name:expr, $kind:expr;)*) => {
+        $(
+            impl ToValue for $int {
+                fn to_value(self) -> Value {
+                    Value::Int(self.into())
+                }
+            }
+
+            impl CastTarget for $int {
+                const KIND: Kind = $kind;
+                const SIZE: usize = std::mem::size_of::<$int>();
+
+                fn from_value(value: Value) -> $int {
+                    match value {
+                        Value::Int(x) => x as $int,
+                        Value::Float(x) | Value::Complex(x, _) => x as $int,
+                    }
+                }
+
+                fn from_value_checked(value: Value, policy: OverflowPolicy) -> Option<$int> {
+                    let x = match value {
+                        Value::Int(x) => x,
+                        Value::Float(x) | Value::Complex(x, _) => truncate_float(x, policy)?,
+                    };
+                    match <$int>::try_from(x) {
+                        Ok(x) => Some(x),
+                        Err(_) => match policy {
+                            OverflowPolicy::Error => None,
+                            OverflowPolicy::Saturate => {
+                                Some(if x < 0 { <$int>::MIN } else { <$int>::MAX })
+                            }
+                            OverflowPolicy::Wrap => Some(x as $int),
+                        },
+                    }
+                }
+            }
+
+            impl CastElement for $int {
+                const TYPE_NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+impl_cast_int! {
+    i8, "int8", Kind::Int;
+    i16, "int16", Kind::Int;
+    i32, "int32", Kind::Int;
+    i64, "int64", Kind::Int;
+    u8, "uint8", Kind::UInt;
+    u16, "uint16", Kind::UInt;
+    u32, "uint32", Kind::UInt;
+    u64, "uint64", Kind::UInt;
+}
+
+macro_rules! impl_cast_float {
+    ($($float:ty, $name:expr, $from_f64:expr, $to_f64:expr;)*) => {
+        $(
+            impl ToValue for $float {
+                fn to_value(self) -> Value {
+                    Value::Float($to_f64(self))
+                }
+            }
+
+            impl CastTarget for $float {
+                const KIND: Kind = Kind::Float;
+                const SIZE: usize = std::mem::size_of::<$float>();
+
+                fn from_value(value: Value) -> $float {
+                    match value {
+                        Value::Int(x) => $from_f64(x as f64),
+                        Value::Float(x) | Value::Complex(x, _) => $from_f64(x),
+                    }
+                }
+
+                fn from_value_checked(value: Value, policy: OverflowPolicy) -> Option<$float> {
+                    let x = match value {
+                        Value::Int(x) => x as f64,
+                        Value::Float(x) | Value::Complex(x, _) => x,
+                    };
+                    let converted = <$float>::from_value(Value::Float(x));
+                    if x.is_finite() && $to_f64(converted).is_infinite() {
+                        match policy {
+                            OverflowPolicy::Error => None,
+                            OverflowPolicy::Saturate => {
+                                Some(if x < 0. { <$float>::MIN } else { <$float>::MAX })
+                            }
+                            OverflowPolicy::Wrap => Some(converted),
+                        }
+                    } else {
+                        Some(converted)
+                    }
+                }
+            }
+
+            impl CastElement for $float {
+                const TYPE_NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+impl_cast_float! {
+    f32, "float32", |x| x as f32, f64::from;
+    f64, "float64", |x| x, |x| x;
+}
+
+#[cfg(feature = "half")]
+impl_cast_float! {
+    f16, "float16", f16::from_f64, f16::to_f64;
+}
+
+#[cfg(feature = "num-complex-0_4")]
+macro_rules! impl_cast_complex {
+    ($($float:ty, $name:expr;)*) => {
+        $(
+            impl ToValue for Complex<$float> {
+                fn to_value(self) -> Value {
+                    Value::Complex(self.re.into(), self.im.into())
+                }
+            }
+
+            impl CastTarget for Complex<$float> {
+                const KIND: Kind = Kind::Complex;
+                const SIZE: usize = std::mem::size_of::<Complex<$float>>();
+
+                fn from_value(value: Value) -> Complex<$float> {
+                    match value {
+                        Value::Int(x) => Complex::new(x as $float, 0.),
+                        Value::Float(x) => Complex::new(x as $float, 0.),
+                        Value::Complex(re, im) => Complex::new(re as $float, im as $float),
+                    }
+                }
+
+                fn from_value_checked(
+                    value: Value,
+                    policy: OverflowPolicy,
+                ) -> Option<Complex<$float>> {
+                    let (re, im) = match value {
+                        Value::Int(x) => (x as f64, 0.),
+                        Value::Float(x) => (x, 0.),
+                        Value::Complex(re, im) => (re, im),
+                    };
+                    Some(Complex::new(
+                        <$float>::from_value_checked(Value::Float(re), policy)?,
+                        <$float>::from_value_checked(Value::Float(im), policy)?,
+                    ))
+                }
+            }
+
+            impl CastElement for Complex<$float> {
+                const TYPE_NAME: &'static str = $name;
+            }
+        )*
+    };
+}
+
+#[cfg(feature = "num-complex-0_4")]
+impl_cast_complex! {
+    f32, "complex64";
+    f64, "complex128";
+}
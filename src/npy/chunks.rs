@@ -0,0 +1,203 @@
+//! Reading an `.npy` file in chunks along its outermost axis.
+
+use super::header::Header;
+use super::raw::{item_size, read_data_len};
+use super::DataShape;
+use crate::{ReadNpyError, ReadableElement};
+use ndarray::{Array, Axis, Dimension};
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::marker::PhantomData;
+use std::path::Path;
+
+/// An iterator which reads an `.npy` file in chunks of up to a given number of
+/// rows, for processing files which are too large to fit in memory.
+///
+/// The chunks are taken along the outermost axis of the data, i.e. the axis
+/// with the largest stride, so that each chunk is a contiguous range of the
+/// file and the reader is only read sequentially. That's the first axis for
+/// standard layout, and the last axis of the header's shape for Fortran
+/// layout; see [`.axis()`](NpyChunks::axis). Each item is an owned array,
+/// decoded like [`ReadNpyExt::read_npy`](crate::ReadNpyExt::read_npy), with
+/// the chunk axis shortened to at most the chunk length.
+///
+/// The data must be exactly as long as described by the header. If it's too
+/// short, the chunk which is missing data is a
+/// [`ReadNpyError::MissingData`] error. Trailing bytes are detected once all
+/// of the chunks have been read, so the item after the last chunk is a
+/// [`ReadNpyError::ExtraBytes`] error in that case. After an error, the
+/// iterator returns `None`.
+///
+/// This requires the size of an element to be determined from the type
+/// descriptor, so it isn't supported for object arrays.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::{array, Array2, Axis};
+/// use ndarray_npy::{NpyChunks, WriteNpyExt};
+///
+/// let mut buf = Vec::new();
+/// array![[1i64, 2], [3, 4], [5, 6], [7, 8], [9, 10]].write_npy(&mut buf)?;
+///
+/// let chunks = NpyChunks::<i64, _, _>::new(&buf[..], 2)?;
+/// assert_eq!(chunks.axis(), Some(Axis(0)));
+/// let mut sums = Vec::new();
+/// for chunk in chunks {
+///     let chunk: Array2<i64> = chunk?;
+///     sums.push(chunk.sum());
+/// }
+/// assert_eq!(sums, [10, 26, 19]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct NpyChunks<A, D, R> {
+    reader: R,
+    header: Header,
+    /// Size of an element in bytes.
+    item_size: usize,
+    /// Axis of the data along which the chunks are taken, if any.
+    axis: Option<usize>,
+    /// Length of the data along `axis`, or 1 if there are no axes.
+    axis_len: usize,
+    /// Maximum length of each chunk along `axis`.
+    chunk_len: usize,
+    /// Index along `axis` of the start of the next chunk.
+    position: usize,
+    /// Whether the iterator has finished, either by checking for trailing
+    /// bytes or because of an error.
+    done: bool,
+    elem: PhantomData<fn() -> Array<A, D>>,
+}
+
+impl<A, D> NpyChunks<A, D, BufReader<File>>
+where
+    A: ReadableElement,
+    D: Dimension,
+{
+    /// Opens the `.npy` file at the specified path and parses its header, for
+    /// reading it in chunks of up to `chunk_len` rows.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is zero.
+    pub fn open<P: AsRef<Path>>(path: P, chunk_len: usize) -> Result<Self, ReadNpyError> {
+        NpyChunks::new(BufReader::new(File::open(path)?), chunk_len)
+    }
+}
+
+impl<A, D, R> NpyChunks<A, D, R>
+where
+    A: ReadableElement,
+    D: Dimension,
+    R: Read,
+{
+    /// Parses the header of the `.npy` file starting at the current position
+    /// of `reader`, for reading it in chunks of up to `chunk_len` rows.
+    ///
+    /// This checks the number of dimensions and the type descriptor, so the
+    /// chunks can only fail because of I/O errors or invalid data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk_len` is zero.
+    pub fn new(mut reader: R, chunk_len: usize) -> Result<Self, ReadNpyError> {
+        assert_ne!(chunk_len, 0, "chunk length must be nonzero");
+        let header = Header::from_reader(&mut reader)?;
+        read_data_len(&header)?;
+        let data_shape = DataShape::new(&header, D::NDIM);
+        // The descriptor is sized, since `read_data_len` succeeded.
+        let item_size = item_size(&data_shape.type_descriptor).unwrap();
+        let ndim = data_shape.shape.ndim();
+        if D::NDIM.is_some_and(|n| n != ndim) {
+            return Err(ReadNpyError::WrongNdim(D::NDIM, ndim));
+        }
+        // Reading zero elements checks the type descriptor.
+        A::read_to_end_exact_vec(io::empty(), &data_shape.type_descriptor, 0)?;
+        let axis = data_shape.memory_order().last().copied();
+        let axis_len = axis.map_or(1, |axis| data_shape.shape[axis]);
+        Ok(NpyChunks {
+            reader,
+            header,
+            item_size,
+            axis,
+            axis_len,
+            chunk_len,
+            position: 0,
+            done: false,
+            elem: PhantomData,
+        })
+    }
+
+    /// Returns the header of the file.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the axis along which the chunks are taken, or `None` for a
+    /// zero-dimensional array, which is read as a single chunk.
+    ///
+    /// This is `Axis(0)` for standard layout. For Fortran layout, it's the
+    /// last axis of the header's shape, which is before the axes of the
+    /// subarray shape, if any.
+    pub fn axis(&self) -> Option<Axis> {
+        self.axis.map(Axis)
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the underlying reader, positioned after the data that has
+    /// been read.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the chunk of length `len` along the chunk axis, starting at the
+    /// current position.
+    fn read_chunk(&mut self, len: usize) -> Result<Array<A, D>, ReadNpyError> {
+        let mut data_shape = DataShape::new(&self.header, D::NDIM);
+        if let Some(axis) = self.axis {
+            data_shape.shape[axis] = len;
+        }
+        let ndim = data_shape.shape.ndim();
+        // The length of the whole array was checked in `new`.
+        let num_elems = data_shape.shape.size();
+        let reader = (&mut self.reader).take((num_elems * self.item_size) as u64);
+        let data = A::read_to_end_exact_vec(reader, &data_shape.type_descriptor, num_elems)?;
+        Array::from_shape_vec(data_shape.stride_shape(), data)
+            .unwrap()
+            .into_dimensionality()
+            .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, ndim))
+    }
+}
+
+impl<A, D, R> Iterator for NpyChunks<A, D, R>
+where
+    A: ReadableElement,
+    D: Dimension,
+    R: Read,
+{
+    type Item = Result<Array<A, D>, ReadNpyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.position >= self.axis_len {
+            self.done = true;
+            return match self.reader.read_to_end(&mut Vec::new()) {
+                Ok(0) => None,
+                Ok(num_extra_bytes) => Some(Err(ReadNpyError::ExtraBytes(num_extra_bytes))),
+                Err(err) => Some(Err(err.into())),
+            };
+        }
+        let len = self.chunk_len.min(self.axis_len - self.position);
+        self.position += len;
+        let chunk = self.read_chunk(len);
+        self.done = chunk.is_err();
+        Some(chunk)
+    }
+}
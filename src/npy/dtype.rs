@@ -0,0 +1,558 @@
+//! A typed model of NumPy dtypes.
+//!
+//! The `descr` field of an `.npy` header is a Python literal which can be
+//! passed to the `numpy.dtype` constructor, and there are many equivalent
+//! spellings of the same dtype: `'<f8'`, `'=f8'` (on little-endian
+//! machines), `'f8'`, `'d'`, and `'float64'` all describe little-endian
+//! 64-bit floats. [`DType`] parses all of these into the same value, and
+//! formats it with the canonical spelling NumPy uses when writing `.npy`
+//! files, e.g. `'<f8'`.
+//!
+//! When an `.npy` header is read, its descriptor is normalized to the
+//! canonical spelling (if it can be parsed as a [`DType`]), so the element
+//! types accept every spelling of the descriptors they support.
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray_npy::dtype::{ByteOrder, DType, Kind, ScalarDType};
+//! use py_literal::Value as PyValue;
+//!
+//! let dtype = DType::from_type_descriptor(&PyValue::String("float32".into()))?;
+//! assert_eq!(dtype.item_size(), Some(4));
+//! if let DType::Scalar(ScalarDType { kind, byte_order, .. }) = &dtype {
+//!     assert_eq!(*kind, Kind::Float);
+//!     assert_eq!(*byte_order, ByteOrder::native());
+//! }
+//! # #[cfg(target_endian = "little")]
+//! assert_eq!(dtype.to_type_descriptor(), PyValue::String("<f4".into()));
+//! # Ok::<_, ndarray_npy::dtype::ParseDTypeError>(())
+//! ```
+
+use crate::datetime::TimeUnit;
+use num_traits::ToPrimitive;
+use py_literal::Value as PyValue;
+use std::error::Error;
+use std::fmt;
+use std::mem;
+use std::os::raw::{c_int, c_long};
+
+/// The byte order of a scalar dtype.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ByteOrder {
+    /// Little-endian (`'<'`).
+    Little,
+    /// Big-endian (`'>'`).
+    Big,
+    /// The byte order is not relevant, e.g. for one-byte types and byte
+    /// strings (`'|'`).
+    NotApplicable,
+}
+
+impl ByteOrder {
+    /// Returns the byte order of the target platform.
+    pub fn native() -> ByteOrder {
+        if cfg!(target_endian = "little") {
+            ByteOrder::Little
+        } else {
+            ByteOrder::Big
+        }
+    }
+
+    /// Returns the character NumPy uses for the byte order in descriptors.
+    pub fn to_char(self) -> char {
+        match self {
+            ByteOrder::Little => '<',
+            ByteOrder::Big => '>',
+            ByteOrder::NotApplicable => '|',
+        }
+    }
+}
+
+/// The kind of a scalar dtype.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Kind {
+    /// Boolean (`'b'`).
+    Bool,
+    /// Signed integer (`'i'`).
+    Int,
+    /// Unsigned integer (`'u'`).
+    UInt,
+    /// Floating point (`'f'`).
+    Float,
+    /// Complex floating point (`'c'`).
+    Complex,
+    /// Byte string (`'S'`).
+    Bytes,
+    /// Unicode string (`'U'`).
+    Unicode,
+    /// Void, i.e. opaque bytes (`'V'`).
+    Void,
+    /// `datetime64` (`'M'`).
+    DateTime,
+    /// `timedelta64` (`'m'`).
+    TimeDelta,
+    /// Python object (`'O'`).
+    Object,
+}
+
+impl Kind {
+    /// Returns the character NumPy uses for the kind in descriptors.
+    pub fn to_char(self) -> char {
+        match self {
+            Kind::Bool => 'b',
+            Kind::Int => 'i',
+            Kind::UInt => 'u',
+            Kind::Float => 'f',
+            Kind::Complex => 'c',
+            Kind::Bytes => 'S',
+            Kind::Unicode => 'U',
+            Kind::Void => 'V',
+            Kind::DateTime => 'M',
+            Kind::TimeDelta => 'm',
+            Kind::Object => 'O',
+        }
+    }
+
+    /// Parses the kind character of a descriptor, e.g. the `'f'` in `'<f8'`.
+    /// `'a'` is accepted as an alias of `'S'`.
+    fn from_char(c: char) -> Option<Kind> {
+        Some(match c {
+            'b' => Kind::Bool,
+            'i' => Kind::Int,
+            'u' => Kind::UInt,
+            'f' => Kind::Float,
+            'c' => Kind::Complex,
+            'S' | 'a' => Kind::Bytes,
+            'U' => Kind::Unicode,
+            'V' => Kind::Void,
+            'M' => Kind::DateTime,
+            'm' => Kind::TimeDelta,
+            'O' => Kind::Object,
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` iff `item_size` is a valid size in bytes for this kind.
+    fn is_valid_item_size(self, item_size: usize) -> bool {
+        match self {
+            Kind::Bool => item_size == 1,
+            Kind::Int | Kind::UInt => matches!(item_size, 1 | 2 | 4 | 8),
+            Kind::Float => matches!(item_size, 2 | 4 | 8 | 12 | 16),
+            Kind::Complex => matches!(item_size, 8 | 16 | 24 | 32),
+            Kind::Bytes | Kind::Void => true,
+            Kind::Unicode => item_size % 4 == 0,
+            Kind::DateTime | Kind::TimeDelta => item_size == 8,
+            Kind::Object => item_size == mem::size_of::<usize>(),
+        }
+    }
+
+    /// Returns `true` iff values of this kind with the given size have a
+    /// byte order.
+    fn has_byte_order(self, item_size: usize) -> bool {
+        match self {
+            Kind::Int | Kind::UInt | Kind::Float | Kind::Complex => item_size > 1,
+            Kind::Unicode | Kind::DateTime | Kind::TimeDelta => true,
+            Kind::Bool | Kind::Bytes | Kind::Void | Kind::Object => false,
+        }
+    }
+}
+
+/// A scalar (i.e. not subarray or structured) dtype, e.g. `'<f8'`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ScalarDType {
+    /// The byte order.
+    ///
+    /// This is [`ByteOrder::NotApplicable`] for types without a byte order,
+    /// except that void types keep an explicit byte order, since some
+    /// packages (e.g. `ml_dtypes`) use it to describe the contents.
+    pub byte_order: ByteOrder,
+    /// The kind of type.
+    pub kind: Kind,
+    /// The size of each value in bytes. For unicode strings, this is four
+    /// times the number of characters.
+    pub item_size: usize,
+    /// The unit of `datetime64` and `timedelta64` types, or `None` for other
+    /// types and for generic `datetime64` and `timedelta64` types.
+    pub time_unit: Option<TimeUnit>,
+}
+
+/// A field of a structured dtype.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Field {
+    /// The name of the field. Fields with an empty name are padding.
+    pub name: String,
+    /// The title of the field, if any.
+    pub title: Option<String>,
+    /// The dtype of the field.
+    pub dtype: DType,
+}
+
+/// A parsed NumPy dtype.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum DType {
+    /// A scalar dtype, e.g. `'<f8'`.
+    Scalar(ScalarDType),
+    /// A subarray dtype, e.g. `('<f8', (2, 3))`.
+    ///
+    /// Nested subarray dtypes are flattened, and subarray dtypes with an
+    /// empty shape are parsed as their base dtype, like NumPy does.
+    Subarray {
+        /// The dtype of the items of the subarray.
+        base: Box<DType>,
+        /// The shape of the subarray.
+        shape: Vec<usize>,
+    },
+    /// A structured dtype with a list-form descriptor, e.g. `[('x', '<f4'),
+    /// ('y', '<f4')]`. The fields are stored one after another, in order.
+    Structured(Vec<Field>),
+}
+
+impl DType {
+    /// Parses a type descriptor, accepting all of the spellings NumPy
+    /// accepts for the supported dtypes.
+    ///
+    /// This supports the following forms of descriptors:
+    ///
+    /// * Scalar descriptors with an optional byte order character (`<`, `>`,
+    ///   `=`, `|`, or `!`), a kind character, and a size, e.g. `'<f8'`,
+    ///   `'f8'`, `'|S16'`, `'a16'`, or `'<M8[ns]'`.
+    /// * Single-character type codes with an optional byte order character,
+    ///   e.g. `'d'`, `'>i'`, or `'?'`.
+    /// * Type names, e.g. `'float64'`, `'double'`, `'uint8'`, or
+    ///   `'datetime64[ns]'`.
+    /// * Subarray descriptors, e.g. `('<f4', (3,))` or `('<f4', 3)`.
+    /// * List-form structured descriptors, e.g. `[('x', '<f4'), ('pos',
+    ///   '<f8', (3,))]`, where each name may also be a `(title, name)`
+    ///   tuple.
+    pub fn from_type_descriptor(type_desc: &PyValue) -> Result<DType, ParseDTypeError> {
+        parse_dtype(type_desc).ok_or_else(|| ParseDTypeError {
+            type_desc: type_desc.clone(),
+        })
+    }
+
+    /// Formats the dtype as a type descriptor, using the canonical spelling
+    /// NumPy uses when writing `.npy` files, e.g. `'<f8'`, `'|b1'`, or
+    /// `('<f4', (3,))`.
+    pub fn to_type_descriptor(&self) -> PyValue {
+        match self {
+            DType::Scalar(scalar) => PyValue::String(scalar.to_string()),
+            DType::Subarray { base, shape } => {
+                PyValue::Tuple(vec![base.to_type_descriptor(), shape_tuple(shape)])
+            }
+            DType::Structured(fields) => PyValue::List(
+                fields
+                    .iter()
+                    .map(|field| {
+                        let name = match &field.title {
+                            Some(title) => PyValue::Tuple(vec![
+                                PyValue::String(title.clone()),
+                                PyValue::String(field.name.clone()),
+                            ]),
+                            None => PyValue::String(field.name.clone()),
+                        };
+                        PyValue::Tuple(match &field.dtype {
+                            DType::Subarray { base, shape } => {
+                                vec![name, base.to_type_descriptor(), shape_tuple(shape)]
+                            }
+                            dtype => vec![name, dtype.to_type_descriptor()],
+                        })
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Returns the size of each element in bytes, or `None` if it overflows
+    /// `usize`.
+    pub fn item_size(&self) -> Option<usize> {
+        match self {
+            DType::Scalar(scalar) => Some(scalar.item_size),
+            DType::Subarray { base, shape } => shape
+                .iter()
+                .try_fold(base.item_size()?, |size, &n| size.checked_mul(n)),
+            DType::Structured(fields) => fields.iter().try_fold(0usize, |size, field| {
+                size.checked_add(field.dtype.item_size()?)
+            }),
+        }
+    }
+}
+
+impl fmt::Display for ScalarDType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let size = match self.kind {
+            Kind::Unicode => self.item_size / 4,
+            _ => self.item_size,
+        };
+        write!(f, "{}{}", self.byte_order.to_char(), self.kind.to_char())?;
+        match (self.kind, self.time_unit) {
+            (Kind::Object, _) => Ok(()),
+            (_, Some(unit)) => write!(f, "{}[{}]", size, unit),
+            (_, None) => write!(f, "{}", size),
+        }
+    }
+}
+
+impl TryFrom<&PyValue> for DType {
+    type Error = ParseDTypeError;
+
+    fn try_from(type_desc: &PyValue) -> Result<DType, ParseDTypeError> {
+        DType::from_type_descriptor(type_desc)
+    }
+}
+
+impl From<&DType> for PyValue {
+    fn from(dtype: &DType) -> PyValue {
+        dtype.to_type_descriptor()
+    }
+}
+
+impl From<DType> for PyValue {
+    fn from(dtype: DType) -> PyValue {
+        dtype.to_type_descriptor()
+    }
+}
+
+/// An error parsing a type descriptor as a [`DType`].
+#[derive(Debug)]
+pub struct ParseDTypeError {
+    type_desc: PyValue,
+}
+
+impl Error for ParseDTypeError {}
+
+impl fmt::Display for ParseDTypeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "unsupported or invalid type descriptor {}",
+            self.type_desc
+        )
+    }
+}
+
+/// Normalizes a type descriptor to its canonical spelling, if it can be
+/// parsed as a [`DType`]. Otherwise, returns it unchanged.
+pub(crate) fn normalize_type_descriptor(type_desc: PyValue) -> PyValue {
+    match parse_dtype(&type_desc) {
+        Some(dtype) => dtype.to_type_descriptor(),
+        None => type_desc,
+    }
+}
+
+/// Formats a subarray shape as a Python tuple.
+fn shape_tuple(shape: &[usize]) -> PyValue {
+    PyValue::Tuple(shape.iter().map(|&n| PyValue::Integer(n.into())).collect())
+}
+
+fn parse_dtype(type_desc: &PyValue) -> Option<DType> {
+    match type_desc {
+        PyValue::String(s) => parse_scalar(s).map(DType::Scalar),
+        PyValue::Tuple(parts) => match &parts[..] {
+            [base, shape] => subarray(parse_dtype(base)?, parse_shape(shape)?),
+            _ => None,
+        },
+        PyValue::List(entries) => entries
+            .iter()
+            .map(parse_field)
+            .collect::<Option<_>>()
+            .map(DType::Structured),
+        _ => None,
+    }
+}
+
+/// Creates a subarray dtype, flattening nested subarrays like NumPy does.
+fn subarray(base: DType, mut shape: Vec<usize>) -> Option<DType> {
+    let base = match base {
+        DType::Subarray {
+            base,
+            shape: inner_shape,
+        } => {
+            shape.extend(inner_shape);
+            *base
+        }
+        base => base,
+    };
+    let dtype = if shape.is_empty() {
+        base
+    } else {
+        DType::Subarray {
+            base: Box::new(base),
+            shape,
+        }
+    };
+    // Check that the size doesn't overflow.
+    dtype.item_size()?;
+    Some(dtype)
+}
+
+/// Parses a subarray shape, which is either an integer or a tuple of
+/// integers.
+fn parse_shape(shape: &PyValue) -> Option<Vec<usize>> {
+    match shape {
+        PyValue::Integer(n) => Some(vec![n.to_usize()?]),
+        PyValue::Tuple(axes) => axes.iter().map(|n| n.as_integer()?.to_usize()).collect(),
+        _ => None,
+    }
+}
+
+/// Parses an entry of a list-form structured descriptor.
+fn parse_field(entry: &PyValue) -> Option<Field> {
+    let parts = match entry {
+        PyValue::Tuple(parts) => parts,
+        _ => return None,
+    };
+    let (name, dtype) = match &parts[..] {
+        [name, type_desc] => (name, parse_dtype(type_desc)?),
+        [name, base, shape] => (name, subarray(parse_dtype(base)?, parse_shape(shape)?)?),
+        _ => return None,
+    };
+    let (title, name) = match name {
+        PyValue::String(name) => (None, name.clone()),
+        PyValue::Tuple(parts) => match &parts[..] {
+            [PyValue::String(title), PyValue::String(name)] => (Some(title.clone()), name.clone()),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    Some(Field { name, title, dtype })
+}
+
+/// Parses a scalar descriptor, a type code, or a type name.
+fn parse_scalar(s: &str) -> Option<ScalarDType> {
+    if let Some(scalar) = parse_type_name(s) {
+        return Some(scalar);
+    }
+    let (order, rest) = match s.chars().next()? {
+        order @ ('<' | '>' | '=' | '|' | '!') => (Some(order), &s[1..]),
+        _ => (None, s),
+    };
+    let (kind, item_size, time_unit) = match type_code(rest) {
+        Some((kind, item_size)) => (kind, item_size, None),
+        None => {
+            let mut chars = rest.chars();
+            let kind = Kind::from_char(chars.next()?)?;
+            let rest = chars.as_str();
+            let (size, time_unit) = match kind {
+                Kind::DateTime | Kind::TimeDelta => match rest.split_once('[') {
+                    Some((size, unit)) => {
+                        (size, Some(TimeUnit::from_abbrev(unit.strip_suffix(']')?)?))
+                    }
+                    None => (rest, None),
+                },
+                _ => (rest, None),
+            };
+            let item_size = match (kind, size) {
+                (Kind::DateTime | Kind::TimeDelta, "") => 8,
+                (Kind::Object, "") => mem::size_of::<usize>(),
+                _ if size.is_empty() || !size.bytes().all(|b| b.is_ascii_digit()) => return None,
+                (Kind::Unicode, _) => size.parse::<usize>().ok()?.checked_mul(4)?,
+                _ => size.parse().ok()?,
+            };
+            (kind, item_size, time_unit)
+        }
+    };
+    scalar(order, kind, item_size, time_unit)
+}
+
+/// Creates a scalar dtype with the given byte order character (if any),
+/// resolving it to the canonical byte order.
+fn scalar(
+    order: Option<char>,
+    kind: Kind,
+    item_size: usize,
+    time_unit: Option<TimeUnit>,
+) -> Option<ScalarDType> {
+    if !kind.is_valid_item_size(item_size) {
+        return None;
+    }
+    let byte_order = if kind.has_byte_order(item_size) {
+        match order {
+            Some('<') => ByteOrder::Little,
+            Some('>' | '!') => ByteOrder::Big,
+            _ => ByteOrder::native(),
+        }
+    } else {
+        match (kind, order) {
+            (Kind::Void, Some('<')) => ByteOrder::Little,
+            (Kind::Void, Some('>' | '!')) => ByteOrder::Big,
+            _ => ByteOrder::NotApplicable,
+        }
+    };
+    Some(ScalarDType {
+        byte_order,
+        kind,
+        item_size,
+        time_unit,
+    })
+}
+
+/// Parses a single-character type code, e.g. `'d'`, into its kind and size.
+fn type_code(code: &str) -> Option<(Kind, usize)> {
+    Some(match code {
+        "?" => (Kind::Bool, 1),
+        "b" => (Kind::Int, 1),
+        "B" => (Kind::UInt, 1),
+        "h" => (Kind::Int, 2),
+        "H" => (Kind::UInt, 2),
+        "i" => (Kind::Int, mem::size_of::<c_int>()),
+        "I" => (Kind::UInt, mem::size_of::<c_int>()),
+        "l" => (Kind::Int, mem::size_of::<c_long>()),
+        "L" => (Kind::UInt, mem::size_of::<c_long>()),
+        "q" => (Kind::Int, 8),
+        "Q" => (Kind::UInt, 8),
+        "p" => (Kind::Int, mem::size_of::<isize>()),
+        "P" => (Kind::UInt, mem::size_of::<usize>()),
+        "e" => (Kind::Float, 2),
+        "f" => (Kind::Float, 4),
+        "d" => (Kind::Float, 8),
+        "F" => (Kind::Complex, 8),
+        "D" => (Kind::Complex, 16),
+        _ => return None,
+    })
+}
+
+/// Parses a type name, e.g. `'float64'`, which has the native byte order.
+fn parse_type_name(name: &str) -> Option<ScalarDType> {
+    let (kind, item_size) = match name {
+        "bool" | "bool_" => (Kind::Bool, 1),
+        "int8" | "byte" => (Kind::Int, 1),
+        "uint8" | "ubyte" => (Kind::UInt, 1),
+        "int16" | "short" => (Kind::Int, 2),
+        "uint16" | "ushort" => (Kind::UInt, 2),
+        "int32" | "intc" => (Kind::Int, 4),
+        "uint32" | "uintc" => (Kind::UInt, 4),
+        "int64" | "longlong" => (Kind::Int, 8),
+        "uint64" | "ulonglong" => (Kind::UInt, 8),
+        "float16" | "half" => (Kind::Float, 2),
+        "float32" | "single" => (Kind::Float, 4),
+        "float64" | "double" | "float" => (Kind::Float, 8),
+        "complex64" | "csingle" => (Kind::Complex, 8),
+        "complex128" | "cdouble" | "complex" => (Kind::Complex, 16),
+        "int" | "int_" | "intp" => (Kind::Int, mem::size_of::<isize>()),
+        "uint" | "uintp" => (Kind::UInt, mem::size_of::<usize>()),
+        "str" | "str_" | "unicode" => (Kind::Unicode, 0),
+        "bytes" | "bytes_" => (Kind::Bytes, 0),
+        "object" | "object_" => (Kind::Object, mem::size_of::<usize>()),
+        _ => {
+            let (kind, unit) = if let Some(unit) = name.strip_prefix("datetime64") {
+                (Kind::DateTime, unit)
+            } else if let Some(unit) = name.strip_prefix("timedelta64") {
+                (Kind::TimeDelta, unit)
+            } else {
+                return None;
+            };
+            let time_unit = match unit {
+                "" => None,
+                unit => Some(TimeUnit::from_abbrev(
+                    unit.strip_prefix('[')?.strip_suffix(']')?,
+                )?),
+            };
+            return scalar(None, kind, 8, time_unit);
+        }
+    };
+    scalar(None, kind, item_size, None)
+}
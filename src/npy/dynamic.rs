@@ -0,0 +1,239 @@
+//! Arrays whose element type is determined at runtime.
+
+use super::dtype::{DType, Kind};
+use super::header::Header;
+use super::{read_array_data, DataShape};
+use crate::{ReadNpyError, ReadableElement, WritableElement, WriteNpyError, WriteNpyExt};
+#[cfg(feature = "half")]
+use half::f16;
+use ndarray::ArrayD;
+#[cfg(feature = "num-complex-0_4")]
+use num_complex_0_4::Complex;
+use std::fmt;
+use std::io;
+
+/// Generic code which can be run on any variant of [`DynNpyArray`].
+///
+/// Closures can't be generic over the element type, so generic code is
+/// written as an implementation of this trait instead, and passed to
+/// [`DynNpyArray::visit`].
+///
+/// # Example
+///
+/// ```
+/// use ndarray::ArrayD;
+/// use ndarray_npy::{DynNpyArray, DynNpyArrayVisitor, ReadableElement, WritableElement};
+/// use std::fmt::Debug;
+///
+/// /// Formats the first element of an array, if any.
+/// struct First;
+///
+/// impl DynNpyArrayVisitor for First {
+///     type Output = Option<String>;
+///
+///     fn visit<A>(self, array: &ArrayD<A>) -> Option<String>
+///     where
+///         A: WritableElement + ReadableElement + Clone + Debug + PartialEq,
+///     {
+///         array.iter().next().map(|elem| format!("{:?}", elem))
+///     }
+/// }
+///
+/// let arr = DynNpyArray::from(ndarray::arr1(&[3i64, 4]).into_dyn());
+/// assert_eq!(arr.visit(First), Some("3".to_string()));
+/// ```
+pub trait DynNpyArrayVisitor {
+    /// The result of the visitor.
+    type Output;
+
+    /// Runs the visitor on the array of a [`DynNpyArray`].
+    fn visit<A>(self, array: &ArrayD<A>) -> Self::Output
+    where
+        A: WritableElement + ReadableElement + Clone + fmt::Debug + PartialEq;
+}
+
+macro_rules! impl_dyn_npy_array {
+    ($($(#[cfg($cfg:meta)])* $variant:ident($elem:ty, $name:literal),)*) => {
+        /// An array read from an `.npy` file, with an element type determined
+        /// by the descriptor in the file's header.
+        ///
+        /// This is useful for reading files whose element type isn't known at
+        /// compile time; see [`read_npy_dyn`]. The array can be written back
+        /// with [`WriteNpyExt`], and generic code can be run on any variant
+        /// with [`visit`](Self::visit).
+        ///
+        /// The variants for `f16` and complex numbers are available only with
+        /// the `half` and `num-complex-0_4` features, respectively. Since
+        /// more variants may be added in the future, this enum is marked
+        /// `#[non_exhaustive]`.
+        #[derive(Clone, Debug, PartialEq)]
+        #[non_exhaustive]
+        pub enum DynNpyArray {
+            $(
+                $(#[cfg($cfg)])*
+                #[doc = concat!("An array of `", stringify!($elem), "`.")]
+                $variant(ArrayD<$elem>),
+            )*
+        }
+
+        impl DynNpyArray {
+            /// Returns the name of the element type, using NumPy's names, e.g.
+            /// `"float64"` or `"complex64"`.
+            pub fn type_name(&self) -> &'static str {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])*
+                        DynNpyArray::$variant(_) => $name,
+                    )*
+                }
+            }
+
+            /// Returns the shape of the array.
+            pub fn shape(&self) -> &[usize] {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])*
+                        DynNpyArray::$variant(array) => array.shape(),
+                    )*
+                }
+            }
+
+            /// Runs `visitor` on the array.
+            pub fn visit<V: DynNpyArrayVisitor>(&self, visitor: V) -> V::Output {
+                match self {
+                    $(
+                        $(#[cfg($cfg)])*
+                        DynNpyArray::$variant(array) => visitor.visit(array),
+                    )*
+                }
+            }
+        }
+
+        $(
+            $(#[cfg($cfg)])*
+            impl From<ArrayD<$elem>> for DynNpyArray {
+                fn from(array: ArrayD<$elem>) -> DynNpyArray {
+                    DynNpyArray::$variant(array)
+                }
+            }
+        )*
+    };
+}
+
+impl_dyn_npy_array! {
+    Bool(bool, "bool"),
+    I8(i8, "int8"),
+    I16(i16, "int16"),
+    I32(i32, "int32"),
+    I64(i64, "int64"),
+    U8(u8, "uint8"),
+    U16(u16, "uint16"),
+    U32(u32, "uint32"),
+    U64(u64, "uint64"),
+    #[cfg(feature = "half")]
+    F16(f16, "float16"),
+    F32(f32, "float32"),
+    F64(f64, "float64"),
+    #[cfg(feature = "num-complex-0_4")]
+    Complex32(Complex<f32>, "complex64"),
+    #[cfg(feature = "num-complex-0_4")]
+    Complex64(Complex<f64>, "complex128"),
+    Bytes(Vec<u8>, "bytes"),
+    String(String, "str"),
+}
+
+impl DynNpyArray {
+    /// Returns the number of axes of the array.
+    pub fn ndim(&self) -> usize {
+        self.shape().len()
+    }
+
+    /// Returns the number of elements in the array.
+    pub fn len(&self) -> usize {
+        self.shape().iter().product()
+    }
+
+    /// Returns `true` iff the array has no elements.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl WriteNpyExt for DynNpyArray {
+    fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+        struct Write<W>(W);
+        impl<W: io::Write> DynNpyArrayVisitor for Write<W> {
+            type Output = Result<(), WriteNpyError>;
+            fn visit<A>(self, array: &ArrayD<A>) -> Self::Output
+            where
+                A: WritableElement,
+            {
+                array.write_npy(self.0)
+            }
+        }
+        self.visit(Write(writer))
+    }
+}
+
+/// Reads an `.npy` file from `reader`, choosing the element type of the
+/// array based on the descriptor in the file's header.
+///
+/// This supports the descriptors of the types of the variants of
+/// [`DynNpyArray`], in either byte order. Subarray descriptors are supported
+/// as well; the subarray shape is appended to the shape of the array. For
+/// other descriptors, this returns [`ReadNpyError::WrongDescriptor`].
+///
+/// # Example
+///
+/// ```
+/// use ndarray_npy::{read_npy_dyn, DynNpyArray};
+/// use std::fs::File;
+///
+/// let arr = read_npy_dyn(File::open("resources/array.npy")?)?;
+/// assert_eq!(arr.type_name(), "int32");
+/// assert_eq!(arr.shape(), [2, 3]);
+/// if let DynNpyArray::I32(arr) = arr {
+///     println!("sum = {}", arr.sum());
+/// }
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn read_npy_dyn<R: io::Read>(mut reader: R) -> Result<DynNpyArray, ReadNpyError> {
+    let header = Header::from_reader(&mut reader)?;
+    read_dyn_array_data(reader, &header)
+}
+
+/// Reads the data of an `.npy` file with the given header, choosing the
+/// element type based on the descriptor.
+pub(crate) fn read_dyn_array_data<R: io::Read>(
+    reader: R,
+    header: &Header,
+) -> Result<DynNpyArray, ReadNpyError> {
+    let data_shape = DataShape::new(header, None);
+    let type_desc = &*data_shape.type_descriptor;
+    let parsed = match DType::from_type_descriptor(type_desc) {
+        Ok(DType::Scalar(scalar)) => Some((scalar.kind, scalar.item_size)),
+        _ => None,
+    };
+    Ok(match parsed {
+        Some((Kind::Bool, 1)) => DynNpyArray::Bool(read_array_data(reader, header)?),
+        Some((Kind::Int, 1)) => DynNpyArray::I8(read_array_data(reader, header)?),
+        Some((Kind::Int, 2)) => DynNpyArray::I16(read_array_data(reader, header)?),
+        Some((Kind::Int, 4)) => DynNpyArray::I32(read_array_data(reader, header)?),
+        Some((Kind::Int, 8)) => DynNpyArray::I64(read_array_data(reader, header)?),
+        Some((Kind::UInt, 1)) => DynNpyArray::U8(read_array_data(reader, header)?),
+        Some((Kind::UInt, 2)) => DynNpyArray::U16(read_array_data(reader, header)?),
+        Some((Kind::UInt, 4)) => DynNpyArray::U32(read_array_data(reader, header)?),
+        Some((Kind::UInt, 8)) => DynNpyArray::U64(read_array_data(reader, header)?),
+        #[cfg(feature = "half")]
+        Some((Kind::Float, 2)) => DynNpyArray::F16(read_array_data(reader, header)?),
+        Some((Kind::Float, 4)) => DynNpyArray::F32(read_array_data(reader, header)?),
+        Some((Kind::Float, 8)) => DynNpyArray::F64(read_array_data(reader, header)?),
+        #[cfg(feature = "num-complex-0_4")]
+        Some((Kind::Complex, 8)) => DynNpyArray::Complex32(read_array_data(reader, header)?),
+        #[cfg(feature = "num-complex-0_4")]
+        Some((Kind::Complex, 16)) => DynNpyArray::Complex64(read_array_data(reader, header)?),
+        Some((Kind::Bytes, _)) => DynNpyArray::Bytes(read_array_data(reader, header)?),
+        Some((Kind::Unicode, _)) => DynNpyArray::String(read_array_data(reader, header)?),
+        _ => return Err(ReadNpyError::WrongDescriptor(type_desc.clone())),
+    })
+}
@@ -0,0 +1,480 @@
+//! Trait implementations for fixed-width byte strings and raw void data.
+//!
+//! NumPy's byte string dtype (`S<n>`, e.g. `|S16`) stores each element in
+//! exactly `n` bytes. Values shorter than `n` bytes are padded with trailing
+//! NUL bytes, which NumPy strips when the values are accessed.
+//!
+//! NumPy's void dtype (`V<n>`, e.g. `|V16`) also stores each element in
+//! exactly `n` bytes, but the bytes are opaque, so they're never trimmed.
+
+use super::{bytes_as_mut_slice, bytes_as_slice, check_for_extra_bytes, parse_scalar_dtype};
+use crate::dtype::{Kind, ScalarDType};
+use crate::{
+    ReadDataError, ReadableElement, ViewDataError, ViewElement, ViewMutElement, WritableElement,
+    WriteDataError,
+};
+use py_literal::Value as PyValue;
+use std::convert::TryFrom;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// A byte string with a width of `N` bytes, corresponding to NumPy's `S<N>`
+/// dtype (e.g. `|S16` for `FixedBytes<16>`).
+///
+/// Values shorter than `N` bytes are padded with trailing NUL bytes. Use
+/// [`as_bytes`](Self::as_bytes) to get the value with the padding stripped,
+/// like NumPy does.
+///
+/// Since the width is known at compile time, arrays of `FixedBytes<N>` can be
+/// viewed with [`ViewNpyExt`](crate::ViewNpyExt) and
+/// [`ViewMutNpyExt`](crate::ViewMutNpyExt). When reading, data with a smaller
+/// width than `N` is accepted and padded to `N` bytes. For byte strings whose
+/// width is known only at runtime, use `Vec<u8>` as the element type instead.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{FixedBytes, ReadNpyExt, WriteNpyExt};
+/// use ndarray::Array1;
+/// use std::convert::TryFrom;
+///
+/// let arr = array![
+///     FixedBytes::<8>::try_from(&b"apple"[..])?,
+///     FixedBytes::<8>::try_from(&b"banana"[..])?,
+/// ];
+/// let mut buf = Vec::new();
+/// arr.write_npy(&mut buf)?;
+/// let read = Array1::<FixedBytes<8>>::read_npy(&buf[..])?;
+/// assert_eq!(read[1].as_bytes(), b"banana");
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct FixedBytes<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> FixedBytes<N> {
+    /// Returns the bytes with trailing NUL bytes stripped.
+    pub fn as_bytes(&self) -> &[u8] {
+        trim_trailing_nuls(&self.0)
+    }
+
+    /// Returns all `N` bytes, including any trailing NUL padding.
+    pub fn as_padded_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> Default for FixedBytes<N> {
+    fn default() -> Self {
+        FixedBytes([0; N])
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for FixedBytes<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        FixedBytes(bytes)
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for FixedBytes<N> {
+    type Error = ByteStringTooLongError;
+
+    /// Pads `bytes` with trailing NUL bytes to a width of `N`.
+    ///
+    /// Returns an error if `bytes` is longer than `N`.
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        if bytes.len() > N {
+            return Err(ByteStringTooLongError {
+                len: bytes.len(),
+                width: N,
+            });
+        }
+        let mut out = [0; N];
+        out[..bytes.len()].copy_from_slice(bytes);
+        Ok(FixedBytes(out))
+    }
+}
+
+/// An error indicating that a byte string is too long for the available
+/// width.
+#[derive(Debug)]
+pub struct ByteStringTooLongError {
+    len: usize,
+    width: usize,
+}
+
+impl Error for ByteStringTooLongError {}
+
+impl fmt::Display for ByteStringTooLongError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "byte string of length {} does not fit in width {}",
+            self.len, self.width
+        )
+    }
+}
+
+impl From<ByteStringTooLongError> for WriteDataError {
+    fn from(err: ByteStringTooLongError) -> WriteDataError {
+        WriteDataError::FormatData(Box::new(err))
+    }
+}
+
+/// An error indicating that a descriptor passed to one of the
+/// `write_*_with_descriptor` methods is not a byte string descriptor.
+#[derive(Debug)]
+struct NotByteStringDescriptorError(PyValue);
+
+impl Error for NotByteStringDescriptorError {}
+
+impl fmt::Display for NotByteStringDescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "descriptor {} is not a byte string descriptor", self.0)
+    }
+}
+
+impl From<NotByteStringDescriptorError> for WriteDataError {
+    fn from(err: NotByteStringDescriptorError) -> WriteDataError {
+        WriteDataError::FormatData(Box::new(err))
+    }
+}
+
+/// Returns `bytes` with trailing NUL bytes removed.
+fn trim_trailing_nuls(bytes: &[u8]) -> &[u8] {
+    let len = bytes.iter().rposition(|&b| b != 0).map_or(0, |i| i + 1);
+    &bytes[..len]
+}
+
+/// Returns the width in bytes if `type_desc` is a byte string descriptor.
+fn byte_string_width(type_desc: &PyValue) -> Option<usize> {
+    match parse_scalar_dtype(type_desc)? {
+        ScalarDType {
+            kind: Kind::Bytes,
+            item_size,
+            ..
+        } => Some(item_size),
+        _ => None,
+    }
+}
+
+/// Formats the byte string descriptor for the given width.
+fn byte_string_descriptor(width: usize) -> PyValue {
+    PyValue::String(format!("|S{}", width))
+}
+
+/// Reads `len` byte strings of width `width` from `reader`, which should
+/// contain no more data after the strings.
+///
+/// Each byte string is passed to `f` to convert it to the output type.
+pub(super) fn read_byte_strings<R, T, F>(
+    mut reader: R,
+    width: usize,
+    len: usize,
+    f: F,
+) -> Result<Vec<T>, ReadDataError>
+where
+    R: io::Read,
+    F: FnMut(&[u8]) -> T,
+{
+    // If the length in bytes overflows, the reader can't contain all of the
+    // data.
+    let mut bytes = vec![0; width.checked_mul(len).ok_or(ReadDataError::MissingData)?];
+    reader.read_exact(&mut bytes)?;
+    check_for_extra_bytes(&mut reader)?;
+    if width == 0 {
+        Ok(std::iter::repeat_with(|| &[][..])
+            .take(len)
+            .map(f)
+            .collect())
+    } else {
+        Ok(bytes.chunks_exact(width).map(f).collect())
+    }
+}
+
+impl<const N: usize> WritableElement for FixedBytes<N> {
+    fn type_descriptor() -> PyValue {
+        byte_string_descriptor(N)
+    }
+
+    fn write<W: io::Write>(&self, mut writer: W) -> Result<(), WriteDataError> {
+        writer.write_all(&self.0)?;
+        Ok(())
+    }
+
+    fn write_slice<W: io::Write>(slice: &[Self], mut writer: W) -> Result<(), WriteDataError> {
+        // `FixedBytes<N>` is `repr(transparent)` around `[u8; N]`, so it has
+        // no padding bytes.
+        writer.write_all(unsafe { super::slice_as_bytes(slice) })?;
+        Ok(())
+    }
+}
+
+impl<const N: usize> ReadableElement for FixedBytes<N> {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        match byte_string_width(type_desc) {
+            Some(width) if width <= N => read_byte_strings(reader, width, len, |bytes| {
+                let mut out = [0; N];
+                out[..width].copy_from_slice(bytes);
+                FixedBytes(out)
+            }),
+            _ => Err(ReadDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+impl<const N: usize> ViewElement for FixedBytes<N> {
+    fn bytes_as_slice<'a>(
+        bytes: &'a [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a [Self], ViewDataError> {
+        match byte_string_width(type_desc) {
+            // All bit patterns are valid for `FixedBytes<N>`.
+            Some(width) if width == N => unsafe { bytes_as_slice(bytes, len) },
+            _ => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+impl<const N: usize> ViewMutElement for FixedBytes<N> {
+    fn bytes_as_mut_slice<'a>(
+        bytes: &'a mut [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a mut [Self], ViewDataError> {
+        match byte_string_width(type_desc) {
+            // All bit patterns are valid for `FixedBytes<N>`.
+            Some(width) if width == N => unsafe { bytes_as_mut_slice(bytes, len) },
+            _ => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+/// Raw, uninterpreted data with a width of `N` bytes, corresponding to
+/// NumPy's void dtype `V<N>` (e.g. `|V16` for `Void<16>`).
+///
+/// This is useful for opaque fixed-size values such as UUIDs and hashes.
+/// Unlike [`FixedBytes`], the bytes are never trimmed or padded, so the width
+/// in the file must be exactly `N`. Since all bit patterns are valid, arrays
+/// of `Void<N>` can be viewed with [`ViewNpyExt`](crate::ViewNpyExt) and
+/// [`ViewMutNpyExt`](crate::ViewMutNpyExt).
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{ReadNpyExt, Void, WriteNpyExt};
+/// use ndarray::Array1;
+///
+/// let arr = array![Void([0xde, 0xad, 0xbe, 0xef]), Void([0; 4])];
+/// let mut buf = Vec::new();
+/// arr.write_npy(&mut buf)?;
+/// let read = Array1::<Void<4>>::read_npy(&buf[..])?;
+/// assert_eq!(read[0].0, [0xde, 0xad, 0xbe, 0xef]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[repr(transparent)]
+pub struct Void<const N: usize>(pub [u8; N]);
+
+impl<const N: usize> Void<N> {
+    /// Returns the bytes.
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+}
+
+impl<const N: usize> Default for Void<N> {
+    fn default() -> Self {
+        Void([0; N])
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for Void<N> {
+    fn from(bytes: [u8; N]) -> Self {
+        Void(bytes)
+    }
+}
+
+impl<const N: usize> From<Void<N>> for [u8; N] {
+    fn from(void: Void<N>) -> Self {
+        void.0
+    }
+}
+
+/// Returns the width in bytes if `type_desc` is a void descriptor.
+///
+/// Since the bytes are opaque, any byte order character is accepted.
+pub(super) fn void_width(type_desc: &PyValue) -> Option<usize> {
+    match parse_scalar_dtype(type_desc)? {
+        ScalarDType {
+            kind: Kind::Void,
+            item_size,
+            ..
+        } => Some(item_size),
+        _ => None,
+    }
+}
+
+impl<const N: usize> WritableElement for Void<N> {
+    fn type_descriptor() -> PyValue {
+        PyValue::String(format!("|V{}", N))
+    }
+
+    fn write<W: io::Write>(&self, mut writer: W) -> Result<(), WriteDataError> {
+        writer.write_all(&self.0)?;
+        Ok(())
+    }
+
+    fn write_slice<W: io::Write>(slice: &[Self], mut writer: W) -> Result<(), WriteDataError> {
+        // `Void<N>` is `repr(transparent)` around `[u8; N]`, so it has no
+        // padding bytes.
+        writer.write_all(unsafe { super::slice_as_bytes(slice) })?;
+        Ok(())
+    }
+}
+
+impl<const N: usize> ReadableElement for Void<N> {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        match void_width(type_desc) {
+            Some(width) if width == N => read_byte_strings(reader, width, len, |bytes| {
+                let mut out = [0; N];
+                out.copy_from_slice(bytes);
+                Void(out)
+            }),
+            _ => Err(ReadDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+impl<const N: usize> ViewElement for Void<N> {
+    fn bytes_as_slice<'a>(
+        bytes: &'a [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a [Self], ViewDataError> {
+        match void_width(type_desc) {
+            // All bit patterns are valid for `Void<N>`.
+            Some(width) if width == N => unsafe { bytes_as_slice(bytes, len) },
+            _ => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+impl<const N: usize> ViewMutElement for Void<N> {
+    fn bytes_as_mut_slice<'a>(
+        bytes: &'a mut [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a mut [Self], ViewDataError> {
+        match void_width(type_desc) {
+            // All bit patterns are valid for `Void<N>`.
+            Some(width) if width == N => unsafe { bytes_as_mut_slice(bytes, len) },
+            _ => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+/// `Vec<u8>` elements are byte strings whose width is determined by the data.
+///
+/// When writing an array, the width is the length of the longest element (at
+/// least 1, like NumPy), and shorter elements are padded with NUL bytes. When
+/// reading, trailing NUL bytes are stripped from each element.
+///
+/// Since the width isn't known from the type alone,
+/// [`type_descriptor`](WritableElement::type_descriptor) returns `|S1`, and
+/// [`write`](WritableElement::write) and
+/// [`write_slice`](WritableElement::write_slice) write elements with that
+/// width, so they return a [`ByteStringTooLongError`] for elements longer
+/// than 1 byte. Use
+/// [`array_type_descriptor`](WritableElement::array_type_descriptor) and
+/// [`write_slice_with_descriptor`](WritableElement::write_slice_with_descriptor)
+/// to write elements with a wider descriptor.
+impl WritableElement for Vec<u8> {
+    fn type_descriptor() -> PyValue {
+        byte_string_descriptor(1)
+    }
+
+    fn write<W: io::Write>(&self, writer: W) -> Result<(), WriteDataError> {
+        self.write_with_descriptor(&Self::type_descriptor(), writer)
+    }
+
+    fn write_slice<W: io::Write>(slice: &[Self], writer: W) -> Result<(), WriteDataError> {
+        Self::write_slice_with_descriptor(slice, &Self::type_descriptor(), writer)
+    }
+
+    fn array_type_descriptor<'a, I>(elems: I) -> PyValue
+    where
+        I: IntoIterator<Item = &'a Self>,
+    {
+        let width = elems.into_iter().map(Vec::len).max().unwrap_or(0).max(1);
+        byte_string_descriptor(width)
+    }
+
+    fn write_with_descriptor<W: io::Write>(
+        &self,
+        type_desc: &PyValue,
+        writer: W,
+    ) -> Result<(), WriteDataError> {
+        Self::write_slice_with_descriptor(std::slice::from_ref(self), type_desc, writer)
+    }
+
+    fn write_slice_with_descriptor<W: io::Write>(
+        slice: &[Self],
+        type_desc: &PyValue,
+        mut writer: W,
+    ) -> Result<(), WriteDataError> {
+        let width = byte_string_width(type_desc)
+            .ok_or_else(|| NotByteStringDescriptorError(type_desc.clone()))?;
+        let zeros = vec![0; width];
+        for elem in slice {
+            write_padded(&mut writer, elem, &zeros)?;
+        }
+        Ok(())
+    }
+}
+
+/// Writes `bytes` padded with NUL bytes to the width `zeros.len()`, where
+/// `zeros` is a slice of NUL bytes.
+fn write_padded<W: io::Write>(
+    writer: &mut W,
+    bytes: &[u8],
+    zeros: &[u8],
+) -> Result<(), WriteDataError> {
+    let width = zeros.len();
+    if bytes.len() > width {
+        return Err(ByteStringTooLongError {
+            len: bytes.len(),
+            width,
+        }
+        .into());
+    }
+    writer.write_all(bytes)?;
+    writer.write_all(&zeros[bytes.len()..])?;
+    Ok(())
+}
+
+impl ReadableElement for Vec<u8> {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        let width = byte_string_width(type_desc)
+            .ok_or_else(|| ReadDataError::WrongDescriptor(type_desc.clone()))?;
+        read_byte_strings(reader, width, len, |bytes| {
+            trim_trailing_nuls(bytes).to_vec()
+        })
+    }
+}
@@ -0,0 +1,574 @@
+//! Element types for NumPy's `datetime64` and `timedelta64` dtypes.
+//!
+//! NumPy stores both dtypes as 64-bit signed integers counting multiples of a
+//! time unit, which is part of the descriptor. For example, `<M8[ns]` is a
+//! little-endian `datetime64` counting nanoseconds since the Unix epoch
+//! (1970-01-01T00:00:00), and `<m8[ms]` is a little-endian `timedelta64`
+//! counting milliseconds. The value `i64::MIN` represents NaT ("not a
+//! time").
+//!
+//! In this crate, the unit is part of the element type, so that the elements
+//! can be represented as plain `i64` values in memory. For example, a
+//! `<M8[ns]` array can be read as `Array1<DateTime64<Nanoseconds>>`, and
+//! writing an array of `TimeDelta64<Milliseconds>` produces a `<m8[ms]`
+//! array. Since the in-memory representation is the raw `i64` value, arrays
+//! of these types can also be viewed without copying with
+//! [`ViewNpyExt`](crate::ViewNpyExt) and
+//! [`ViewMutNpyExt`](crate::ViewMutNpyExt). Reading or viewing data with a
+//! different unit than the element type results in a `WrongDescriptor`
+//! error; use [`TimeUnit::from_type_descriptor`] to determine the unit of an
+//! array from its header. Units with a multiplier (e.g. `M8[10ms]`) are not
+//! supported.
+//!
+//! With the `chrono` feature, the values can be converted to and from
+//! [`chrono`](https://crates.io/crates/chrono) types.
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::{array, Array1};
+//! use ndarray_npy::datetime::{DateTime64, Seconds};
+//! use ndarray_npy::{ReadNpyExt, WriteNpyExt};
+//!
+//! let arr = array![
+//!     DateTime64::<Seconds>::new(1_600_000_000),
+//!     DateTime64::NAT,
+//! ];
+//! let mut buf = Vec::new();
+//! arr.write_npy(&mut buf)?;
+//! let read = Array1::<DateTime64<Seconds>>::read_npy(&buf[..])?;
+//! assert_eq!(read[0].get(), Some(1_600_000_000));
+//! assert!(read[1].is_nat());
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use super::{bytes_as_mut_slice, bytes_as_slice, check_for_extra_bytes};
+use crate::dtype::{DType, Kind, ScalarDType};
+use crate::{
+    ReadDataError, ReadableElement, ViewDataError, ViewElement, ViewMutElement, WritableElement,
+    WriteDataError,
+};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use py_literal::Value as PyValue;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::marker::PhantomData;
+
+/// The unit of a `datetime64` or `timedelta64` value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TimeUnit {
+    /// Years (`Y`).
+    Years,
+    /// Months (`M`).
+    Months,
+    /// Weeks (`W`).
+    Weeks,
+    /// Days (`D`).
+    Days,
+    /// Hours (`h`).
+    Hours,
+    /// Minutes (`m`).
+    Minutes,
+    /// Seconds (`s`).
+    Seconds,
+    /// Milliseconds (`ms`).
+    Milliseconds,
+    /// Microseconds (`us`).
+    Microseconds,
+    /// Nanoseconds (`ns`).
+    Nanoseconds,
+    /// Picoseconds (`ps`).
+    Picoseconds,
+    /// Femtoseconds (`fs`).
+    Femtoseconds,
+    /// Attoseconds (`as`).
+    Attoseconds,
+}
+
+/// The length of a unit relative to one second, for units of fixed length.
+#[derive(Clone, Copy)]
+#[cfg_attr(not(feature = "chrono"), allow(dead_code))]
+enum Scale {
+    /// The unit is this number of seconds.
+    Seconds(i64),
+    /// One second is this number of units.
+    PerSecond(i64),
+}
+
+impl TimeUnit {
+    /// Returns the abbreviation NumPy uses for the unit in descriptors.
+    pub fn abbrev(self) -> &'static str {
+        match self {
+            TimeUnit::Years => "Y",
+            TimeUnit::Months => "M",
+            TimeUnit::Weeks => "W",
+            TimeUnit::Days => "D",
+            TimeUnit::Hours => "h",
+            TimeUnit::Minutes => "m",
+            TimeUnit::Seconds => "s",
+            TimeUnit::Milliseconds => "ms",
+            TimeUnit::Microseconds => "us",
+            TimeUnit::Nanoseconds => "ns",
+            TimeUnit::Picoseconds => "ps",
+            TimeUnit::Femtoseconds => "fs",
+            TimeUnit::Attoseconds => "as",
+        }
+    }
+
+    /// Parses a unit abbreviation, such as `"ms"`.
+    ///
+    /// NumPy also accepts `"μs"` for microseconds, so that is accepted too.
+    pub fn from_abbrev(abbrev: &str) -> Option<TimeUnit> {
+        Some(match abbrev {
+            "Y" => TimeUnit::Years,
+            "M" => TimeUnit::Months,
+            "W" => TimeUnit::Weeks,
+            "D" => TimeUnit::Days,
+            "h" => TimeUnit::Hours,
+            "m" => TimeUnit::Minutes,
+            "s" => TimeUnit::Seconds,
+            "ms" => TimeUnit::Milliseconds,
+            "us" | "μs" => TimeUnit::Microseconds,
+            "ns" => TimeUnit::Nanoseconds,
+            "ps" => TimeUnit::Picoseconds,
+            "fs" => TimeUnit::Femtoseconds,
+            "as" => TimeUnit::Attoseconds,
+            _ => return None,
+        })
+    }
+
+    /// Returns the unit of a `datetime64` or `timedelta64` type descriptor,
+    /// such as `'<M8[ns]'` or `'>m8[D]'`.
+    ///
+    /// Returns `None` if the descriptor is not a `datetime64` or
+    /// `timedelta64` descriptor with one of the supported units.
+    pub fn from_type_descriptor(type_desc: &PyValue) -> Option<TimeUnit> {
+        match DType::from_type_descriptor(type_desc) {
+            Ok(DType::Scalar(ScalarDType {
+                kind: Kind::DateTime | Kind::TimeDelta,
+                time_unit,
+                ..
+            })) => time_unit,
+            _ => None,
+        }
+    }
+
+    /// Returns the length of the unit relative to one second, or `None` for
+    /// units without a fixed length (years and months).
+    #[cfg_attr(not(feature = "chrono"), allow(dead_code))]
+    fn scale(self) -> Option<Scale> {
+        Some(match self {
+            TimeUnit::Years | TimeUnit::Months => return None,
+            TimeUnit::Weeks => Scale::Seconds(7 * 24 * 60 * 60),
+            TimeUnit::Days => Scale::Seconds(24 * 60 * 60),
+            TimeUnit::Hours => Scale::Seconds(60 * 60),
+            TimeUnit::Minutes => Scale::Seconds(60),
+            TimeUnit::Seconds => Scale::Seconds(1),
+            TimeUnit::Milliseconds => Scale::PerSecond(1_000),
+            TimeUnit::Microseconds => Scale::PerSecond(1_000_000),
+            TimeUnit::Nanoseconds => Scale::PerSecond(1_000_000_000),
+            TimeUnit::Picoseconds => Scale::PerSecond(1_000_000_000_000),
+            TimeUnit::Femtoseconds => Scale::PerSecond(1_000_000_000_000_000),
+            TimeUnit::Attoseconds => Scale::PerSecond(1_000_000_000_000_000_000),
+        })
+    }
+}
+
+impl fmt::Display for TimeUnit {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.abbrev())
+    }
+}
+
+/// A type-level time unit for [`DateTime64`] and [`TimeDelta64`].
+///
+/// This is implemented by the unit marker types in this module, such as
+/// [`Nanoseconds`].
+pub trait DateTimeUnit {
+    /// The corresponding runtime unit.
+    const UNIT: TimeUnit;
+}
+
+macro_rules! define_units {
+    ($($name:ident),*) => {
+        $(
+            #[doc = concat!("Type-level unit marker for [`TimeUnit::", stringify!($name), "`].")]
+            #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+            pub struct $name;
+
+            impl DateTimeUnit for $name {
+                const UNIT: TimeUnit = TimeUnit::$name;
+            }
+        )*
+    };
+}
+
+define_units!(
+    Years,
+    Months,
+    Weeks,
+    Days,
+    Hours,
+    Minutes,
+    Seconds,
+    Milliseconds,
+    Microseconds,
+    Nanoseconds,
+    Picoseconds,
+    Femtoseconds,
+    Attoseconds
+);
+
+/// The raw value that represents NaT ("not a time").
+const NAT: i64 = i64::MIN;
+
+macro_rules! define_time_type {
+    ($(#[$meta:meta])* $name:ident, $kind:expr) => {
+        $(#[$meta])*
+        #[repr(transparent)]
+        pub struct $name<U> {
+            value: i64,
+            unit: PhantomData<U>,
+        }
+
+        impl<U> $name<U> {
+            /// The NaT ("not a time") value.
+            pub const NAT: Self = Self::new(NAT);
+
+            /// Creates a value from its raw representation.
+            ///
+            /// Note that `i64::MIN` represents NaT.
+            pub const fn new(value: i64) -> Self {
+                $name {
+                    value,
+                    unit: PhantomData,
+                }
+            }
+
+            /// Returns the raw representation of the value (`i64::MIN` for
+            /// NaT).
+            pub const fn raw(self) -> i64 {
+                self.value
+            }
+
+            /// Returns the raw representation of the value, or `None` if the
+            /// value is NaT.
+            pub const fn get(self) -> Option<i64> {
+                if self.value == NAT {
+                    None
+                } else {
+                    Some(self.value)
+                }
+            }
+
+            /// Returns `true` if the value is NaT ("not a time").
+            pub const fn is_nat(self) -> bool {
+                self.value == NAT
+            }
+        }
+
+        impl<U: DateTimeUnit> $name<U> {
+            /// Returns the descriptor for the given byte order character.
+            fn descriptor(order: char) -> String {
+                format!("{}{}8[{}]", order, $kind, U::UNIT.abbrev())
+            }
+        }
+
+        impl<U> From<Option<i64>> for $name<U> {
+            /// Converts `None` to NaT.
+            fn from(value: Option<i64>) -> Self {
+                Self::new(value.unwrap_or(NAT))
+            }
+        }
+
+        impl<U> Clone for $name<U> {
+            fn clone(&self) -> Self {
+                *self
+            }
+        }
+
+        impl<U> Copy for $name<U> {}
+
+        impl<U> PartialEq for $name<U> {
+            fn eq(&self, other: &Self) -> bool {
+                self.value == other.value
+            }
+        }
+
+        impl<U> Eq for $name<U> {}
+
+        impl<U> PartialOrd for $name<U> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        /// Orders by the raw value, so NaT is less than all other values.
+        impl<U> Ord for $name<U> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.value.cmp(&other.value)
+            }
+        }
+
+        impl<U> Hash for $name<U> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.value.hash(state)
+            }
+        }
+
+        impl<U: DateTimeUnit> fmt::Debug for $name<U> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                match self.get() {
+                    Some(value) => write!(f, "{}({}{})", stringify!($name), value, U::UNIT),
+                    None => write!(f, "{}(NaT)", stringify!($name)),
+                }
+            }
+        }
+
+        impl<U: DateTimeUnit> WritableElement for $name<U> {
+            fn type_descriptor() -> PyValue {
+                if cfg!(target_endian = "little") {
+                    PyValue::String(Self::descriptor('<'))
+                } else if cfg!(target_endian = "big") {
+                    PyValue::String(Self::descriptor('>'))
+                } else {
+                    unreachable!()
+                }
+            }
+
+            fn write<W: io::Write>(&self, mut writer: W) -> Result<(), WriteDataError> {
+                writer.write_all(&self.value.to_ne_bytes())?;
+                Ok(())
+            }
+
+            fn write_slice<W: io::Write>(
+                slice: &[Self],
+                mut writer: W,
+            ) -> Result<(), WriteDataError> {
+                // `Self` is `repr(transparent)` around `i64`, so it has no
+                // padding bytes.
+                writer.write_all(unsafe { super::slice_as_bytes(slice) })?;
+                Ok(())
+            }
+        }
+
+        impl<U: DateTimeUnit> ReadableElement for $name<U> {
+            fn read_to_end_exact_vec<R: io::Read>(
+                mut reader: R,
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<Vec<Self>, ReadDataError> {
+                let mut raw = vec![0; len];
+                match *type_desc {
+                    PyValue::String(ref s) if *s == Self::descriptor('<') => {
+                        reader.read_i64_into::<LittleEndian>(&mut raw)?;
+                    }
+                    PyValue::String(ref s) if *s == Self::descriptor('>') => {
+                        reader.read_i64_into::<BigEndian>(&mut raw)?;
+                    }
+                    ref other => return Err(ReadDataError::WrongDescriptor(other.clone())),
+                }
+                check_for_extra_bytes(&mut reader)?;
+                Ok(raw.into_iter().map(Self::new).collect())
+            }
+        }
+
+        impl<U: DateTimeUnit> ViewElement for $name<U> {
+            fn bytes_as_slice<'a>(
+                bytes: &'a [u8],
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<&'a [Self], ViewDataError> {
+                match check_native_descriptor(type_desc, &Self::descriptor('<'), &Self::descriptor('>')) {
+                    // All bit patterns are valid for `Self`, since it's
+                    // `repr(transparent)` around `i64`.
+                    Ok(()) => unsafe { bytes_as_slice(bytes, len) },
+                    Err(err) => Err(err),
+                }
+            }
+        }
+
+        impl<U: DateTimeUnit> ViewMutElement for $name<U> {
+            fn bytes_as_mut_slice<'a>(
+                bytes: &'a mut [u8],
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<&'a mut [Self], ViewDataError> {
+                match check_native_descriptor(type_desc, &Self::descriptor('<'), &Self::descriptor('>')) {
+                    // All bit patterns are valid for `Self`, since it's
+                    // `repr(transparent)` around `i64`.
+                    Ok(()) => unsafe { bytes_as_mut_slice(bytes, len) },
+                    Err(err) => Err(err),
+                }
+            }
+        }
+    };
+}
+
+/// Returns `Ok(_)` iff `type_desc` is the native-endian one of the
+/// little-endian and big-endian descriptors.
+fn check_native_descriptor(
+    type_desc: &PyValue,
+    little_desc: &str,
+    big_desc: &str,
+) -> Result<(), ViewDataError> {
+    let (native_desc, non_native_desc) = if cfg!(target_endian = "little") {
+        (little_desc, big_desc)
+    } else {
+        (big_desc, little_desc)
+    };
+    match type_desc {
+        PyValue::String(s) if s == native_desc => Ok(()),
+        PyValue::String(s) if s == non_native_desc => Err(ViewDataError::NonNativeEndian),
+        other => Err(ViewDataError::WrongDescriptor(other.clone())),
+    }
+}
+
+define_time_type!(
+    /// A `datetime64` value with unit `U`, counting units since the Unix
+    /// epoch (1970-01-01T00:00:00).
+    ///
+    /// This corresponds to descriptors such as `<M8[ns]` (for
+    /// `DateTime64<Nanoseconds>`). See the [module-level
+    /// documentation](self) for more information.
+    DateTime64,
+    'M'
+);
+
+define_time_type!(
+    /// A `timedelta64` value with unit `U`.
+    ///
+    /// This corresponds to descriptors such as `<m8[ms]` (for
+    /// `TimeDelta64<Milliseconds>`). See the [module-level
+    /// documentation](self) for more information.
+    TimeDelta64,
+    'm'
+);
+
+#[cfg(feature = "chrono")]
+mod chrono_impls {
+    use super::{DateTime64, DateTimeUnit, Scale, TimeDelta64, TimeUnit, NAT};
+    use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, TimeDelta};
+
+    const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+    /// Converts a raw value with the given scale to seconds and nanoseconds
+    /// (`0 <= nanos < 1_000_000_000`), rounding toward negative infinity.
+    fn to_secs_nanos(value: i64, scale: Scale) -> Option<(i64, u32)> {
+        match scale {
+            Scale::Seconds(secs_per_unit) => Some((value.checked_mul(secs_per_unit)?, 0)),
+            Scale::PerSecond(units_per_sec) => {
+                let secs = value.div_euclid(units_per_sec);
+                let rem = value.rem_euclid(units_per_sec);
+                let nanos = if units_per_sec >= NANOS_PER_SECOND {
+                    rem / (units_per_sec / NANOS_PER_SECOND)
+                } else {
+                    rem * (NANOS_PER_SECOND / units_per_sec)
+                };
+                Some((secs, nanos as u32))
+            }
+        }
+    }
+
+    /// Converts seconds and nanoseconds (`0 <= nanos < 1_000_000_000`) to a
+    /// raw value with the given scale, rounding toward negative infinity.
+    ///
+    /// Returns `None` on overflow or if the result would be NaT.
+    fn from_secs_nanos(secs: i64, nanos: u32, scale: Scale) -> Option<i64> {
+        let value = match scale {
+            Scale::Seconds(secs_per_unit) => secs.div_euclid(secs_per_unit),
+            Scale::PerSecond(units_per_sec) => {
+                let nanos = i64::from(nanos);
+                let sub = if units_per_sec >= NANOS_PER_SECOND {
+                    nanos * (units_per_sec / NANOS_PER_SECOND)
+                } else {
+                    nanos / (NANOS_PER_SECOND / units_per_sec)
+                };
+                secs.checked_mul(units_per_sec)?.checked_add(sub)?
+            }
+        };
+        if value == NAT {
+            None
+        } else {
+            Some(value)
+        }
+    }
+
+    impl<U: DateTimeUnit> DateTime64<U> {
+        /// Converts the value to a [`NaiveDateTime`] (interpreted as UTC).
+        ///
+        /// Units smaller than nanoseconds are truncated toward negative
+        /// infinity. Returns `None` if the value is NaT or is out of range
+        /// for `NaiveDateTime`.
+        pub fn to_naive_date_time(self) -> Option<NaiveDateTime> {
+            let value = self.get()?;
+            match U::UNIT {
+                TimeUnit::Years => {
+                    let year = i32::try_from(value.checked_add(1970)?).ok()?;
+                    NaiveDate::from_ymd_opt(year, 1, 1)?.and_hms_opt(0, 0, 0)
+                }
+                TimeUnit::Months => {
+                    let year = i32::try_from(value.div_euclid(12).checked_add(1970)?).ok()?;
+                    let month = value.rem_euclid(12) as u32 + 1;
+                    NaiveDate::from_ymd_opt(year, month, 1)?.and_hms_opt(0, 0, 0)
+                }
+                unit => {
+                    let (secs, nanos) = to_secs_nanos(value, unit.scale()?)?;
+                    Some(DateTime::from_timestamp(secs, nanos)?.naive_utc())
+                }
+            }
+        }
+
+        /// Converts a [`NaiveDateTime`] (interpreted as UTC) to a value with
+        /// unit `U`.
+        ///
+        /// Like NumPy, the value is truncated toward negative infinity if the
+        /// unit is larger than the precision of the `NaiveDateTime`. Returns
+        /// `None` if the result would overflow.
+        pub fn from_naive_date_time(date_time: NaiveDateTime) -> Option<Self> {
+            let value = match U::UNIT {
+                TimeUnit::Years => i64::from(date_time.year()) - 1970,
+                TimeUnit::Months => {
+                    (i64::from(date_time.year()) - 1970) * 12 + i64::from(date_time.month0())
+                }
+                unit => {
+                    let utc = date_time.and_utc();
+                    from_secs_nanos(utc.timestamp(), utc.timestamp_subsec_nanos(), unit.scale()?)?
+                }
+            };
+            Some(Self::new(value))
+        }
+    }
+
+    impl<U: DateTimeUnit> TimeDelta64<U> {
+        /// Converts the value to a [`TimeDelta`].
+        ///
+        /// Units smaller than nanoseconds are truncated toward negative
+        /// infinity. Returns `None` if the value is NaT, if the unit is years
+        /// or months (which have no fixed length), or if the value is out of
+        /// range for `TimeDelta`.
+        pub fn to_time_delta(self) -> Option<TimeDelta> {
+            let (secs, nanos) = to_secs_nanos(self.get()?, U::UNIT.scale()?)?;
+            TimeDelta::new(secs, nanos)
+        }
+
+        /// Converts a [`TimeDelta`] to a value with unit `U`.
+        ///
+        /// The value is truncated toward negative infinity if the unit is
+        /// larger than nanoseconds. Returns `None` if the unit is years or
+        /// months (which have no fixed length) or if the result would
+        /// overflow.
+        pub fn from_time_delta(delta: TimeDelta) -> Option<Self> {
+            let mut secs = delta.num_seconds();
+            let mut nanos = delta.subsec_nanos();
+            if nanos < 0 {
+                secs -= 1;
+                nanos += NANOS_PER_SECOND as i32;
+            }
+            let value = from_secs_nanos(secs, nanos as u32, U::UNIT.scale()?)?;
+            Some(Self::new(value))
+        }
+    }
+}
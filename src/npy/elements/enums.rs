@@ -0,0 +1,205 @@
+//! Support for fieldless enums stored as integer codes.
+//!
+//! Categorical data is often stored in NumPy as integer codes, e.g. with the
+//! `<i4` or `|u1` dtype. A fieldless enum with an integer representation,
+//! e.g. `#[repr(i32)]`, can be read, written, and viewed directly as an array
+//! of such codes, where each code is the discriminant of a variant. Reading
+//! and viewing check that every code is the discriminant of some variant.
+//!
+//! The easiest way to implement the element traits for an enum is to use
+//! `#[derive(NpyEnum)]` (which requires the `derive` feature). The functions
+//! in this module are what the derived implementations use under the hood.
+
+use crate::{
+    ReadDataError, ReadableElement, ViewDataError, ViewElement, ViewMutElement, WritableElement,
+    WriteDataError,
+};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::mem;
+use std::slice;
+
+/// Re-export of the type used for descriptors, for use by derived
+/// implementations.
+pub use py_literal::Value as PyValue;
+
+/// An error indicating that a value is not the discriminant of any variant of
+/// an enum.
+#[derive(Debug)]
+struct InvalidEnumValueError {
+    type_name: &'static str,
+    value: String,
+    index: usize,
+}
+
+impl Error for InvalidEnumValueError {}
+
+impl fmt::Display for InvalidEnumValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "invalid value {} for enum {} in element {}",
+            self.value, self.type_name, self.index
+        )
+    }
+}
+
+impl From<InvalidEnumValueError> for ReadDataError {
+    fn from(err: InvalidEnumValueError) -> ReadDataError {
+        ReadDataError::ParseData(Box::new(err))
+    }
+}
+
+impl From<InvalidEnumValueError> for ViewDataError {
+    fn from(err: InvalidEnumValueError) -> ViewDataError {
+        ViewDataError::InvalidData(Box::new(err))
+    }
+}
+
+/// Returns `Ok(_)` iff each of the values is the discriminant of a variant of
+/// the enum, i.e. `from_repr` returns `Some(_)` for each value.
+fn check_valid_for_enum<T, R>(
+    values: &[R],
+    type_name: &'static str,
+    from_repr: impl Fn(R) -> Option<T>,
+) -> Result<(), InvalidEnumValueError>
+where
+    R: Copy + fmt::Display,
+{
+    match values.iter().position(|&value| from_repr(value).is_none()) {
+        Some(index) => Err(InvalidEnumValueError {
+            type_name,
+            value: values[index].to_string(),
+            index,
+        }),
+        None => Ok(()),
+    }
+}
+
+/// Checks that `T` has the same size and alignment as `R`.
+fn assert_same_layout<T, R>() {
+    assert_eq!(mem::size_of::<T>(), mem::size_of::<R>());
+    assert_eq!(mem::align_of::<T>(), mem::align_of::<R>());
+}
+
+/// Reads `len` integer codes of type `R` from `reader` (see
+/// [`ReadableElement::read_to_end_exact_vec`]) and converts them to the enum
+/// `T` with `from_repr`.
+///
+/// If `from_repr` returns `None` for a code, returns an error which includes
+/// `type_name`, the code, and the index of the element.
+pub fn read_enum<T, R, Rd>(
+    reader: Rd,
+    type_desc: &PyValue,
+    len: usize,
+    type_name: &'static str,
+    from_repr: impl Fn(R) -> Option<T>,
+) -> Result<Vec<T>, ReadDataError>
+where
+    R: ReadableElement + Copy + fmt::Display,
+    Rd: io::Read,
+{
+    let values = R::read_to_end_exact_vec(reader, type_desc, len)?;
+    values
+        .iter()
+        .enumerate()
+        .map(|(index, &value)| {
+            from_repr(value).ok_or_else(|| {
+                InvalidEnumValueError {
+                    type_name,
+                    value: value.to_string(),
+                    index,
+                }
+                .into()
+            })
+        })
+        .collect()
+}
+
+/// Writes the enum values in `slice` as integer codes of type `R` (see
+/// [`WritableElement::write_slice`]).
+///
+/// # Safety
+///
+/// `T` must be a fieldless enum with `#[repr(R)]`, so that each value of `T`
+/// is a valid value of `R` (its discriminant).
+///
+/// # Panics
+///
+/// Panics if `T` and `R` have different sizes or alignments.
+pub unsafe fn write_enum_slice<T, R, W>(slice: &[T], writer: W) -> Result<(), WriteDataError>
+where
+    R: WritableElement,
+    W: io::Write,
+{
+    assert_same_layout::<T, R>();
+    let codes: &[R] = slice::from_raw_parts(slice.as_ptr().cast(), slice.len());
+    R::write_slice(codes, writer)
+}
+
+/// Casts `&[u8]` to `&[T]` (see [`ViewElement::bytes_as_slice`]), checking
+/// that each value is a valid integer code of type `R` for the enum `T`,
+/// i.e. `from_repr` returns `Some(_)`.
+///
+/// If `from_repr` returns `None` for a code, returns an error which includes
+/// `type_name`, the code, and the index of the element.
+///
+/// # Safety
+///
+/// `T` must be a fieldless enum with `#[repr(R)]`, and `from_repr` must
+/// return `Some(_)` only for the discriminants of its variants.
+///
+/// # Panics
+///
+/// Panics if `T` and `R` have different sizes or alignments.
+pub unsafe fn view_enum_slice<'a, T, R>(
+    bytes: &'a [u8],
+    type_desc: &PyValue,
+    len: usize,
+    type_name: &'static str,
+    from_repr: impl Fn(R) -> Option<T>,
+) -> Result<&'a [T], ViewDataError>
+where
+    R: ViewElement + Copy + fmt::Display,
+{
+    assert_same_layout::<T, R>();
+    let codes = R::bytes_as_slice(bytes, type_desc, len)?;
+    check_valid_for_enum(codes, type_name, from_repr)?;
+    Ok(slice::from_raw_parts(codes.as_ptr().cast(), codes.len()))
+}
+
+/// Casts `&mut [u8]` to `&mut [T]` (see
+/// [`ViewMutElement::bytes_as_mut_slice`]), checking that each value is a
+/// valid integer code of type `R` for the enum `T`, i.e. `from_repr` returns
+/// `Some(_)`.
+///
+/// If `from_repr` returns `None` for a code, returns an error which includes
+/// `type_name`, the code, and the index of the element.
+///
+/// # Safety
+///
+/// `T` must be a fieldless enum with `#[repr(R)]`, and `from_repr` must
+/// return `Some(_)` only for the discriminants of its variants.
+///
+/// # Panics
+///
+/// Panics if `T` and `R` have different sizes or alignments.
+pub unsafe fn view_enum_mut_slice<'a, T, R>(
+    bytes: &'a mut [u8],
+    type_desc: &PyValue,
+    len: usize,
+    type_name: &'static str,
+    from_repr: impl Fn(R) -> Option<T>,
+) -> Result<&'a mut [T], ViewDataError>
+where
+    R: ViewMutElement + Copy + fmt::Display,
+{
+    assert_same_layout::<T, R>();
+    let codes = R::bytes_as_mut_slice(bytes, type_desc, len)?;
+    check_valid_for_enum(codes, type_name, from_repr)?;
+    Ok(slice::from_raw_parts_mut(
+        codes.as_mut_ptr().cast(),
+        codes.len(),
+    ))
+}
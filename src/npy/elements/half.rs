@@ -0,0 +1,107 @@
+//! Trait implementations for the half-precision types in the `half` crate.
+//!
+//! NumPy has a native descriptor for IEEE 754 half-precision floats (`f2`), but
+//! not for bfloat16. The [`ml_dtypes`] package, which is the de facto standard
+//! for bfloat16 with NumPy, saves bfloat16 arrays with a two-byte void
+//! descriptor (`<V2`) containing the bits of the values in little-endian
+//! order. `bf16` uses the same convention, so files written by this crate can
+//! be loaded with `np.load(...).view(ml_dtypes.bfloat16)` and vice versa. When
+//! reading or viewing, `|V2` is also interpreted as little-endian bfloat16,
+//! and `>V2` is interpreted as big-endian bfloat16.
+//!
+//! [`ml_dtypes::BFloat16`](crate::ml_dtypes::BFloat16) is the same format
+//! with the same descriptors, for use without the `half` feature. The two
+//! types convert into each other with `From`, and either of them can read
+//! files written with the other.
+//!
+//! [`ml_dtypes`]: https://github.com/jax-ml/ml_dtypes
+
+use super::{bfloat16_little_endian, bytes_as_mut_slice, bytes_as_slice, check_for_extra_bytes};
+use crate::{ReadDataError, ReadableElement, ViewDataError, ViewElement, ViewMutElement};
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
+use half::slice::HalfFloatSliceExt;
+use half::{bf16, f16};
+use py_literal::Value as PyValue;
+use std::io;
+
+macro_rules! impl_readable_half_multi_byte {
+    ($elem:ty, [$($little_desc:expr),*], [$($big_desc:expr),*], $zero:expr) => {
+        impl ReadableElement for $elem {
+            fn read_to_end_exact_vec<R: io::Read>(
+                mut reader: R,
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<Vec<Self>, ReadDataError> {
+                let mut out = vec![$zero; len];
+                let bits_slice: &mut [u16] = out.reinterpret_cast_mut();
+                match *type_desc {
+                    PyValue::String(ref s) if $(s == $little_desc)||* => {
+                        reader.read_u16_into::<LittleEndian>(bits_slice)?;
+                    }
+                    PyValue::String(ref s) if $(s == $big_desc)||* => {
+                        reader.read_u16_into::<BigEndian>(bits_slice)?;
+                    }
+                    ref other => {
+                        return Err(ReadDataError::WrongDescriptor(other.clone()));
+                    }
+                }
+                check_for_extra_bytes(&mut reader)?;
+                Ok(out)
+            }
+        }
+    };
+}
+
+macro_rules! impl_half_multi_byte {
+    ($elem:ty, $little_desc:expr, $big_desc:expr, $zero:expr) => {
+        impl_writable_element_always_valid_cast!($elem, $little_desc, $big_desc);
+        impl_readable_half_multi_byte!($elem, [$little_desc], [$big_desc], $zero);
+        #[cfg(target_endian = "little")]
+        impl_view_and_view_mut_always_valid_cast_multi_byte!($elem, $little_desc, $big_desc);
+        #[cfg(target_endian = "big")]
+        impl_view_and_view_mut_always_valid_cast_multi_byte!($elem, $big_desc, $little_desc);
+    };
+}
+
+impl_half_multi_byte!(f16, "<f2", ">f2", f16::ZERO);
+
+impl_writable_element_always_valid_cast!(bf16, "<V2", ">V2");
+impl_readable_half_multi_byte!(bf16, ["<V2", "|V2"], [">V2"], bf16::ZERO);
+
+impl ViewElement for bf16 {
+    fn bytes_as_slice<'a>(
+        bytes: &'a [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a [Self], ViewDataError> {
+        match bfloat16_little_endian(type_desc) {
+            Some(little_endian) => {
+                if little_endian != cfg!(target_endian = "little") {
+                    return Err(ViewDataError::NonNativeEndian);
+                }
+                // All bit patterns are valid for `bf16`.
+                unsafe { bytes_as_slice(bytes, len) }
+            }
+            None => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+impl ViewMutElement for bf16 {
+    fn bytes_as_mut_slice<'a>(
+        bytes: &'a mut [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a mut [Self], ViewDataError> {
+        match bfloat16_little_endian(type_desc) {
+            Some(little_endian) => {
+                if little_endian != cfg!(target_endian = "little") {
+                    return Err(ViewDataError::NonNativeEndian);
+                }
+                // All bit patterns are valid for `bf16`.
+                unsafe { bytes_as_mut_slice(bytes, len) }
+            }
+            None => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
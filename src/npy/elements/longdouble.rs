@@ -0,0 +1,333 @@
+//! Element types for reading NumPy's `longdouble` and `clongdouble` dtypes.
+//!
+//! On x86 and x86-64 Linux, `np.longdouble` is the x87 80-bit extended
+//! precision format, padded to 16 bytes (descriptor `<f16`) on x86-64 or to
+//! 12 bytes (descriptor `<f12`) on x86. `np.clongdouble` consists of two such
+//! values (descriptors `<c32` and `<c24`). Rust has no corresponding
+//! primitive type, so these element types decode the values in software and
+//! convert them to `f64` (or `Complex<f64>`). Only reading is supported.
+//!
+//! Note that on some other platforms (e.g. AArch64 Linux), `<f16` is the
+//! IEEE 754 quadruple precision format instead. Data in that format can't be
+//! distinguished from the x87 format by its descriptor and will be decoded
+//! incorrectly.
+//!
+//! Since `f64` has less precision and a smaller exponent range than the x87
+//! format, the conversion may lose information. The element types are
+//! generic over a [`ConversionMode`], which determines what happens in that
+//! case:
+//!
+//! * With [`Round`] (the default), values are rounded to the nearest `f64`
+//!   (ties to even), like a C cast from `long double` to `double`. Values
+//!   too large for `f64` become infinite.
+//!
+//! * With [`Exact`], reading fails with a
+//!   [`ParseData`](crate::ReadDataError::ParseData) error identifying the
+//!   first element which can't be represented exactly.
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::Array1;
+//! use ndarray_npy::longdouble::{Exact, LongDouble};
+//! use ndarray_npy::{ReadNpyError, ReadNpyExt};
+//!
+//! # fn example() -> Result<(), ReadNpyError> {
+//! let rounded = Array1::<LongDouble>::read_npy(std::fs::File::open("longdouble.npy")?)?;
+//! let values: Array1<f64> = rounded.mapv(LongDouble::get);
+//!
+//! // Fails if any value would be rounded.
+//! let _exact = Array1::<LongDouble<Exact>>::read_npy(std::fs::File::open("longdouble.npy")?)?;
+//! # Ok(())
+//! # }
+//! ```
+
+use super::check_for_extra_bytes;
+use crate::{ReadDataError, ReadableElement};
+use byteorder::{ByteOrder, LittleEndian};
+#[cfg(feature = "num-complex-0_4")]
+use num_complex_0_4::Complex;
+use py_literal::Value as PyValue;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::marker::PhantomData;
+
+/// Determines how [`LongDouble`] and [`ComplexLongDouble`] handle values
+/// that can't be represented exactly.
+pub trait ConversionMode {
+    /// Whether an error is returned for values that can't be represented
+    /// exactly.
+    const EXACT: bool;
+}
+
+/// Rounds values to the nearest representable value.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Round;
+
+impl ConversionMode for Round {
+    const EXACT: bool = false;
+}
+
+/// Returns an error for values that can't be represented exactly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Exact;
+
+impl ConversionMode for Exact {
+    const EXACT: bool = true;
+}
+
+/// An error indicating that a `long double` value can't be represented
+/// exactly as `f64`.
+#[derive(Debug)]
+struct InexactLongDoubleError {
+    index: usize,
+}
+
+impl Error for InexactLongDoubleError {}
+
+impl fmt::Display for InexactLongDoubleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "long double value in element {} cannot be represented exactly as f64",
+            self.index
+        )
+    }
+}
+
+impl From<InexactLongDoubleError> for ReadDataError {
+    fn from(err: InexactLongDoubleError) -> ReadDataError {
+        ReadDataError::ParseData(Box::new(err))
+    }
+}
+
+/// Returns the size in bytes of each (real) x87 extended precision value if
+/// `type_desc` is a `longdouble` descriptor (`kind` is `'f'`) or a
+/// `clongdouble` descriptor (`kind` is `'c'`).
+fn x87_size(type_desc: &PyValue, kind: char) -> Option<usize> {
+    let (desc_16, desc_12) = match kind {
+        'f' => ("<f16", "<f12"),
+        'c' => ("<c32", "<c24"),
+        _ => unreachable!(),
+    };
+    match type_desc {
+        PyValue::String(s) if s == desc_16 => Some(16),
+        PyValue::String(s) if s == desc_12 => Some(12),
+        _ => None,
+    }
+}
+
+/// Decodes a little-endian x87 extended precision value (the first 10 bytes
+/// of `bytes`) and converts it to the nearest `f64`.
+///
+/// Returns the value and whether the conversion was exact. NaNs are
+/// considered exact, even though their payload may not be preserved.
+fn decode_x87(bytes: &[u8]) -> (f64, bool) {
+    let mantissa = LittleEndian::read_u64(&bytes[0..8]);
+    let sign_exp = LittleEndian::read_u16(&bytes[8..10]);
+    let negative = sign_exp & 0x8000 != 0;
+    let exp = i32::from(sign_exp & 0x7fff);
+    let integer_bit = mantissa >> 63 != 0;
+    let signed = |x: f64| if negative { -x } else { x };
+    match exp {
+        0x7fff => {
+            if integer_bit && mantissa << 1 == 0 {
+                (signed(f64::INFINITY), true)
+            } else {
+                // NaNs, and the invalid pseudo-infinities and pseudo-NaNs.
+                (signed(f64::NAN), true)
+            }
+        }
+        // Denormals (and pseudo-denormals) have the same scale as the
+        // smallest normal exponent.
+        0 => round_to_f64(negative, mantissa, 1 - 16383 - 63),
+        // Unnormals are invalid operands on the 80387 and later.
+        _ if !integer_bit => (signed(f64::NAN), true),
+        _ => round_to_f64(negative, mantissa, exp - 16383 - 63),
+    }
+}
+
+/// Rounds `mantissa * 2^exp` (negated if `negative`) to the nearest `f64`,
+/// with ties to even.
+///
+/// Returns the value and whether the conversion was exact.
+fn round_to_f64(negative: bool, mantissa: u64, exp: i32) -> (f64, bool) {
+    let sign_bit = u64::from(negative) << 63;
+    if mantissa == 0 {
+        return (f64::from_bits(sign_bit), true);
+    }
+    // Normalize so that the value is `m * 2^(e - 63)` with the most
+    // significant bit of `m` set, i.e. the value is in `[2^e, 2^(e+1))`.
+    let leading_zeros = mantissa.leading_zeros();
+    let m = u128::from(mantissa << leading_zeros);
+    let e = exp + 63 - leading_zeros as i32;
+
+    // Number of low bits of `m` to discard, and biased exponent of the
+    // result (0 for subnormals).
+    let (shift, mut biased_exp) = if e >= -1022 {
+        (11, e + 1023)
+    } else {
+        // Subnormal, or so small that it rounds to zero. Shifts larger than
+        // 65 all discard every bit of `m` without reaching the halfway point.
+        ((11 + (-1022 - e)).min(66) as u32, 0)
+    };
+    let mut significand = m >> shift;
+    let remainder = m & ((1 << shift) - 1);
+    let half = 1 << (shift - 1);
+    if remainder > half || (remainder == half && significand & 1 == 1) {
+        significand += 1;
+    }
+    let exact = remainder == 0;
+    if biased_exp > 0 && significand == 1 << 53 {
+        significand >>= 1;
+        biased_exp += 1;
+    }
+    if biased_exp >= 0x7ff {
+        return (f64::from_bits(sign_bit | f64::INFINITY.to_bits()), false);
+    }
+    // For normal values, the implicit leading bit of `significand` is
+    // replaced by the exponent. For subnormal values, `significand` may have
+    // been rounded up to `2^52`, which correctly produces the smallest normal
+    // value.
+    let bits = if biased_exp > 0 {
+        ((biased_exp as u64) << 52) | (significand as u64 & ((1 << 52) - 1))
+    } else {
+        significand as u64
+    };
+    (f64::from_bits(sign_bit | bits), exact)
+}
+
+/// Reads `len` elements of `num_values` x87 values each from `reader`, which
+/// should contain no more data after the elements, and passes the decoded
+/// values of each element to `f`.
+fn read_x87<R, T, F, M>(
+    mut reader: R,
+    size: usize,
+    num_values: usize,
+    len: usize,
+    mut f: F,
+) -> Result<Vec<T>, ReadDataError>
+where
+    R: io::Read,
+    F: FnMut(&[f64]) -> T,
+    M: ConversionMode,
+{
+    // If the length in bytes overflows, the reader can't contain all of the
+    // data.
+    let elem_bytes = size * num_values;
+    let mut bytes = vec![
+        0;
+        elem_bytes
+            .checked_mul(len)
+            .ok_or(ReadDataError::MissingData)?
+    ];
+    reader.read_exact(&mut bytes)?;
+    check_for_extra_bytes(&mut reader)?;
+    let mut values = vec![0.; num_values];
+    bytes
+        .chunks_exact(elem_bytes)
+        .enumerate()
+        .map(|(index, elem)| {
+            for (value, bytes) in values.iter_mut().zip(elem.chunks_exact(size)) {
+                let (decoded, exact) = decode_x87(bytes);
+                if M::EXACT && !exact {
+                    return Err(InexactLongDoubleError { index }.into());
+                }
+                *value = decoded;
+            }
+            Ok(f(&values))
+        })
+        .collect()
+}
+
+/// A `long double` value (descriptor `<f16` or `<f12`) converted to `f64`.
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq, PartialOrd)]
+pub struct LongDouble<M: ConversionMode = Round> {
+    value: f64,
+    mode: PhantomData<M>,
+}
+
+impl<M: ConversionMode> LongDouble<M> {
+    /// Creates a new value.
+    pub const fn new(value: f64) -> Self {
+        LongDouble {
+            value,
+            mode: PhantomData,
+        }
+    }
+
+    /// Returns the value.
+    pub const fn get(self) -> f64 {
+        self.value
+    }
+}
+
+impl<M: ConversionMode> From<LongDouble<M>> for f64 {
+    fn from(value: LongDouble<M>) -> f64 {
+        value.value
+    }
+}
+
+impl<M: ConversionMode> ReadableElement for LongDouble<M> {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        let size = x87_size(type_desc, 'f')
+            .ok_or_else(|| ReadDataError::WrongDescriptor(type_desc.clone()))?;
+        read_x87::<_, _, _, M>(reader, size, 1, len, |values| LongDouble::new(values[0]))
+    }
+}
+
+/// A complex `long double` value (descriptor `<c32` or `<c24`) converted to
+/// `Complex<f64>`.
+///
+/// See the [module-level documentation](self) for details.
+#[cfg(feature = "num-complex-0_4")]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ComplexLongDouble<M: ConversionMode = Round> {
+    value: Complex<f64>,
+    mode: PhantomData<M>,
+}
+
+#[cfg(feature = "num-complex-0_4")]
+impl<M: ConversionMode> ComplexLongDouble<M> {
+    /// Creates a new value.
+    pub const fn new(value: Complex<f64>) -> Self {
+        ComplexLongDouble {
+            value,
+            mode: PhantomData,
+        }
+    }
+
+    /// Returns the value.
+    pub const fn get(self) -> Complex<f64> {
+        self.value
+    }
+}
+
+#[cfg(feature = "num-complex-0_4")]
+impl<M: ConversionMode> From<ComplexLongDouble<M>> for Complex<f64> {
+    fn from(value: ComplexLongDouble<M>) -> Complex<f64> {
+        value.value
+    }
+}
+
+#[cfg(feature = "num-complex-0_4")]
+impl<M: ConversionMode> ReadableElement for ComplexLongDouble<M> {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        let size = x87_size(type_desc, 'c')
+            .ok_or_else(|| ReadDataError::WrongDescriptor(type_desc.clone()))?;
+        read_x87::<_, _, _, M>(reader, size, 2, len, |values| {
+            ComplexLongDouble::new(Complex::new(values[0], values[1]))
+        })
+    }
+}
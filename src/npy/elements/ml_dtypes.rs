@@ -0,0 +1,306 @@
+//! Element types for the low-precision floating point formats of the
+//! [`ml_dtypes`] package.
+//!
+//! NumPy has no native dtypes for the formats commonly used in machine
+//! learning, so `ml_dtypes` saves them with void descriptors of the same size:
+//! bfloat16 arrays are saved as `<V2`, and float8 arrays are saved as `|V1`
+//! regardless of the float8 variant. The descriptor therefore doesn't
+//! identify the format; instead, the caller declares how to interpret it by
+//! choosing the element type:
+//!
+//! | Element type       | `ml_dtypes` type | Descriptor |
+//! |--------------------|------------------|------------|
+//! | [`BFloat16`]       | `bfloat16`       | `<V2`      |
+//! | [`Float8E4M3Fn`]   | `float8_e4m3fn`  | `\|V1`     |
+//! | [`Float8E5M2`]     | `float8_e5m2`    | `\|V1`     |
+//!
+//! The element types store the raw bits of the values, so reading, writing,
+//! and viewing don't need any conversion. Use [`MlFloat::to_f32`] to decode
+//! individual values, or [`read_npy_to_f32`] to read an array and decode all
+//! of its values.
+//!
+//! `BFloat16` has the same format and descriptors as the `bf16` type of the
+//! `half` feature, including interpreting `|V2` as little-endian and `>V2` as
+//! big-endian, so either type can read files written with the other.
+//! `BFloat16` only stores the bits and doesn't support arithmetic, so it's
+//! available without the `half` dependency. With the `half` feature, the two
+//! types convert into each other with `From`, without changing the bits.
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::{array, Array1};
+//! use ndarray_npy::ml_dtypes::{read_npy_to_f32, Float8E4M3Fn, MlFloat};
+//! use ndarray_npy::WriteNpyExt;
+//!
+//! let arr = array![Float8E4M3Fn::from_bits(0x38), Float8E4M3Fn::from_bits(0xc4)];
+//! assert_eq!(arr[1].to_f32(), -3.);
+//! let mut buf = Vec::new();
+//! arr.write_npy(&mut buf)?;
+//! let decoded: Array1<f32> = read_npy_to_f32::<Float8E4M3Fn, _, _>(&buf[..])?;
+//! assert_eq!(decoded, array![1., -3.]);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+//!
+//! [`ml_dtypes`]: https://github.com/jax-ml/ml_dtypes
+
+use super::bytes::{read_byte_strings, void_width};
+use super::{bfloat16_little_endian, bytes_as_mut_slice, bytes_as_slice};
+use crate::{
+    ReadDataError, ReadNpyError, ReadNpyExt, ReadableElement, ViewDataError, ViewElement,
+    ViewMutElement,
+};
+use ndarray::{Array, Dimension};
+use py_literal::Value as PyValue;
+use std::io;
+
+/// A low-precision floating point format which can be decoded to `f32`.
+pub trait MlFloat: Copy + ReadableElement {
+    /// Converts the value to `f32`. This is exact for all of the formats.
+    fn to_f32(self) -> f32;
+}
+
+/// Reads an `.npy` file of low-precision values and decodes them to `f32`.
+///
+/// The type parameter `F` declares how the values in the file are
+/// interpreted, e.g. `read_npy_to_f32::<Float8E5M2, _, _>(reader)` reads
+/// `|V1` data as `float8_e5m2` values.
+pub fn read_npy_to_f32<F, R, D>(reader: R) -> Result<Array<f32, D>, ReadNpyError>
+where
+    F: MlFloat,
+    R: io::Read,
+    D: Dimension,
+{
+    Ok(Array::<F, D>::read_npy(reader)?.mapv(F::to_f32))
+}
+
+/// Decodes a finite value of a small binary floating point format with the
+/// given number of exponent bits, mantissa bits, and exponent bias. The
+/// caller must handle infinities and NaNs.
+fn decode_minifloat(bits: u32, exp_bits: u32, man_bits: u32, bias: i32) -> f32 {
+    let negative = bits >> (exp_bits + man_bits) & 1 != 0;
+    let exp = (bits >> man_bits & ((1 << exp_bits) - 1)) as i32;
+    let man = bits & ((1 << man_bits) - 1);
+    let magnitude = if exp == 0 {
+        man as f32 * 2f32.powi(1 - bias - man_bits as i32)
+    } else {
+        ((1 << man_bits) | man) as f32 * 2f32.powi(exp - bias - man_bits as i32)
+    };
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+/// A bfloat16 value (`ml_dtypes.bfloat16`, descriptor `<V2`).
+///
+/// See the [module-level documentation](self) for details.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct BFloat16(u16);
+
+impl BFloat16 {
+    /// Creates a value from its bits.
+    pub const fn from_bits(bits: u16) -> Self {
+        BFloat16(bits)
+    }
+
+    /// Returns the bits of the value.
+    pub const fn to_bits(self) -> u16 {
+        self.0
+    }
+}
+
+impl MlFloat for BFloat16 {
+    fn to_f32(self) -> f32 {
+        f32::from_bits(u32::from(self.0) << 16)
+    }
+}
+
+impl From<BFloat16> for f32 {
+    fn from(value: BFloat16) -> f32 {
+        value.to_f32()
+    }
+}
+
+#[cfg(feature = "half")]
+impl From<half::bf16> for BFloat16 {
+    fn from(value: half::bf16) -> BFloat16 {
+        BFloat16(value.to_bits())
+    }
+}
+
+#[cfg(feature = "half")]
+impl From<BFloat16> for half::bf16 {
+    fn from(value: BFloat16) -> half::bf16 {
+        half::bf16::from_bits(value.0)
+    }
+}
+
+impl_writable_element_always_valid_cast!(BFloat16, "<V2", ">V2");
+
+impl ReadableElement for BFloat16 {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        let little_endian = bfloat16_little_endian(type_desc)
+            .ok_or_else(|| ReadDataError::WrongDescriptor(type_desc.clone()))?;
+        read_byte_strings(reader, 2, len, |bytes| {
+            let bytes = [bytes[0], bytes[1]];
+            BFloat16(if little_endian {
+                u16::from_le_bytes(bytes)
+            } else {
+                u16::from_be_bytes(bytes)
+            })
+        })
+    }
+}
+
+impl ViewElement for BFloat16 {
+    fn bytes_as_slice<'a>(
+        bytes: &'a [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a [Self], ViewDataError> {
+        match bfloat16_little_endian(type_desc) {
+            Some(little_endian) => {
+                if little_endian != cfg!(target_endian = "little") {
+                    return Err(ViewDataError::NonNativeEndian);
+                }
+                // All bit patterns are valid for `BFloat16`.
+                unsafe { bytes_as_slice(bytes, len) }
+            }
+            None => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+impl ViewMutElement for BFloat16 {
+    fn bytes_as_mut_slice<'a>(
+        bytes: &'a mut [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a mut [Self], ViewDataError> {
+        match bfloat16_little_endian(type_desc) {
+            Some(little_endian) => {
+                if little_endian != cfg!(target_endian = "little") {
+                    return Err(ViewDataError::NonNativeEndian);
+                }
+                // All bit patterns are valid for `BFloat16`.
+                unsafe { bytes_as_mut_slice(bytes, len) }
+            }
+            None => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+        }
+    }
+}
+
+macro_rules! impl_float8 {
+    ($(#[$meta:meta])* $name:ident, |$bits:ident| $to_f32:expr) => {
+        $(#[$meta])*
+        ///
+        /// See the [module-level documentation](self) for details.
+        #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+        #[repr(transparent)]
+        pub struct $name(u8);
+
+        impl $name {
+            /// Creates a value from its bits.
+            pub const fn from_bits(bits: u8) -> Self {
+                $name(bits)
+            }
+
+            /// Returns the bits of the value.
+            pub const fn to_bits(self) -> u8 {
+                self.0
+            }
+        }
+
+        impl MlFloat for $name {
+            fn to_f32(self) -> f32 {
+                let $bits = self.0;
+                $to_f32
+            }
+        }
+
+        impl From<$name> for f32 {
+            fn from(value: $name) -> f32 {
+                value.to_f32()
+            }
+        }
+
+        impl_writable_element_always_valid_cast!($name, "|V1", "|V1");
+
+        impl ReadableElement for $name {
+            fn read_to_end_exact_vec<R: io::Read>(
+                reader: R,
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<Vec<Self>, ReadDataError> {
+                match void_width(type_desc) {
+                    Some(1) => read_byte_strings(reader, 1, len, |bytes| $name(bytes[0])),
+                    _ => Err(ReadDataError::WrongDescriptor(type_desc.clone())),
+                }
+            }
+        }
+
+        impl ViewElement for $name {
+            fn bytes_as_slice<'a>(
+                bytes: &'a [u8],
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<&'a [Self], ViewDataError> {
+                match void_width(type_desc) {
+                    // All bit patterns are valid.
+                    Some(1) => unsafe { bytes_as_slice(bytes, len) },
+                    _ => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+                }
+            }
+        }
+
+        impl ViewMutElement for $name {
+            fn bytes_as_mut_slice<'a>(
+                bytes: &'a mut [u8],
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<&'a mut [Self], ViewDataError> {
+                match void_width(type_desc) {
+                    // All bit patterns are valid.
+                    Some(1) => unsafe { bytes_as_mut_slice(bytes, len) },
+                    _ => Err(ViewDataError::WrongDescriptor(type_desc.clone())),
+                }
+            }
+        }
+    };
+}
+
+impl_float8!(
+    /// A float8 value with 4 exponent bits and 3 mantissa bits
+    /// (`ml_dtypes.float8_e4m3fn`, descriptor `|V1`).
+    ///
+    /// This format has no infinities; the largest finite magnitude is 448,
+    /// and only the bit patterns `0x7f` and `0xff` are NaN.
+    Float8E4M3Fn,
+    |bits| if bits & 0x7f == 0x7f {
+        f32::NAN
+    } else {
+        decode_minifloat(bits.into(), 4, 3, 7)
+    }
+);
+
+impl_float8!(
+    /// A float8 value with 5 exponent bits and 2 mantissa bits
+    /// (`ml_dtypes.float8_e5m2`, descriptor `|V1`).
+    ///
+    /// This format follows the IEEE 754 conventions, with infinities and
+    /// NaNs.
+    Float8E5M2,
+    |bits| match (bits >> 2 & 0x1f, bits & 0x3) {
+        (0x1f, 0) if bits & 0x80 != 0 => f32::NEG_INFINITY,
+        (0x1f, 0) => f32::INFINITY,
+        (0x1f, _) => f32::NAN,
+        _ => decode_minifloat(bits.into(), 5, 2, 15),
+    }
+);
@@ -1,6 +1,8 @@
 //! Implementations of the `*Element` traits.
 
+use crate::dtype::{DType, ScalarDType};
 use crate::{ReadDataError, ViewDataError};
+use py_literal::Value as PyValue;
 use std::io;
 use std::mem;
 use std::slice;
@@ -18,6 +20,55 @@ fn check_for_extra_bytes<R: io::Read>(reader: &mut R) -> Result<(), ReadDataErro
     }
 }
 
+/// Returns `Some(true)` if `type_desc` is a little-endian `V2` descriptor,
+/// `Some(false)` if it's a big-endian one, and `None` otherwise. `|V2` is
+/// considered little-endian.
+///
+/// This is the bfloat16 convention of the `ml_dtypes` package, which is used
+/// by both `half::bf16` and [`ml_dtypes::BFloat16`].
+fn bfloat16_little_endian(type_desc: &PyValue) -> Option<bool> {
+    match type_desc {
+        PyValue::String(s) if s == "<V2" || s == "|V2" => Some(true),
+        PyValue::String(s) if s == ">V2" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parses `type_desc` as a scalar dtype, e.g. `'<U8'` or `'|S16'`.
+///
+/// Returns `None` if `type_desc` is not a valid scalar descriptor.
+fn parse_scalar_dtype(type_desc: &PyValue) -> Option<ScalarDType> {
+    match DType::from_type_descriptor(type_desc) {
+        Ok(DType::Scalar(scalar)) => Some(scalar),
+        _ => None,
+    }
+}
+
+/// Formats a subarray descriptor with the given base type descriptor and
+/// shape. If `base` is itself a subarray descriptor, the result is flattened
+/// so that its axes follow `shape`.
+pub(crate) fn subarray_descriptor(base: PyValue, shape: &[usize]) -> PyValue {
+    let (base, shape) = match DType::from_type_descriptor(&base) {
+        Ok(DType::Subarray {
+            base: inner,
+            shape: inner_shape,
+        }) => (
+            inner.to_type_descriptor(),
+            shape.iter().chain(&inner_shape).copied().collect(),
+        ),
+        _ => (base, shape.to_vec()),
+    };
+    PyValue::Tuple(vec![
+        base,
+        PyValue::Tuple(
+            shape
+                .into_iter()
+                .map(|n| PyValue::Integer(n.into()))
+                .collect(),
+        ),
+    ])
+}
+
 /// Returns `Ok(_)` iff a slice containing `bytes_len` bytes is the correct length to cast to
 /// a slice with element type `T` and length `len`.
 ///
@@ -197,6 +248,25 @@ macro_rules! impl_writable_element_always_valid_cast {
 }
 
 mod bool;
+mod bytes;
 #[cfg(feature = "num-complex-0_4")]
 mod complex;
+pub mod datetime;
+pub mod enums;
+#[cfg(feature = "half")]
+mod half;
+pub mod longdouble;
+pub mod ml_dtypes;
+mod newtype;
 mod num;
+#[cfg(feature = "ordered-float")]
+mod ordered_float;
+#[cfg(feature = "pickle")]
+mod pickle;
+pub mod record;
+mod string;
+mod subarray;
+
+pub use self::bytes::{ByteStringTooLongError, FixedBytes, Void};
+#[cfg(feature = "pickle")]
+pub use self::pickle::PyObject;
@@ -0,0 +1,212 @@
+//! A macro for implementing the element traits for newtypes.
+
+/// Implements the element traits for newtypes by delegating to the type they
+/// wrap.
+///
+/// Each newtype must be a tuple struct with a single field, e.g. `struct
+/// Meters(f64);`, and is stored exactly like its field, with the same
+/// descriptor. By default, the macro implements [`WritableElement`],
+/// [`ReadableElement`], [`ViewElement`], and [`ViewMutElement`], which
+/// requires the field's type to implement all of them. To implement only
+/// some of the traits (e.g. for a newtype wrapping `String`, which can't be
+/// viewed), list them after a colon.
+///
+/// The macro checks at compile time that the newtype has the same size and
+/// alignment as its field, which guarantees that they have the same layout.
+/// Adding `#[repr(transparent)]` to the newtype guarantees this as well.
+/// Generic newtypes are not supported.
+///
+/// Note that reading and viewing construct the newtype without upholding any
+/// additional invariants the newtype may have beyond those of its field.
+///
+/// [`WritableElement`]: crate::WritableElement
+/// [`ReadableElement`]: crate::ReadableElement
+/// [`ViewElement`]: crate::ViewElement
+/// [`ViewMutElement`]: crate::ViewMutElement
+///
+/// # Example
+///
+/// ```
+/// use ndarray::{array, Array1};
+/// use ndarray_npy::{impl_newtype_element, ReadNpyExt, WriteNpyExt};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// #[repr(transparent)]
+/// struct Meters(f64);
+///
+/// #[derive(Clone, Debug, PartialEq)]
+/// struct Label(String);
+///
+/// impl_newtype_element!(Meters(f64));
+/// impl_newtype_element!(Label(String): WritableElement, ReadableElement);
+///
+/// let arr = array![Meters(1.5), Meters(-2.)];
+/// let mut buf = Vec::new();
+/// arr.write_npy(&mut buf)?;
+/// assert_eq!(Array1::<f64>::read_npy(&buf[..])?, array![1.5, -2.]);
+/// assert_eq!(Array1::<Meters>::read_npy(&buf[..])?, arr);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[macro_export]
+macro_rules! impl_newtype_element {
+    ($name:ident($inner:ty)) => {
+        $crate::impl_newtype_element!(
+            $name($inner): WritableElement, ReadableElement, ViewElement, ViewMutElement
+        );
+    };
+    ($name:ident($inner:ty): $($trait:ident),+ $(,)?) => {
+        const _: () = {
+            // The newtype must be a tuple struct with a single field of type
+            // `$inner`. Since it also has the same size, the field is at
+            // offset zero and there's no padding, so the layouts are the
+            // same.
+            #[allow(dead_code)]
+            fn check_single_field(value: $name) -> $inner {
+                let $name(inner) = value;
+                inner
+            }
+            ::std::assert!(
+                ::std::mem::size_of::<$name>() == ::std::mem::size_of::<$inner>(),
+                "the newtype must have the same size as its field",
+            );
+            ::std::assert!(
+                ::std::mem::align_of::<$name>() == ::std::mem::align_of::<$inner>(),
+                "the newtype must have the same alignment as its field",
+            );
+        };
+        $($crate::impl_newtype_element!(@impl $trait, $name, $inner);)+
+    };
+    (@impl WritableElement, $name:ident, $inner:ty) => {
+        impl $crate::WritableElement for $name {
+            fn type_descriptor() -> $crate::record::PyValue {
+                <$inner as $crate::WritableElement>::type_descriptor()
+            }
+
+            fn write<W: ::std::io::Write>(
+                &self,
+                writer: W,
+            ) -> ::std::result::Result<(), $crate::WriteDataError> {
+                let $name(inner) = self;
+                <$inner as $crate::WritableElement>::write(inner, writer)
+            }
+
+            fn write_slice<W: ::std::io::Write>(
+                slice: &[Self],
+                writer: W,
+            ) -> ::std::result::Result<(), $crate::WriteDataError> {
+                <$inner as $crate::WritableElement>::write_slice(
+                    $crate::impl_newtype_element!(@inner_slice slice, $inner),
+                    writer,
+                )
+            }
+
+            fn array_type_descriptor<'a, I>(elems: I) -> $crate::record::PyValue
+            where
+                I: ::std::iter::IntoIterator<Item = &'a Self>,
+                Self: 'a,
+            {
+                <$inner as $crate::WritableElement>::array_type_descriptor(
+                    ::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(elems),
+                        |$name(inner)| inner,
+                    ),
+                )
+            }
+
+            fn write_with_descriptor<W: ::std::io::Write>(
+                &self,
+                type_desc: &$crate::record::PyValue,
+                writer: W,
+            ) -> ::std::result::Result<(), $crate::WriteDataError> {
+                let $name(inner) = self;
+                <$inner as $crate::WritableElement>::write_with_descriptor(inner, type_desc, writer)
+            }
+
+            fn write_slice_with_descriptor<W: ::std::io::Write>(
+                slice: &[Self],
+                type_desc: &$crate::record::PyValue,
+                writer: W,
+            ) -> ::std::result::Result<(), $crate::WriteDataError> {
+                <$inner as $crate::WritableElement>::write_slice_with_descriptor(
+                    $crate::impl_newtype_element!(@inner_slice slice, $inner),
+                    type_desc,
+                    writer,
+                )
+            }
+        }
+    };
+    (@impl ReadableElement, $name:ident, $inner:ty) => {
+        impl $crate::ReadableElement for $name {
+            fn read_to_end_exact_vec<R: ::std::io::Read>(
+                reader: R,
+                type_desc: &$crate::record::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<::std::vec::Vec<Self>, $crate::ReadDataError> {
+                let values =
+                    <$inner as $crate::ReadableElement>::read_to_end_exact_vec(reader, type_desc, len)?;
+                ::std::result::Result::Ok(
+                    ::std::iter::Iterator::collect(::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(values),
+                        $name,
+                    )),
+                )
+            }
+
+            fn read_to_end_exact_vec_with_shape<R: ::std::io::Read>(
+                reader: R,
+                type_desc: &$crate::record::PyValue,
+                shape: &[usize],
+                fortran: bool,
+            ) -> ::std::result::Result<::std::vec::Vec<Self>, $crate::ReadDataError> {
+                let values =
+                    <$inner as $crate::ReadableElement>::read_to_end_exact_vec_with_shape(
+                        reader, type_desc, shape, fortran,
+                    )?;
+                ::std::result::Result::Ok(
+                    ::std::iter::Iterator::collect(::std::iter::Iterator::map(
+                        ::std::iter::IntoIterator::into_iter(values),
+                        $name,
+                    )),
+                )
+            }
+        }
+    };
+    (@impl ViewElement, $name:ident, $inner:ty) => {
+        impl $crate::ViewElement for $name {
+            fn bytes_as_slice<'a>(
+                bytes: &'a [u8],
+                type_desc: &$crate::record::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<&'a [Self], $crate::ViewDataError> {
+                let inner = <$inner as $crate::ViewElement>::bytes_as_slice(bytes, type_desc, len)?;
+                // This is safe because `Self` has the same layout as `$inner`.
+                // (See the compile-time checks.)
+                ::std::result::Result::Ok(unsafe {
+                    ::std::slice::from_raw_parts(inner.as_ptr().cast::<Self>(), inner.len())
+                })
+            }
+        }
+    };
+    (@impl ViewMutElement, $name:ident, $inner:ty) => {
+        impl $crate::ViewMutElement for $name {
+            fn bytes_as_mut_slice<'a>(
+                bytes: &'a mut [u8],
+                type_desc: &$crate::record::PyValue,
+                len: usize,
+            ) -> ::std::result::Result<&'a mut [Self], $crate::ViewDataError> {
+                let inner =
+                    <$inner as $crate::ViewMutElement>::bytes_as_mut_slice(bytes, type_desc, len)?;
+                // This is safe because `Self` has the same layout as `$inner`.
+                // (See the compile-time checks.)
+                ::std::result::Result::Ok(unsafe {
+                    ::std::slice::from_raw_parts_mut(inner.as_mut_ptr().cast::<Self>(), inner.len())
+                })
+            }
+        }
+    };
+    (@inner_slice $slice:ident, $inner:ty) => {
+        // This is safe because `Self` has the same layout as `$inner`. (See
+        // the compile-time checks.)
+        unsafe { ::std::slice::from_raw_parts($slice.as_ptr().cast::<$inner>(), $slice.len()) }
+    };
+}
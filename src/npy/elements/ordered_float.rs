@@ -0,0 +1,130 @@
+//! Trait implementations for the float wrappers in the `ordered-float` crate.
+//!
+//! `OrderedFloat<T>` and `NotNan<T>` are `#[repr(transparent)]` wrappers, so
+//! they use the same descriptors as `f32` and `f64`. Every value is a valid
+//! `OrderedFloat`, while reading and viewing `NotNan` check that none of the
+//! values are NaN.
+
+use super::{bytes_as_mut_slice, bytes_as_slice};
+use crate::{ReadDataError, ReadableElement, ViewDataError, ViewElement, ViewMutElement};
+use num_traits::Float;
+use ordered_float::{NotNan, OrderedFloat};
+use py_literal::Value as PyValue;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// An error indicating that a value read as `NotNan` is NaN.
+#[derive(Debug)]
+struct NanValueError {
+    index: usize,
+}
+
+impl Error for NanValueError {}
+
+impl fmt::Display for NanValueError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NaN value in element {}", self.index)
+    }
+}
+
+impl From<NanValueError> for ReadDataError {
+    fn from(err: NanValueError) -> ReadDataError {
+        ReadDataError::ParseData(Box::new(err))
+    }
+}
+
+impl From<NanValueError> for ViewDataError {
+    fn from(err: NanValueError) -> ViewDataError {
+        ViewDataError::InvalidData(Box::new(err))
+    }
+}
+
+/// Returns `Ok(_)` iff none of the values are NaN.
+fn check_not_nan<T: Float>(values: &[T]) -> Result<(), NanValueError> {
+    match values.iter().position(|value| value.is_nan()) {
+        Some(index) => Err(NanValueError { index }),
+        None => Ok(()),
+    }
+}
+
+macro_rules! impl_ordered_float {
+    ($float:ty, $little_desc:expr, $big_desc:expr) => {
+        impl_writable_element_always_valid_cast!(OrderedFloat<$float>, $little_desc, $big_desc);
+        #[cfg(target_endian = "little")]
+        impl_view_and_view_mut_always_valid_cast_multi_byte!(
+            OrderedFloat<$float>,
+            $little_desc,
+            $big_desc
+        );
+        #[cfg(target_endian = "big")]
+        impl_view_and_view_mut_always_valid_cast_multi_byte!(
+            OrderedFloat<$float>,
+            $big_desc,
+            $little_desc
+        );
+
+        impl ReadableElement for OrderedFloat<$float> {
+            fn read_to_end_exact_vec<R: io::Read>(
+                reader: R,
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<Vec<Self>, ReadDataError> {
+                let values = <$float>::read_to_end_exact_vec(reader, type_desc, len)?;
+                Ok(values.into_iter().map(OrderedFloat).collect())
+            }
+        }
+
+        // `NotNan` is written like the underlying float, since every valid
+        // value is a valid float.
+        impl_writable_element_always_valid_cast!(NotNan<$float>, $little_desc, $big_desc);
+
+        impl ReadableElement for NotNan<$float> {
+            fn read_to_end_exact_vec<R: io::Read>(
+                reader: R,
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<Vec<Self>, ReadDataError> {
+                let values = <$float>::read_to_end_exact_vec(reader, type_desc, len)?;
+                check_not_nan(&values)?;
+                Ok(values
+                    .into_iter()
+                    // This is safe because none of the values are NaN. (See
+                    // the call to `check_not_nan` above.)
+                    .map(|value| unsafe { NotNan::new_unchecked(value) })
+                    .collect())
+            }
+        }
+
+        impl ViewElement for NotNan<$float> {
+            fn bytes_as_slice<'a>(
+                bytes: &'a [u8],
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<&'a [Self], ViewDataError> {
+                let values = <$float>::bytes_as_slice(bytes, type_desc, len)?;
+                check_not_nan(values)?;
+                // This is safe because `NotNan` is `#[repr(transparent)]` and
+                // none of the values are NaN.
+                unsafe { bytes_as_slice(bytes, len) }
+            }
+        }
+
+        impl ViewMutElement for NotNan<$float> {
+            fn bytes_as_mut_slice<'a>(
+                bytes: &'a mut [u8],
+                type_desc: &PyValue,
+                len: usize,
+            ) -> Result<&'a mut [Self], ViewDataError> {
+                let values = <$float>::bytes_as_mut_slice(bytes, type_desc, len)?;
+                check_not_nan(values)?;
+                // This is safe because `NotNan` is `#[repr(transparent)]` and
+                // none of the values are NaN.
+                unsafe { bytes_as_mut_slice(bytes, len) }
+            }
+        }
+    };
+}
+
+impl_ordered_float!(f32, "<f4", ">f4");
+impl_ordered_float!(f64, "<f8", ">f8");
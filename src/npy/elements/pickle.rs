@@ -0,0 +1,849 @@
+//! Reading object arrays (descriptor `|O`) saved with `allow_pickle=True`.
+//!
+//! NumPy saves object arrays by pickling the whole array after the header.
+//! The pickle calls `numpy.core.multiarray._reconstruct` to create the array
+//! and then sets its state, which includes the elements as a list of Python
+//! objects in C order.
+//!
+//! Unpickling arbitrary data is unsafe, because pickles can call arbitrary
+//! functions. So, this module implements only the subset of the pickle
+//! protocol (versions 2 and later) needed for object arrays of `None`,
+//! `bool`, `int`, `float`, `str`, `bytes`, and lists and tuples of those
+//! values. The only globals which are accepted are the few NumPy functions
+//! and types used to reconstruct the array, and these are recognized by name
+//! without calling anything. Any other opcode or global results in an error.
+
+use super::check_for_extra_bytes;
+use crate::{ReadDataError, ReadableElement};
+use py_literal::Value as PyValue;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Read};
+use std::rc::Rc;
+
+/// A Python object in an object array (descriptor `|O`).
+///
+/// Object arrays are read by decoding the pickle data written by
+/// `np.save(..., allow_pickle=True)`. Only a safe subset of the pickle
+/// protocol is supported (see below); reading never executes any code.
+///
+/// Supported objects are `None`, `bool`, `int` (if it fits in an `i64`),
+/// `float`, `str`, `bytes`, `list`, and `tuple`. Strings pickled by Python 2
+/// are decoded as ASCII, like `np.load` does by default. Reading an object
+/// array containing any other objects results in an error. Writing object
+/// arrays is not supported.
+///
+/// Objects which are referenced more than once in the pickle are copied, so
+/// to bound memory use with untrusted data, reading fails if the objects are
+/// much larger than the pickle itself (more than 256 times its length, where
+/// each object counts as 1 plus the length of its data for `str` and
+/// `bytes`).
+///
+/// # Example
+///
+/// ```no_run
+/// use ndarray::Array1;
+/// use ndarray_npy::{read_npy, PyObject};
+///
+/// let arr: Array1<PyObject> = read_npy("objects.npy")?;
+/// for obj in &arr {
+///     if let PyObject::Str(s) = obj {
+///         println!("{}", s);
+///     }
+/// }
+/// # Ok::<_, ndarray_npy::ReadNpyError>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub enum PyObject {
+    /// Python `None`.
+    None,
+    /// Python `bool`.
+    Bool(bool),
+    /// Python `int`.
+    Int(i64),
+    /// Python `float`.
+    Float(f64),
+    /// Python `str`.
+    Str(String),
+    /// Python `bytes`.
+    Bytes(Vec<u8>),
+    /// Python `list`.
+    List(Vec<PyObject>),
+    /// Python `tuple`.
+    Tuple(Vec<PyObject>),
+}
+
+/// An error decoding the pickle data of an object array.
+#[derive(Debug)]
+enum ParsePickleError {
+    /// The opcode is not in the supported subset of the pickle protocol.
+    UnsupportedOpcode { opcode: u8, offset: u64 },
+    /// The global is not one of the NumPy globals used to reconstruct arrays.
+    UnsupportedGlobal { module: String, name: String },
+    /// An integer doesn't fit in an `i64`.
+    IntegerTooLarge { offset: u64 },
+    /// The object can't be an element of `PyObject`.
+    UnsupportedObject(&'static str),
+    /// Lists or tuples are nested too deeply.
+    TooDeeplyNested,
+    /// The objects are too large relative to the length of the pickle, e.g.
+    /// because the same object is referenced many times.
+    TooLarge,
+    /// The pickle is malformed, or is not an object array.
+    Malformed(&'static str),
+    /// The number of elements doesn't match the header.
+    WrongLength { expected: usize, actual: usize },
+    /// The shape of the pickled array doesn't match the header.
+    WrongShape {
+        expected: Vec<usize>,
+        actual: Vec<usize>,
+    },
+}
+
+impl Error for ParsePickleError {}
+
+impl fmt::Display for ParsePickleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParsePickleError::UnsupportedOpcode { opcode, offset } => write!(
+                f,
+                "unsupported pickle opcode {:#04x} at offset {}",
+                opcode, offset
+            ),
+            ParsePickleError::UnsupportedGlobal { module, name } => {
+                write!(f, "unsupported pickle global {}.{}", module, name)
+            }
+            ParsePickleError::IntegerTooLarge { offset } => {
+                write!(f, "integer at offset {} does not fit in i64", offset)
+            }
+            ParsePickleError::UnsupportedObject(kind) => {
+                write!(f, "unsupported object in object array: {}", kind)
+            }
+            ParsePickleError::TooDeeplyNested => write!(f, "objects are nested too deeply"),
+            ParsePickleError::TooLarge => {
+                write!(
+                    f,
+                    "objects are too large relative to the length of the pickle"
+                )
+            }
+            ParsePickleError::Malformed(msg) => write!(f, "malformed pickle: {}", msg),
+            ParsePickleError::WrongLength { expected, actual } => write!(
+                f,
+                "pickled array has {} elements, but the header specifies {}",
+                actual, expected
+            ),
+            ParsePickleError::WrongShape { expected, actual } => write!(
+                f,
+                "pickled array has shape {:?}, but the header specifies {:?}",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl From<ParsePickleError> for ReadDataError {
+    fn from(err: ParsePickleError) -> ReadDataError {
+        ReadDataError::ParseData(Box::new(err))
+    }
+}
+
+/// Maximum nesting depth of lists and tuples.
+const MAX_DEPTH: usize = 256;
+
+/// Maximum nesting depth of lists and tuples while unpickling.
+///
+/// The list of elements and the state tuple which contains it add two levels
+/// to the depth of the elements.
+const MAX_UNPICKLE_DEPTH: usize = MAX_DEPTH + 2;
+
+/// Maximum size of the converted objects per byte of the pickle.
+///
+/// Objects which are referenced more than once through the memo are shared
+/// while unpickling, but they're copied when converted to `PyObject`s. A
+/// short pickle could otherwise expand exponentially, e.g. with a chain of
+/// lists which each contain the previous one twice. The size of an object is
+/// 1 plus the length of its data for `str` and `bytes`.
+const MAX_EXPANSION: u64 = 256;
+
+/// A global which may be referenced by the pickle of an object array.
+#[derive(Clone, Copy, Debug)]
+enum Global {
+    /// `numpy.core.multiarray._reconstruct` (`numpy._core` in NumPy 2).
+    Reconstruct,
+    /// `numpy.ndarray`.
+    NdArray,
+    /// `numpy.dtype`.
+    DType,
+    /// `_codecs.encode`, which protocol 2 uses to pickle `bytes` values.
+    CodecsEncode,
+}
+
+impl Global {
+    fn from_name(module: &str, name: &str) -> Result<Global, ParsePickleError> {
+        match (module, name) {
+            ("numpy.core.multiarray" | "numpy._core.multiarray", "_reconstruct") => {
+                Ok(Global::Reconstruct)
+            }
+            ("numpy", "ndarray") => Ok(Global::NdArray),
+            ("numpy", "dtype") => Ok(Global::DType),
+            ("_codecs", "encode") => Ok(Global::CodecsEncode),
+            _ => Err(ParsePickleError::UnsupportedGlobal {
+                module: module.to_owned(),
+                name: name.to_owned(),
+            }),
+        }
+    }
+}
+
+/// The state of an array created by `_reconstruct`.
+#[derive(Clone, Debug)]
+struct ArrayState {
+    shape: Vec<usize>,
+    fortran: bool,
+    /// The dtype descriptor, e.g. `O8`.
+    dtype: String,
+    /// The elements in C order.
+    elements: Vec<Value>,
+}
+
+/// A list on the stack of the unpickler.
+#[derive(Debug, Default)]
+struct List {
+    items: Vec<Value>,
+    /// The nesting depth of the list; see [`Value::depth`].
+    depth: usize,
+    /// Whether the list is an item of another list or tuple.
+    ///
+    /// Appending to the list is then an error, since it would change the
+    /// depth of the containing list or tuple. The pickler only does that for
+    /// lists which contain themselves.
+    nested: bool,
+}
+
+/// A value on the stack of the unpickler.
+#[derive(Clone, Debug)]
+enum Value {
+    None,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    /// Strings, bytes, and tuples are shared, so that values from the memo
+    /// can be cloned cheaply.
+    Str(Rc<str>),
+    Bytes(Rc<[u8]>),
+    /// Lists are shared, since they may be modified after being memoized.
+    List(Rc<RefCell<List>>),
+    /// A tuple and its nesting depth; see [`Value::depth`].
+    Tuple {
+        items: Rc<[Value]>,
+        depth: usize,
+    },
+    Global(Global),
+    /// A dtype created by `numpy.dtype`, with its descriptor.
+    DType(String),
+    /// An array created by `_reconstruct`, whose state is set by `BUILD`.
+    Array(Option<ArrayState>),
+}
+
+impl Value {
+    /// Creates a tuple, failing if it would be nested too deeply.
+    fn tuple(items: Vec<Value>) -> Result<Value, ParsePickleError> {
+        let depth = nest(&items)?;
+        Ok(Value::Tuple {
+            items: items.into(),
+            depth,
+        })
+    }
+
+    /// Returns the nesting depth of the value, which is 1 more than the
+    /// maximum depth of the items for lists and tuples, and 0 otherwise.
+    fn depth(&self) -> usize {
+        match self {
+            Value::List(list) => list.borrow().depth,
+            Value::Tuple { depth, .. } => *depth,
+            _ => 0,
+        }
+    }
+
+    /// Converts the value to a `PyObject`.
+    ///
+    /// The size of the result is subtracted from `budget`, failing if it
+    /// would become negative. See [`MAX_EXPANSION`].
+    fn to_py_object(&self, depth: usize, budget: &mut u64) -> Result<PyObject, ParsePickleError> {
+        if depth > MAX_DEPTH {
+            return Err(ParsePickleError::TooDeeplyNested);
+        }
+        let data_len = match self {
+            Value::Str(s) => s.len(),
+            Value::Bytes(b) => b.len(),
+            _ => 0,
+        };
+        *budget = budget
+            .checked_sub(1 + data_len as u64)
+            .ok_or(ParsePickleError::TooLarge)?;
+        let mut convert_all = |values: &[Value]| {
+            values
+                .iter()
+                .map(|value| value.to_py_object(depth + 1, budget))
+                .collect::<Result<Vec<_>, _>>()
+        };
+        Ok(match self {
+            Value::None => PyObject::None,
+            Value::Bool(b) => PyObject::Bool(*b),
+            Value::Int(i) => PyObject::Int(*i),
+            Value::Float(x) => PyObject::Float(*x),
+            Value::Str(s) => PyObject::Str(s.to_string()),
+            Value::Bytes(b) => PyObject::Bytes(b.to_vec()),
+            Value::List(list) => PyObject::List(convert_all(&list.borrow().items)?),
+            Value::Tuple { items, .. } => PyObject::Tuple(convert_all(items)?),
+            Value::Global(_) => return Err(ParsePickleError::UnsupportedObject("global")),
+            Value::DType(_) => return Err(ParsePickleError::UnsupportedObject("dtype")),
+            Value::Array(_) => return Err(ParsePickleError::UnsupportedObject("ndarray")),
+        })
+    }
+
+    fn into_str(self) -> Result<String, ParsePickleError> {
+        match self {
+            Value::Str(s) => Ok(s.to_string()),
+            _ => Err(ParsePickleError::Malformed("expected a string")),
+        }
+    }
+
+    fn into_tuple(self) -> Result<Vec<Value>, ParsePickleError> {
+        match self {
+            Value::Tuple { items, .. } => Ok(items.to_vec()),
+            _ => Err(ParsePickleError::Malformed("expected a tuple")),
+        }
+    }
+
+    fn into_shape(self) -> Result<Vec<usize>, ParsePickleError> {
+        self.into_tuple()?
+            .into_iter()
+            .map(|axis| match axis {
+                Value::Int(n) => usize::try_from(n)
+                    .map_err(|_| ParsePickleError::Malformed("negative axis length")),
+                _ => Err(ParsePickleError::Malformed("expected an axis length")),
+            })
+            .collect()
+    }
+}
+
+/// Returns the depth of a list or tuple containing `items`, and marks the
+/// lists among them as nested. Fails if the depth would be too large.
+fn nest(items: &[Value]) -> Result<usize, ParsePickleError> {
+    let mut depth = 1;
+    for item in items {
+        if let Value::List(list) = item {
+            list.borrow_mut().nested = true;
+        }
+        depth = depth.max(item.depth() + 1);
+    }
+    if depth > MAX_UNPICKLE_DEPTH {
+        return Err(ParsePickleError::TooDeeplyNested);
+    }
+    Ok(depth)
+}
+
+/// An unpickler for the subset of the pickle protocol used by object arrays.
+struct Unpickler<R> {
+    reader: R,
+    /// Number of bytes consumed from `reader`.
+    offset: u64,
+    stack: Vec<Value>,
+    /// Positions in `stack` of the marks.
+    marks: Vec<usize>,
+    /// The memo, which is a map so that large indices don't allocate.
+    memo: HashMap<usize, Value>,
+}
+
+impl<R: BufRead> Unpickler<R> {
+    fn new(reader: R) -> Self {
+        Unpickler {
+            reader,
+            offset: 0,
+            stack: Vec::new(),
+            marks: Vec::new(),
+            memo: HashMap::new(),
+        }
+    }
+
+    fn read_bytes(&mut self, len: u64) -> Result<Vec<u8>, ReadDataError> {
+        // The length is untrusted, so the buffer isn't allocated up front.
+        let mut buf = Vec::new();
+        (&mut self.reader).take(len).read_to_end(&mut buf)?;
+        if (buf.len() as u64) < len {
+            return Err(ReadDataError::MissingData);
+        }
+        self.offset += len;
+        Ok(buf)
+    }
+
+    fn read_array<const N: usize>(&mut self) -> Result<[u8; N], ReadDataError> {
+        let mut buf = [0; N];
+        self.reader.read_exact(&mut buf).map_err(|err| {
+            if err.kind() == io::ErrorKind::UnexpectedEof {
+                ReadDataError::MissingData
+            } else {
+                ReadDataError::Io(err)
+            }
+        })?;
+        self.offset += N as u64;
+        Ok(buf)
+    }
+
+    fn read_u8(&mut self) -> Result<u8, ReadDataError> {
+        Ok(self.read_array::<1>()?[0])
+    }
+
+    fn read_u32(&mut self) -> Result<u32, ReadDataError> {
+        Ok(u32::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_u64(&mut self) -> Result<u64, ReadDataError> {
+        Ok(u64::from_le_bytes(self.read_array()?))
+    }
+
+    fn read_line(&mut self) -> Result<String, ReadDataError> {
+        let mut line = Vec::new();
+        (&mut self.reader).take(256).read_until(b'\n', &mut line)?;
+        self.offset += line.len() as u64;
+        if line.pop() != Some(b'\n') {
+            return Err(ParsePickleError::Malformed("unterminated line").into());
+        }
+        String::from_utf8(line).map_err(|_| ParsePickleError::Malformed("invalid line").into())
+    }
+
+    fn read_str(&mut self, len: u64) -> Result<Value, ReadDataError> {
+        let bytes = self.read_bytes(len)?;
+        String::from_utf8(bytes)
+            .map(|s| Value::Str(s.into()))
+            .map_err(|_| ParsePickleError::Malformed("invalid UTF-8 in string").into())
+    }
+
+    /// Reads a Python 2 `str`, which is decoded as ASCII.
+    fn read_py2_str(&mut self, len: u64) -> Result<Value, ReadDataError> {
+        let bytes = self.read_bytes(len)?;
+        if !bytes.is_ascii() {
+            return Err(ParsePickleError::Malformed("non-ASCII Python 2 string").into());
+        }
+        Ok(Value::Str(String::from_utf8(bytes).unwrap().into()))
+    }
+
+    /// Reads a little-endian two's complement integer of `len` bytes.
+    fn read_long(&mut self, len: u64) -> Result<Value, ReadDataError> {
+        let offset = self.offset;
+        let bytes = self.read_bytes(len)?;
+        let negative = bytes.last().is_some_and(|&b| b & 0x80 != 0);
+        let fill = if negative { 0xff } else { 0 };
+        if bytes.len() > 8 && bytes[8..].iter().any(|&b| b != fill) {
+            return Err(ParsePickleError::IntegerTooLarge { offset }.into());
+        }
+        let mut buf = [fill; 8];
+        let n = bytes.len().min(8);
+        buf[..n].copy_from_slice(&bytes[..n]);
+        let value = i64::from_le_bytes(buf);
+        if (value < 0) != negative {
+            return Err(ParsePickleError::IntegerTooLarge { offset }.into());
+        }
+        Ok(Value::Int(value))
+    }
+
+    fn pop(&mut self) -> Result<Value, ParsePickleError> {
+        if self
+            .marks
+            .last()
+            .is_some_and(|&mark| mark >= self.stack.len())
+        {
+            return Err(ParsePickleError::Malformed("stack underflow"));
+        }
+        self.stack
+            .pop()
+            .ok_or(ParsePickleError::Malformed("stack underflow"))
+    }
+
+    fn pop_mark(&mut self) -> Result<Vec<Value>, ParsePickleError> {
+        let mark = self
+            .marks
+            .pop()
+            .ok_or(ParsePickleError::Malformed("missing mark"))?;
+        Ok(self.stack.split_off(mark))
+    }
+
+    fn top(&mut self) -> Result<&mut Value, ParsePickleError> {
+        self.stack
+            .last_mut()
+            .ok_or(ParsePickleError::Malformed("stack underflow"))
+    }
+
+    fn memo_put(&mut self, index: usize) -> Result<(), ParsePickleError> {
+        let value = self.top()?.clone();
+        self.memo.insert(index, value);
+        Ok(())
+    }
+
+    fn memo_get(&mut self, index: usize) -> Result<(), ParsePickleError> {
+        let value = self
+            .memo
+            .get(&index)
+            .cloned()
+            .ok_or(ParsePickleError::Malformed("missing memo entry"))?;
+        self.stack.push(value);
+        Ok(())
+    }
+
+    fn append(&mut self, items: Vec<Value>) -> Result<(), ParsePickleError> {
+        let list = match self.top()? {
+            Value::List(list) => Rc::clone(list),
+            _ => return Err(ParsePickleError::Malformed("append to non-list")),
+        };
+        if list.borrow().nested {
+            return Err(ParsePickleError::UnsupportedObject("recursive list"));
+        }
+        let depth = nest(&items)?;
+        let mut list = list.borrow_mut();
+        list.depth = list.depth.max(depth);
+        list.items.extend(items);
+        Ok(())
+    }
+
+    /// Applies a global to its arguments (the `REDUCE` opcode). This never
+    /// calls anything; the supported globals are interpreted structurally.
+    fn reduce(&mut self) -> Result<(), ParsePickleError> {
+        let args = self.pop()?.into_tuple()?;
+        let global = match self.pop()? {
+            Value::Global(global) => global,
+            _ => return Err(ParsePickleError::Malformed("reduce of non-global")),
+        };
+        let value = match (global, &args[..]) {
+            (Global::Reconstruct, [Value::Global(Global::NdArray), _, _]) => Value::Array(None),
+            (Global::DType, [Value::Str(descr), ..]) => Value::DType(descr.to_string()),
+            (Global::CodecsEncode, [Value::Str(s), Value::Str(encoding)])
+                if &**encoding == "latin1" =>
+            {
+                Value::Bytes(
+                    s.chars()
+                        .map(u8::try_from)
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| ParsePickleError::Malformed("invalid latin1 bytes"))?
+                        .into(),
+                )
+            }
+            _ => {
+                return Err(ParsePickleError::Malformed(
+                    "unexpected arguments of global",
+                ))
+            }
+        };
+        self.stack.push(value);
+        Ok(())
+    }
+
+    /// Sets the state of the object on top of the stack (the `BUILD` opcode).
+    fn build(&mut self) -> Result<(), ParsePickleError> {
+        let state = self.pop()?;
+        match self.top()? {
+            // The state of a dtype consists of its byte order, fields, etc.,
+            // none of which matter for object arrays.
+            Value::DType(_) => Ok(()),
+            Value::Array(array @ None) => {
+                let state = state.into_tuple()?;
+                let (shape, dtype, fortran, elements) = match <[Value; 5]>::try_from(state) {
+                    Ok([_version, shape, Value::DType(dtype), Value::Bool(fortran), elements]) => {
+                        (shape, dtype, fortran, elements)
+                    }
+                    _ => return Err(ParsePickleError::Malformed("unexpected ndarray state")),
+                };
+                let elements = match elements {
+                    Value::List(list) => list.borrow().items.clone(),
+                    _ => return Err(ParsePickleError::UnsupportedObject("non-object ndarray")),
+                };
+                *array = Some(ArrayState {
+                    shape: shape.into_shape()?,
+                    fortran,
+                    dtype,
+                    elements,
+                });
+                Ok(())
+            }
+            _ => Err(ParsePickleError::Malformed("unexpected BUILD")),
+        }
+    }
+
+    /// Runs the unpickler until the `STOP` opcode and returns the result.
+    fn load(&mut self) -> Result<Value, ReadDataError> {
+        loop {
+            let offset = self.offset;
+            let opcode = self.read_u8()?;
+            match opcode {
+                // PROTO
+                0x80 => {
+                    self.read_u8()?;
+                }
+                // FRAME
+                0x95 => {
+                    self.read_u64()?;
+                }
+                // STOP
+                b'.' => return Ok(self.pop()?),
+                // MARK
+                b'(' => self.marks.push(self.stack.len()),
+                // NONE, NEWTRUE, NEWFALSE
+                b'N' => self.stack.push(Value::None),
+                0x88 => self.stack.push(Value::Bool(true)),
+                0x89 => self.stack.push(Value::Bool(false)),
+                // BININT, BININT1, BININT2
+                b'J' => {
+                    let value = i32::from_le_bytes(self.read_array()?);
+                    self.stack.push(Value::Int(value.into()));
+                }
+                b'K' => {
+                    let value = self.read_u8()?;
+                    self.stack.push(Value::Int(value.into()));
+                }
+                b'M' => {
+                    let value = u16::from_le_bytes(self.read_array()?);
+                    self.stack.push(Value::Int(value.into()));
+                }
+                // LONG1, LONG4
+                0x8a => {
+                    let len = self.read_u8()?;
+                    let value = self.read_long(len.into())?;
+                    self.stack.push(value);
+                }
+                0x8b => {
+                    let len = self.read_u32()?;
+                    let value = self.read_long(len.into())?;
+                    self.stack.push(value);
+                }
+                // BINFLOAT
+                b'G' => {
+                    let value = f64::from_be_bytes(self.read_array()?);
+                    self.stack.push(Value::Float(value));
+                }
+                // SHORT_BINUNICODE, BINUNICODE, BINUNICODE8
+                0x8c => {
+                    let len = self.read_u8()?;
+                    let value = self.read_str(len.into())?;
+                    self.stack.push(value);
+                }
+                b'X' => {
+                    let len = self.read_u32()?;
+                    let value = self.read_str(len.into())?;
+                    self.stack.push(value);
+                }
+                0x8d => {
+                    let len = self.read_u64()?;
+                    let value = self.read_str(len)?;
+                    self.stack.push(value);
+                }
+                // SHORT_BINSTRING, BINSTRING (Python 2 `str`)
+                b'U' => {
+                    let len = self.read_u8()?;
+                    let value = self.read_py2_str(len.into())?;
+                    self.stack.push(value);
+                }
+                b'T' => {
+                    let len = self.read_u32()?;
+                    let value = self.read_py2_str(len.into())?;
+                    self.stack.push(value);
+                }
+                // SHORT_BINBYTES, BINBYTES, BINBYTES8
+                b'C' => {
+                    let len = self.read_u8()?;
+                    let value = self.read_bytes(len.into())?;
+                    self.stack.push(Value::Bytes(value.into()));
+                }
+                b'B' => {
+                    let len = self.read_u32()?;
+                    let value = self.read_bytes(len.into())?;
+                    self.stack.push(Value::Bytes(value.into()));
+                }
+                0x8e => {
+                    let len = self.read_u64()?;
+                    let value = self.read_bytes(len)?;
+                    self.stack.push(Value::Bytes(value.into()));
+                }
+                // EMPTY_LIST, APPEND, APPENDS
+                b']' => self.stack.push(Value::List(Rc::new(RefCell::new(List {
+                    depth: 1,
+                    ..List::default()
+                })))),
+                b'a' => {
+                    let item = self.pop()?;
+                    self.append(vec![item])?;
+                }
+                b'e' => {
+                    let items = self.pop_mark()?;
+                    self.append(items)?;
+                }
+                // EMPTY_TUPLE, TUPLE, TUPLE1, TUPLE2, TUPLE3
+                b')' => self.stack.push(Value::tuple(Vec::new())?),
+                b't' => {
+                    let items = self.pop_mark()?;
+                    self.stack.push(Value::tuple(items)?);
+                }
+                0x85..=0x87 => {
+                    let len = usize::from(opcode - 0x84);
+                    if self.stack.len() < len
+                        || self
+                            .marks
+                            .last()
+                            .is_some_and(|&mark| mark > self.stack.len() - len)
+                    {
+                        return Err(ParsePickleError::Malformed("stack underflow").into());
+                    }
+                    let items = self.stack.split_off(self.stack.len() - len);
+                    self.stack.push(Value::tuple(items)?);
+                }
+                // BINPUT, LONG_BINPUT, MEMOIZE
+                b'q' => {
+                    let index = self.read_u8()?;
+                    self.memo_put(index.into())?;
+                }
+                b'r' => {
+                    let index = self.read_u32()?;
+                    self.memo_put(index as usize)?;
+                }
+                0x94 => self.memo_put(self.memo.len())?,
+                // BINGET, LONG_BINGET
+                b'h' => {
+                    let index = self.read_u8()?;
+                    self.memo_get(index.into())?;
+                }
+                b'j' => {
+                    let index = self.read_u32()?;
+                    self.memo_get(index as usize)?;
+                }
+                // GLOBAL, STACK_GLOBAL
+                b'c' => {
+                    let module = self.read_line()?;
+                    let name = self.read_line()?;
+                    let global = Global::from_name(&module, &name)?;
+                    self.stack.push(Value::Global(global));
+                }
+                0x93 => {
+                    let name = self.pop()?.into_str()?;
+                    let module = self.pop()?.into_str()?;
+                    let global = Global::from_name(&module, &name)?;
+                    self.stack.push(Value::Global(global));
+                }
+                // REDUCE, BUILD
+                b'R' => self.reduce()?,
+                b'b' => self.build()?,
+                _ => return Err(ParsePickleError::UnsupportedOpcode { opcode, offset }.into()),
+            }
+        }
+    }
+}
+
+impl<R> Drop for Unpickler<R> {
+    fn drop(&mut self) {
+        // A list can contain itself (directly or indirectly) only through a
+        // reference from the memo, so clearing the memoized lists breaks any
+        // reference cycles, which would otherwise leak.
+        for value in self.memo.values() {
+            if let Value::List(list) = value {
+                list.borrow_mut().items.clear();
+            }
+        }
+    }
+}
+
+/// Reorders elements from C order to Fortran order.
+fn c_to_fortran_order<T: Clone>(elements: &[T], shape: &[usize]) -> Vec<T> {
+    let len = elements.len();
+    let mut out = Vec::with_capacity(len);
+    let mut index = vec![0; shape.len()];
+    for _ in 0..len {
+        // Compute the C-order position of the Fortran-order multi-index.
+        let pos = index.iter().zip(shape).fold(0, |pos, (&i, &n)| pos * n + i);
+        out.push(elements[pos].clone());
+        for (i, &n) in index.iter_mut().zip(shape) {
+            *i += 1;
+            if *i < n {
+                break;
+            }
+            *i = 0;
+        }
+    }
+    out
+}
+
+/// Reads the pickle of an object array with `len` elements.
+///
+/// If the shape and layout of the array in the header are known, the pickled
+/// shape must match the header's, and the elements are returned in the
+/// header's layout. Otherwise, they're returned in the layout of the pickled
+/// array.
+fn read_objects<R: io::Read>(
+    reader: R,
+    type_desc: &PyValue,
+    len: usize,
+    header_shape: Option<(&[usize], bool)>,
+) -> Result<Vec<PyObject>, ReadDataError> {
+    match type_desc {
+        PyValue::String(s) if s == "|O" || s == "O" => {}
+        _ => return Err(ReadDataError::WrongDescriptor(type_desc.clone())),
+    }
+    let mut unpickler = Unpickler::new(io::BufReader::new(reader));
+    let array = match unpickler.load()? {
+        Value::Array(Some(array)) if array.dtype.starts_with('O') => array,
+        _ => return Err(ParsePickleError::Malformed("expected an object array").into()),
+    };
+    check_for_extra_bytes(&mut unpickler.reader)?;
+    if let Some((shape, _)) = header_shape {
+        if array.shape != shape {
+            return Err(ParsePickleError::WrongShape {
+                expected: shape.to_vec(),
+                actual: array.shape,
+            }
+            .into());
+        }
+    }
+    let pickled_len = array
+        .shape
+        .iter()
+        .try_fold(1usize, |len, &axis_len| len.checked_mul(axis_len));
+    if array.elements.len() != len || pickled_len != Some(len) {
+        return Err(ParsePickleError::WrongLength {
+            expected: len,
+            actual: array.elements.len(),
+        }
+        .into());
+    }
+    // The pickled elements are in C order, but the header describes the data
+    // in Fortran order if the array was in Fortran order.
+    let fortran = header_shape.map_or(array.fortran, |(_, fortran)| fortran);
+    let elements = if fortran {
+        c_to_fortran_order(&array.elements, &array.shape)
+    } else {
+        array.elements
+    };
+    let mut budget = unpickler.offset.saturating_mul(MAX_EXPANSION);
+    Ok(elements
+        .iter()
+        .map(|value| value.to_py_object(0, &mut budget))
+        .collect::<Result<_, _>>()?)
+}
+
+impl ReadableElement for PyObject {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        read_objects(reader, type_desc, len, None)
+    }
+
+    fn read_to_end_exact_vec_with_shape<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        shape: &[usize],
+        fortran: bool,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        let len = shape.iter().product();
+        read_objects(reader, type_desc, len, Some((shape, fortran)))
+    }
+}
@@ -0,0 +1,527 @@
+//! Support for NumPy structured (record) dtypes.
+//!
+//! A structured dtype is described in the `.npy` header by a list of fields,
+//! e.g. `[('x', '<f4'), ('y', '<f4'), ('id', '<u8')]`. The fields are stored
+//! one after another in each element, in the order they are listed. Padding
+//! between fields (and at the end of each element) is described by fields
+//! with an empty name and a void type, e.g. `('', '|V4')`.
+//!
+//! The easiest way to read and write arrays of structs is to use
+//! `#[derive(NpyRecord)]` (which requires the `derive` feature). The types in
+//! this module are what the derived implementations use under the hood; they
+//! are public so that the element traits can also be implemented by hand for
+//! types the derive doesn't support.
+
+use super::{bytes_as_slice, check_for_extra_bytes};
+use crate::dtype::DType;
+use crate::{
+    ReadDataError, ReadableElement, ViewDataError, ViewElement, WritableElement, WriteDataError,
+};
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::mem::{self, MaybeUninit};
+use std::ptr;
+use std::slice;
+
+/// Re-export of the type used for descriptors, for use by derived
+/// implementations.
+pub use py_literal::Value as PyValue;
+
+/// A named field of a structured dtype.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordField {
+    /// Name of the field.
+    pub name: String,
+    /// Descriptor of the field's type, e.g. `'<f4'`.
+    pub type_descriptor: PyValue,
+    /// Offset of the field from the start of the element, in bytes.
+    pub offset: usize,
+    /// Size of the field, in bytes.
+    pub size: usize,
+}
+
+/// The layout of a structured dtype.
+///
+/// Only the named fields are stored; padding is implied by the gaps between
+/// the fields and by the total size of the element.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordLayout {
+    fields: Vec<RecordField>,
+    itemsize: usize,
+}
+
+impl RecordLayout {
+    /// Creates a layout with the given `(name, type_descriptor, offset)`
+    /// fields and total element size in bytes.
+    ///
+    /// Returns `None` if the size of a field's type can't be determined from
+    /// its descriptor, if the fields are not in order of increasing offset,
+    /// if any fields overlap, or if a field extends past `itemsize`.
+    pub fn with_offsets<I, N>(fields: I, itemsize: usize) -> Option<RecordLayout>
+    where
+        I: IntoIterator<Item = (N, PyValue, usize)>,
+        N: Into<String>,
+    {
+        let mut end = 0;
+        let fields = fields
+            .into_iter()
+            .map(|(name, type_descriptor, offset)| {
+                let size = DType::from_type_descriptor(&type_descriptor)
+                    .ok()?
+                    .item_size()?;
+                if offset < end {
+                    return None;
+                }
+                end = offset.checked_add(size)?;
+                Some(RecordField {
+                    name: name.into(),
+                    type_descriptor,
+                    offset,
+                    size,
+                })
+            })
+            .collect::<Option<Vec<_>>>()?;
+        if end > itemsize {
+            return None;
+        }
+        Some(RecordLayout { fields, itemsize })
+    }
+
+    /// Creates a layout with the given `(name, type_descriptor)` fields placed
+    /// one after another without padding.
+    ///
+    /// Returns `None` if the size of a field's type can't be determined from
+    /// its descriptor.
+    pub fn packed<I, N>(fields: I) -> Option<RecordLayout>
+    where
+        I: IntoIterator<Item = (N, PyValue)>,
+        N: Into<String>,
+    {
+        let mut offset = 0;
+        let fields = fields
+            .into_iter()
+            .map(|(name, type_descriptor)| {
+                let size = DType::from_type_descriptor(&type_descriptor)
+                    .ok()?
+                    .item_size()?;
+                let field = RecordField {
+                    name: name.into(),
+                    type_descriptor,
+                    offset,
+                    size,
+                };
+                offset = offset.checked_add(size)?;
+                Some(field)
+            })
+            .collect::<Option<Vec<_>>>()?;
+        Some(RecordLayout {
+            fields,
+            itemsize: offset,
+        })
+    }
+
+    /// Parses a list-form descriptor, e.g. `[('x', '<f4'), ('', '|V4'),
+    /// ('id', '<u8')]`.
+    ///
+    /// Fields with an empty name are treated as padding. A field name may
+    /// also be a `(title, name)` tuple, in which case the title is ignored.
+    /// The type descriptor of a field with a subarray shape, e.g. `('pos',
+    /// '<f8', (3,))`, is stored as a subarray descriptor, e.g. `('<f8',
+    /// (3,))`.
+    ///
+    /// Returns `None` if `type_desc` is not a list-form descriptor, or if the
+    /// size of a field's type can't be determined from its descriptor.
+    pub fn from_type_descriptor(type_desc: &PyValue) -> Option<RecordLayout> {
+        let entries = match type_desc {
+            PyValue::List(entries) => entries,
+            _ => return None,
+        };
+        let mut fields = Vec::with_capacity(entries.len());
+        let mut offset: usize = 0;
+        for entry in entries {
+            let (name, type_descriptor) = match entry {
+                PyValue::Tuple(parts) => match &parts[..] {
+                    [name, type_descriptor] => (field_name(name)?, type_descriptor.clone()),
+                    // A field with a subarray shape, e.g. `('pos', '<f8', (3,))`.
+                    [name, base, shape] => (
+                        field_name(name)?,
+                        PyValue::Tuple(vec![base.clone(), shape.clone()]),
+                    ),
+                    _ => return None,
+                },
+                _ => return None,
+            };
+            let size = DType::from_type_descriptor(&type_descriptor)
+                .ok()?
+                .item_size()?;
+            if !name.is_empty() {
+                fields.push(RecordField {
+                    name: name.to_owned(),
+                    type_descriptor,
+                    offset,
+                    size,
+                });
+            }
+            offset = offset.checked_add(size)?;
+        }
+        Some(RecordLayout {
+            fields,
+            itemsize: offset,
+        })
+    }
+
+    /// Formats the layout as a list-form descriptor, describing gaps between
+    /// fields with `('', '|V<n>')` padding fields like NumPy does.
+    pub fn to_type_descriptor(&self) -> PyValue {
+        let mut entries = Vec::with_capacity(self.fields.len());
+        let mut end = 0;
+        for field in &self.fields {
+            if field.offset > end {
+                entries.push(padding_entry(field.offset - end));
+            }
+            let name = PyValue::String(field.name.clone());
+            entries.push(PyValue::Tuple(
+                match DType::from_type_descriptor(&field.type_descriptor) {
+                    Ok(DType::Subarray { base, shape }) => {
+                        vec![name, base.to_type_descriptor(), shape_tuple(&shape)]
+                    }
+                    _ => vec![name, field.type_descriptor.clone()],
+                },
+            ));
+            end = field.offset + field.size;
+        }
+        if self.itemsize > end {
+            entries.push(padding_entry(self.itemsize - end));
+        }
+        PyValue::List(entries)
+    }
+
+    /// Returns the named fields, in order of increasing offset.
+    pub fn fields(&self) -> &[RecordField] {
+        &self.fields
+    }
+
+    /// Returns the field with the given name, if there is one.
+    pub fn field(&self, name: &str) -> Option<&RecordField> {
+        self.fields.iter().find(|field| field.name == name)
+    }
+
+    /// Returns the size of each element in bytes, including padding.
+    pub fn itemsize(&self) -> usize {
+        self.itemsize
+    }
+
+    /// Returns `true` iff the layout's named fields are exactly `names` (in
+    /// any order).
+    fn has_field_names(&self, names: &[&str]) -> bool {
+        self.fields.len() == names.len() && names.iter().all(|name| self.field(name).is_some())
+    }
+}
+
+/// Returns the name of a field from the first item of a list-form descriptor
+/// entry, which is either a name or a `(title, name)` tuple.
+fn field_name(name: &PyValue) -> Option<&str> {
+    match name {
+        PyValue::String(name) => Some(name),
+        PyValue::Tuple(parts) => match &parts[..] {
+            [_, PyValue::String(name)] => Some(name),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Formats a subarray shape as a Python tuple.
+fn shape_tuple(shape: &[usize]) -> PyValue {
+    PyValue::Tuple(shape.iter().map(|&n| PyValue::Integer(n.into())).collect())
+}
+
+/// Returns the list-form descriptor entry for `size` bytes of padding.
+fn padding_entry(size: usize) -> PyValue {
+    PyValue::Tuple(vec![
+        PyValue::String(String::new()),
+        PyValue::String(format!("|V{}", size)),
+    ])
+}
+
+/// An error writing a record with a layout that doesn't match it.
+#[derive(Debug)]
+enum FormatRecordError {
+    /// The descriptor is not a list-form descriptor.
+    NotRecordDescriptor(PyValue),
+    /// The layout has no field with the given name.
+    MissingField(String),
+    /// The field was written after a field with a larger offset.
+    FieldOutOfOrder(String),
+}
+
+impl Error for FormatRecordError {}
+
+impl fmt::Display for FormatRecordError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatRecordError::NotRecordDescriptor(desc) => {
+                write!(f, "descriptor {} is not a structured descriptor", desc)
+            }
+            FormatRecordError::MissingField(name) => {
+                write!(f, "descriptor has no field named {:?}", name)
+            }
+            FormatRecordError::FieldOutOfOrder(name) => {
+                write!(f, "field {:?} was written out of order", name)
+            }
+        }
+    }
+}
+
+impl From<FormatRecordError> for WriteDataError {
+    fn from(err: FormatRecordError) -> WriteDataError {
+        WriteDataError::FormatData(Box::new(err))
+    }
+}
+
+/// Writes records field by field, filling the padding with zero bytes.
+///
+/// For each record, call [`write_field`](Self::write_field) for the fields
+/// in order of increasing offset, then call
+/// [`finish_record`](Self::finish_record).
+pub struct RecordWriter<'a, W> {
+    layout: &'a RecordLayout,
+    writer: W,
+    /// Position within the current record.
+    pos: usize,
+}
+
+impl<'a, W: io::Write> RecordWriter<'a, W> {
+    /// Creates a writer for records with the given layout.
+    pub fn new(layout: &'a RecordLayout, writer: W) -> Self {
+        RecordWriter {
+            layout,
+            writer,
+            pos: 0,
+        }
+    }
+
+    /// Parses the list-form descriptor `type_desc` and calls `f` with a
+    /// writer for records with that layout.
+    pub fn with_type_descriptor<F>(
+        type_desc: &PyValue,
+        writer: W,
+        f: F,
+    ) -> Result<(), WriteDataError>
+    where
+        F: FnOnce(&mut RecordWriter<'_, W>) -> Result<(), WriteDataError>,
+    {
+        let layout = RecordLayout::from_type_descriptor(type_desc)
+            .ok_or_else(|| FormatRecordError::NotRecordDescriptor(type_desc.clone()))?;
+        f(&mut RecordWriter::new(&layout, writer))
+    }
+
+    /// Writes the field with the given name in the current record.
+    pub fn write_field<T>(&mut self, name: &str, value: &T) -> Result<(), WriteDataError>
+    where
+        T: WritableElement,
+    {
+        let field = self
+            .layout
+            .field(name)
+            .ok_or_else(|| FormatRecordError::MissingField(name.to_owned()))?;
+        if field.offset < self.pos {
+            return Err(FormatRecordError::FieldOutOfOrder(name.to_owned()).into());
+        }
+        self.write_zeros(field.offset - self.pos)?;
+        value.write_with_descriptor(&field.type_descriptor, &mut self.writer)?;
+        self.pos = field.offset + field.size;
+        Ok(())
+    }
+
+    /// Writes the trailing padding of the current record.
+    pub fn finish_record(&mut self) -> Result<(), WriteDataError> {
+        self.write_zeros(self.layout.itemsize - self.pos)?;
+        self.pos = 0;
+        Ok(())
+    }
+
+    fn write_zeros(&mut self, len: usize) -> Result<(), WriteDataError> {
+        const ZEROS: [u8; 64] = [0; 64];
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk = remaining.min(ZEROS.len());
+            self.writer.write_all(&ZEROS[..chunk])?;
+            remaining -= chunk;
+        }
+        Ok(())
+    }
+}
+
+/// The data of an array of records, split into fields when the fields are
+/// converted to their element types.
+pub struct RecordData {
+    type_desc: PyValue,
+    layout: RecordLayout,
+    bytes: Vec<u8>,
+    len: usize,
+}
+
+impl RecordData {
+    /// Reads `len` records from `reader`, which should contain no more data
+    /// after the records.
+    ///
+    /// Returns [`ReadDataError::WrongDescriptor`] unless `type_desc` is a
+    /// list-form descriptor whose named fields are exactly `field_names` (in
+    /// any order).
+    pub fn read<R: io::Read>(
+        mut reader: R,
+        type_desc: &PyValue,
+        len: usize,
+        field_names: &[&str],
+    ) -> Result<RecordData, ReadDataError> {
+        let layout = RecordLayout::from_type_descriptor(type_desc)
+            .filter(|layout| layout.has_field_names(field_names))
+            .ok_or_else(|| ReadDataError::WrongDescriptor(type_desc.clone()))?;
+        // If the length in bytes overflows, the reader can't contain all of
+        // the data.
+        let data_bytes = layout
+            .itemsize
+            .checked_mul(len)
+            .ok_or(ReadDataError::MissingData)?;
+        let mut bytes = vec![0; data_bytes];
+        reader.read_exact(&mut bytes)?;
+        check_for_extra_bytes(&mut reader)?;
+        Ok(RecordData {
+            type_desc: type_desc.clone(),
+            layout,
+            bytes,
+            len,
+        })
+    }
+
+    /// Returns the number of records.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` iff there are no records.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Converts the values of the field with the given name in all records
+    /// to the element type `T`.
+    ///
+    /// The byte order of the field is determined by its own descriptor, so
+    /// fields with different byte orders can be mixed in one record.
+    ///
+    /// **Panics** if there is no field with the given name.
+    pub fn field<T: ReadableElement>(&self, name: &str) -> Result<Vec<T>, ReadDataError> {
+        let field = self
+            .layout
+            .field(name)
+            .unwrap_or_else(|| panic!("no field named {:?}", name));
+        let mut column = Vec::with_capacity(field.size * self.len);
+        if field.size > 0 {
+            for record in self.bytes.chunks_exact(self.layout.itemsize) {
+                column.extend_from_slice(&record[field.offset..field.offset + field.size]);
+            }
+        }
+        T::read_to_end_exact_vec(&column[..], &field.type_descriptor, self.len).map_err(|err| {
+            match err {
+                // Report the descriptor of the whole record rather than of
+                // the field, since that's what's in the header.
+                ReadDataError::WrongDescriptor(_) => {
+                    ReadDataError::WrongDescriptor(self.type_desc.clone())
+                }
+                err => err,
+            }
+        })
+    }
+}
+
+/// Checks that the list-form descriptor `type_desc` describes records that
+/// can be viewed as `T`, and returns the layout.
+///
+/// The named fields of the descriptor must be exactly `fields`, which lists
+/// the `(name, offset)` of each field of `T`, and the element size must be
+/// `size_of::<T>()`. The caller must then check each field with
+/// [`check_view_field`].
+pub fn view_layout<T>(
+    type_desc: &PyValue,
+    fields: &[(&str, usize)],
+) -> Result<RecordLayout, ViewDataError> {
+    RecordLayout::from_type_descriptor(type_desc)
+        .filter(|layout| {
+            layout.itemsize == mem::size_of::<T>()
+                && layout.fields.len() == fields.len()
+                && fields.iter().all(|&(name, offset)| {
+                    layout
+                        .field(name)
+                        .is_some_and(|field| field.offset == offset)
+                })
+        })
+        .ok_or_else(|| ViewDataError::WrongDescriptor(type_desc.clone()))
+}
+
+/// Checks that the values of a field in `len` records stored in `bytes` are
+/// valid values of type `T`, using [`ViewElement::bytes_as_slice`].
+///
+/// This checks the field's descriptor (including the byte order) even if
+/// `len` is zero. The fields don't need to be aligned, so this works for
+/// packed structs. If `bytes` is too short to contain `len` records, only
+/// the records it contains are checked.
+pub fn check_view_field<T: ViewElement>(
+    bytes: &[u8],
+    layout: &RecordLayout,
+    type_desc: &PyValue,
+    name: &str,
+    len: usize,
+) -> Result<(), ViewDataError> {
+    let field = layout
+        .field(name)
+        .unwrap_or_else(|| panic!("no field named {:?}", name));
+    if field.size != mem::size_of::<T>() {
+        return Err(ViewDataError::WrongDescriptor(type_desc.clone()));
+    }
+    // Each value is copied into a properly aligned buffer before checking it.
+    let mut buf = MaybeUninit::<T>::uninit();
+    let check = |buf: &MaybeUninit<T>, len| {
+        // Safety: The caller initializes the first `len * size_of::<T>()`
+        // bytes of `buf`, where `len` is 0 or 1.
+        let buf_bytes =
+            unsafe { slice::from_raw_parts(buf.as_ptr().cast::<u8>(), len * field.size) };
+        T::bytes_as_slice(buf_bytes, &field.type_descriptor, len)
+            .map(|_| ())
+            .map_err(|err| match err {
+                ViewDataError::WrongDescriptor(_) => {
+                    ViewDataError::WrongDescriptor(type_desc.clone())
+                }
+                err => err,
+            })
+    };
+    check(&buf, 0)?;
+    if field.size > 0 {
+        for record in bytes.chunks_exact(layout.itemsize).take(len) {
+            let value = &record[field.offset..field.offset + field.size];
+            // Safety: `value` has length `size_of::<T>()`.
+            unsafe {
+                ptr::copy_nonoverlapping(value.as_ptr(), buf.as_mut_ptr().cast(), field.size)
+            };
+            check(&buf, 1)?;
+        }
+    }
+    Ok(())
+}
+
+/// Casts `&[u8]` to `&[T]`, where the resulting slice should have length
+/// `len`.
+///
+/// Returns `Err` if the length or alignment of `bytes` is incorrect.
+///
+/// # Safety
+///
+/// The caller must ensure that the cast is valid for the type `T`, e.g. by
+/// checking the layout with [`view_layout`] and every field of `T` with
+/// [`check_view_field`]. (This function checks only that the length and
+/// alignment are correct.)
+pub unsafe fn bytes_as_record_slice<T>(bytes: &[u8], len: usize) -> Result<&[T], ViewDataError> {
+    bytes_as_slice(bytes, len)
+}
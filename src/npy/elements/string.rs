@@ -0,0 +1,274 @@
+//! Trait implementations for `String`.
+//!
+//! NumPy's unicode string dtype (`U<n>`, e.g. `<U32`) stores each element as
+//! exactly `n` UCS-4 code units (i.e. Unicode code points encoded as 32-bit
+//! integers) in the specified byte order. Values shorter than `n` code points
+//! are padded with trailing NUL code points, which NumPy strips when the
+//! values are accessed.
+
+use super::{check_for_extra_bytes, parse_scalar_dtype};
+use crate::dtype::{self, Kind, ScalarDType};
+use crate::{ReadDataError, ReadableElement, WritableElement, WriteDataError};
+use byteorder::{BigEndian, ByteOrder, LittleEndian, NativeEndian};
+use py_literal::Value as PyValue;
+use std::error::Error;
+use std::fmt;
+use std::io;
+
+/// An error parsing a `String` from UCS-4 code units.
+#[derive(Debug)]
+struct ParseUcs4Error {
+    bad_value: u32,
+    index: usize,
+}
+
+impl Error for ParseUcs4Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for ParseUcs4Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "error parsing value {:#010x} as a Unicode code point in element {}",
+            self.bad_value, self.index
+        )
+    }
+}
+
+impl From<ParseUcs4Error> for ReadDataError {
+    fn from(err: ParseUcs4Error) -> ReadDataError {
+        ReadDataError::ParseData(Box::new(err))
+    }
+}
+
+/// An error formatting a `String` with a descriptor that doesn't fit it.
+#[derive(Debug)]
+enum FormatUcs4Error {
+    /// The descriptor is not a unicode string descriptor.
+    WrongDescriptor(PyValue),
+    /// The string has more code points than the width of the descriptor.
+    TooLong { len: usize, width: usize },
+}
+
+impl Error for FormatUcs4Error {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        None
+    }
+}
+
+impl fmt::Display for FormatUcs4Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FormatUcs4Error::WrongDescriptor(desc) => {
+                write!(f, "descriptor {} is not a unicode string descriptor", desc)
+            }
+            FormatUcs4Error::TooLong { len, width } => write!(
+                f,
+                "string of {} code points does not fit in width {}",
+                len, width
+            ),
+        }
+    }
+}
+
+impl From<FormatUcs4Error> for WriteDataError {
+    fn from(err: FormatUcs4Error) -> WriteDataError {
+        WriteDataError::FormatData(Box::new(err))
+    }
+}
+
+/// Byte order of UCS-4 code units.
+#[derive(Clone, Copy, PartialEq)]
+enum Ucs4Order {
+    Little,
+    Big,
+}
+
+impl Ucs4Order {
+    fn native() -> Ucs4Order {
+        if cfg!(target_endian = "little") {
+            Ucs4Order::Little
+        } else {
+            Ucs4Order::Big
+        }
+    }
+
+    fn read_u32(self, bytes: &[u8]) -> u32 {
+        match self {
+            Ucs4Order::Little => LittleEndian::read_u32(bytes),
+            Ucs4Order::Big => BigEndian::read_u32(bytes),
+        }
+    }
+}
+
+/// Returns the byte order and width (in code points) if `type_desc` is a
+/// unicode string descriptor.
+fn unicode_order_and_width(type_desc: &PyValue) -> Option<(Ucs4Order, usize)> {
+    let (order, item_size) = match parse_scalar_dtype(type_desc)? {
+        ScalarDType {
+            kind: Kind::Unicode,
+            byte_order: dtype::ByteOrder::Little,
+            item_size,
+            ..
+        } => (Ucs4Order::Little, item_size),
+        ScalarDType {
+            kind: Kind::Unicode,
+            byte_order: dtype::ByteOrder::Big,
+            item_size,
+            ..
+        } => (Ucs4Order::Big, item_size),
+        _ => return None,
+    };
+    // Each code point takes four bytes.
+    Some((order, item_size / 4))
+}
+
+/// Formats the native-endian unicode string descriptor for the given width.
+fn unicode_descriptor(width: usize) -> PyValue {
+    let order = match Ucs4Order::native() {
+        Ucs4Order::Little => '<',
+        Ucs4Order::Big => '>',
+    };
+    PyValue::String(format!("{}U{}", order, width))
+}
+
+/// Returns the number of code points in `s`.
+fn num_code_points(s: &str) -> usize {
+    s.chars().count()
+}
+
+/// Writes `s` as native-endian UCS-4 code units, padding with NUL code
+/// points to the width of `buf`.
+///
+/// `buf` is scratch space of four bytes per code point of the width, which
+/// is reused across elements.
+fn write_ucs4<W: io::Write>(writer: &mut W, s: &str, buf: &mut [u8]) -> Result<(), WriteDataError> {
+    let width = buf.len() / 4;
+    let len = num_code_points(s);
+    if len > width {
+        return Err(FormatUcs4Error::TooLong { len, width }.into());
+    }
+    let (code_units, padding) = buf.split_at_mut(len * 4);
+    for (c, code_unit) in s.chars().zip(code_units.chunks_exact_mut(4)) {
+        NativeEndian::write_u32(code_unit, u32::from(c));
+    }
+    padding.fill(0);
+    writer.write_all(buf)?;
+    Ok(())
+}
+
+/// `String` elements are written as unicode strings whose width is the
+/// number of code points in the longest element (at least 1, like NumPy).
+/// When reading, trailing NUL code points are stripped from each element.
+///
+/// Since the width isn't known from the type alone,
+/// [`type_descriptor`](WritableElement::type_descriptor) returns a width of
+/// 1, and [`write`](WritableElement::write) and
+/// [`write_slice`](WritableElement::write_slice) write elements with that
+/// width, so they return an error for elements longer than 1 code point. Use
+/// [`array_type_descriptor`](WritableElement::array_type_descriptor) and
+/// [`write_slice_with_descriptor`](WritableElement::write_slice_with_descriptor)
+/// to write elements with a wider descriptor.
+impl WritableElement for String {
+    fn type_descriptor() -> PyValue {
+        unicode_descriptor(1)
+    }
+
+    fn write<W: io::Write>(&self, writer: W) -> Result<(), WriteDataError> {
+        self.write_with_descriptor(&Self::type_descriptor(), writer)
+    }
+
+    fn write_slice<W: io::Write>(slice: &[Self], writer: W) -> Result<(), WriteDataError> {
+        Self::write_slice_with_descriptor(slice, &Self::type_descriptor(), writer)
+    }
+
+    fn array_type_descriptor<'a, I>(elems: I) -> PyValue
+    where
+        I: IntoIterator<Item = &'a Self>,
+    {
+        let width = elems
+            .into_iter()
+            .map(|s| num_code_points(s))
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        unicode_descriptor(width)
+    }
+
+    fn write_with_descriptor<W: io::Write>(
+        &self,
+        type_desc: &PyValue,
+        writer: W,
+    ) -> Result<(), WriteDataError> {
+        Self::write_slice_with_descriptor(std::slice::from_ref(self), type_desc, writer)
+    }
+
+    fn write_slice_with_descriptor<W: io::Write>(
+        slice: &[Self],
+        type_desc: &PyValue,
+        mut writer: W,
+    ) -> Result<(), WriteDataError> {
+        // Only native-endian descriptors are produced by
+        // `array_type_descriptor`.
+        let width = match unicode_order_and_width(type_desc) {
+            Some((order, width)) if order == Ucs4Order::native() => width,
+            _ => return Err(FormatUcs4Error::WrongDescriptor(type_desc.clone()).into()),
+        };
+        // The width comes from the item size, so this doesn't overflow.
+        let mut buf = vec![0; width * 4];
+        for elem in slice {
+            write_ucs4(&mut writer, elem, &mut buf)?;
+        }
+        Ok(())
+    }
+}
+
+impl ReadableElement for String {
+    fn read_to_end_exact_vec<R: io::Read>(
+        mut reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        let (order, width) = unicode_order_and_width(type_desc)
+            .ok_or_else(|| ReadDataError::WrongDescriptor(type_desc.clone()))?;
+
+        // If the length in bytes overflows, the reader can't contain all of
+        // the data.
+        let elem_bytes = width.checked_mul(4).ok_or(ReadDataError::MissingData)?;
+        let data_bytes = elem_bytes
+            .checked_mul(len)
+            .ok_or(ReadDataError::MissingData)?;
+        let mut bytes = vec![0; data_bytes];
+        reader.read_exact(&mut bytes)?;
+        check_for_extra_bytes(&mut reader)?;
+
+        if width == 0 {
+            return Ok(vec![String::new(); len]);
+        }
+        bytes
+            .chunks_exact(elem_bytes)
+            .enumerate()
+            .map(|(index, elem)| {
+                let code_units: Vec<u32> =
+                    elem.chunks_exact(4).map(|c| order.read_u32(c)).collect();
+                let trimmed_len = code_units
+                    .iter()
+                    .rposition(|&c| c != 0)
+                    .map_or(0, |i| i + 1);
+                code_units[..trimmed_len]
+                    .iter()
+                    .map(|&c| {
+                        char::from_u32(c).ok_or(ParseUcs4Error {
+                            bad_value: c,
+                            index,
+                        })
+                    })
+                    .collect::<Result<String, _>>()
+                    .map_err(ReadDataError::from)
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,173 @@
+//! Trait implementations for arrays (`[T; N]`).
+//!
+//! An array corresponds to a NumPy subarray dtype, e.g. `('<f4', (3,))` for
+//! `[f32; 3]`. Nested arrays correspond to multi-dimensional subarrays, e.g.
+//! `('<f4', (2, 3))` for `[[f32; 3]; 2]`. Arrays are most useful as fields of
+//! structured dtypes, e.g. `[('pos', '<f8', (3,))]`.
+
+use super::subarray_descriptor;
+use crate::dtype::DType;
+use crate::{
+    ReadDataError, ReadableElement, ViewDataError, ViewElement, ViewMutElement, WritableElement,
+    WriteDataError,
+};
+use py_literal::Value as PyValue;
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::slice;
+
+/// An error indicating that a descriptor passed to one of the
+/// `write_*_with_descriptor` methods is not a subarray descriptor of the
+/// right length.
+#[derive(Debug)]
+struct NotSubarrayDescriptorError {
+    type_desc: PyValue,
+    len: usize,
+}
+
+impl Error for NotSubarrayDescriptorError {}
+
+impl fmt::Display for NotSubarrayDescriptorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "descriptor {} is not a subarray descriptor with first axis of length {}",
+            self.type_desc, self.len
+        )
+    }
+}
+
+impl From<NotSubarrayDescriptorError> for WriteDataError {
+    fn from(err: NotSubarrayDescriptorError) -> WriteDataError {
+        WriteDataError::FormatData(Box::new(err))
+    }
+}
+
+/// Returns the descriptor of the items of a subarray descriptor whose first
+/// axis has length `len`.
+///
+/// For example, this returns `'<f4'` for `('<f4', (3,))` and `('<f4', (2,))`
+/// for `('<f4', (3, 2))` if `len` is 3.
+fn split_first_axis(type_desc: &PyValue, len: usize) -> Option<PyValue> {
+    let (base, shape) = match DType::from_type_descriptor(type_desc).ok()? {
+        DType::Subarray { base, shape } => (base.to_type_descriptor(), shape),
+        _ => return None,
+    };
+    match shape.split_first() {
+        Some((&first, [])) if first == len => Some(base),
+        Some((&first, rest)) if first == len => Some(subarray_descriptor(base, rest)),
+        _ => None,
+    }
+}
+
+impl<T: WritableElement, const N: usize> WritableElement for [T; N] {
+    fn type_descriptor() -> PyValue {
+        subarray_descriptor(T::type_descriptor(), &[N])
+    }
+
+    fn write<W: io::Write>(&self, writer: W) -> Result<(), WriteDataError> {
+        T::write_slice(self, writer)
+    }
+
+    fn write_slice<W: io::Write>(slice: &[Self], writer: W) -> Result<(), WriteDataError> {
+        T::write_slice(slice.as_flattened(), writer)
+    }
+
+    fn array_type_descriptor<'a, I>(elems: I) -> PyValue
+    where
+        I: IntoIterator<Item = &'a Self>,
+        Self: 'a,
+    {
+        subarray_descriptor(T::array_type_descriptor(elems.into_iter().flatten()), &[N])
+    }
+
+    fn write_with_descriptor<W: io::Write>(
+        &self,
+        type_desc: &PyValue,
+        writer: W,
+    ) -> Result<(), WriteDataError> {
+        Self::write_slice_with_descriptor(slice::from_ref(self), type_desc, writer)
+    }
+
+    fn write_slice_with_descriptor<W: io::Write>(
+        slice: &[Self],
+        type_desc: &PyValue,
+        writer: W,
+    ) -> Result<(), WriteDataError> {
+        let item_desc =
+            split_first_axis(type_desc, N).ok_or_else(|| NotSubarrayDescriptorError {
+                type_desc: type_desc.clone(),
+                len: N,
+            })?;
+        T::write_slice_with_descriptor(slice.as_flattened(), &item_desc, writer)
+    }
+}
+
+impl<T: ReadableElement, const N: usize> ReadableElement for [T; N] {
+    fn read_to_end_exact_vec<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        let item_desc = split_first_axis(type_desc, N)
+            .ok_or_else(|| ReadDataError::WrongDescriptor(type_desc.clone()))?;
+        // If the number of items overflows, the reader can't contain all of
+        // the data.
+        let items_len = len.checked_mul(N).ok_or(ReadDataError::MissingData)?;
+        let items =
+            T::read_to_end_exact_vec(reader, &item_desc, items_len).map_err(|err| match err {
+                ReadDataError::WrongDescriptor(_) => {
+                    ReadDataError::WrongDescriptor(type_desc.clone())
+                }
+                err => err,
+            })?;
+        let mut items = items.into_iter();
+        Ok((0..len)
+            .map(|_| std::array::from_fn(|_| items.next().unwrap()))
+            .collect())
+    }
+}
+
+impl<T: ViewElement, const N: usize> ViewElement for [T; N] {
+    fn bytes_as_slice<'a>(
+        bytes: &'a [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a [Self], ViewDataError> {
+        let item_desc = split_first_axis(type_desc, N)
+            .ok_or_else(|| ViewDataError::WrongDescriptor(type_desc.clone()))?;
+        let items_len = len
+            .checked_mul(N)
+            .expect("Required number of items should not overflow.");
+        let items = T::bytes_as_slice(bytes, &item_desc, items_len).map_err(|err| match err {
+            ViewDataError::WrongDescriptor(_) => ViewDataError::WrongDescriptor(type_desc.clone()),
+            err => err,
+        })?;
+        // `[T; N]` has the same layout as `N` consecutive `T` values.
+        Ok(unsafe { slice::from_raw_parts(items.as_ptr().cast(), len) })
+    }
+}
+
+impl<T: ViewMutElement, const N: usize> ViewMutElement for [T; N] {
+    fn bytes_as_mut_slice<'a>(
+        bytes: &'a mut [u8],
+        type_desc: &PyValue,
+        len: usize,
+    ) -> Result<&'a mut [Self], ViewDataError> {
+        let item_desc = split_first_axis(type_desc, N)
+            .ok_or_else(|| ViewDataError::WrongDescriptor(type_desc.clone()))?;
+        let items_len = len
+            .checked_mul(N)
+            .expect("Required number of items should not overflow.");
+        let items =
+            T::bytes_as_mut_slice(bytes, &item_desc, items_len).map_err(|err| match err {
+                ViewDataError::WrongDescriptor(_) => {
+                    ViewDataError::WrongDescriptor(type_desc.clone())
+                }
+                err => err,
+            })?;
+        // `[T; N]` has the same layout as `N` consecutive `T` values.
+        Ok(unsafe { slice::from_raw_parts_mut(items.as_mut_ptr().cast(), len) })
+    }
+}
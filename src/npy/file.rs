@@ -0,0 +1,647 @@
+//! A handle to an `.npy` file whose header has been parsed.
+
+use super::cast::{read_array_data_as, CastElement, CastPolicy};
+use super::dtype::{DType, ParseDTypeError};
+use super::dynamic::read_dyn_array_data;
+use super::header::{Header, Layout};
+use super::raw::{data_len, item_size, read_data_len, read_raw_data};
+use super::{read_array_data, DataShape, DynNpyArray};
+use crate::{ReadNpyError, ReadableElement};
+use ndarray::{Array, ArrayD, Axis, Dimension, IxDyn, Slice, SliceArg, SliceInfoElem};
+use py_literal::Value as PyValue;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{self, BufReader, SeekFrom};
+use std::ops::Range;
+use std::path::Path;
+
+/// An `.npy` file whose header has been parsed, for reading the data in
+/// various ways.
+///
+/// The header is parsed once, when the file is opened. Its properties are
+/// available without any further I/O, and each read method seeks to the
+/// data, so the methods can be called any number of times and in any order.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::{array, Array2};
+/// use ndarray_npy::cast::CastPolicy;
+/// use ndarray_npy::{NpyFile, WriteNpyExt};
+/// use std::io::Cursor;
+///
+/// let mut buf = Vec::new();
+/// array![[1i32, 2], [3, 4], [5, 6]].write_npy(&mut buf)?;
+///
+/// let mut file = NpyFile::new(Cursor::new(buf))?;
+/// assert_eq!(file.shape(), [3, 2]);
+/// assert_eq!(file.data_len(), Some(24));
+///
+/// let arr: Array2<i32> = file.read()?;
+/// assert_eq!(arr, array![[1, 2], [3, 4], [5, 6]]);
+/// let arr: Array2<f64> = file.read_as(CastPolicy::Lossless)?;
+/// assert_eq!(arr, array![[1., 2.], [3., 4.], [5., 6.]]);
+/// let rows: Array2<i32> = file.read_rows(1..3)?;
+/// assert_eq!(rows, array![[3, 4], [5, 6]]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct NpyFile<R> {
+    reader: R,
+    header: Header,
+    /// Position of the start of the `.npy` file in `reader`.
+    start: u64,
+    /// Offset of the data from the start of the `.npy` file.
+    data_offset: u64,
+}
+
+impl NpyFile<BufReader<File>> {
+    /// Opens the `.npy` file at the specified path and parses its header.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ReadNpyError> {
+        NpyFile::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: io::Read + io::Seek> NpyFile<R> {
+    /// Parses the header of the `.npy` file starting at the current position
+    /// of `reader`.
+    ///
+    /// The data is expected to extend to the end of `reader`, like for
+    /// [`ReadNpyExt::read_npy`](crate::ReadNpyExt::read_npy).
+    pub fn new(mut reader: R) -> Result<Self, ReadNpyError> {
+        let start = reader.stream_position()?;
+        let header = Header::from_reader(&mut reader)?;
+        let data_offset = reader.stream_position()? - start;
+        Ok(NpyFile {
+            reader,
+            header,
+            start,
+            data_offset,
+        })
+    }
+
+    /// Returns the header of the file.
+    pub fn header(&self) -> &Header {
+        &self.header
+    }
+
+    /// Returns the shape of the array.
+    pub fn shape(&self) -> &[usize] {
+        &self.header.shape
+    }
+
+    /// Returns the type descriptor of the array.
+    pub fn type_descriptor(&self) -> &PyValue {
+        &self.header.type_descriptor
+    }
+
+    /// Parses the type descriptor of the array.
+    pub fn dtype(&self) -> Result<DType, ParseDTypeError> {
+        self.header.dtype()
+    }
+
+    /// Returns the layout of the array.
+    pub fn layout(&self) -> Layout {
+        self.header.layout
+    }
+
+    /// Returns the offset in bytes of the data from the start of the file.
+    pub fn data_offset(&self) -> u64 {
+        self.data_offset
+    }
+
+    /// Returns the length in bytes of the data, as determined from the shape
+    /// and type descriptor.
+    ///
+    /// Returns `None` if the size of an element can't be determined from the
+    /// type descriptor (e.g. for object arrays) or if the length overflows
+    /// `isize`.
+    pub fn data_len(&self) -> Option<usize> {
+        data_len(&self.header).ok()
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Seeks to the position `offset` bytes after the start of the data.
+    fn seek_data(&mut self, offset: u64) -> io::Result<()> {
+        self.reader
+            .seek(SeekFrom::Start(self.start + self.data_offset + offset))?;
+        Ok(())
+    }
+
+    /// Reads `buf.len()` bytes of data, starting `offset` bytes after the
+    /// start of the data.
+    fn read_data_at(&mut self, offset: u64, buf: &mut [u8]) -> Result<(), ReadNpyError> {
+        read_exact_at(
+            &mut self.reader,
+            self.start + self.data_offset + offset,
+            buf,
+        )
+    }
+
+    /// Reads the array, like [`ReadNpyExt::read_npy`](crate::ReadNpyExt::read_npy).
+    pub fn read<A, D>(&mut self) -> Result<Array<A, D>, ReadNpyError>
+    where
+        A: ReadableElement,
+        D: Dimension,
+    {
+        self.seek_data(0)?;
+        read_array_data(&mut self.reader, &self.header)
+    }
+
+    /// Reads the array, choosing the element type based on the descriptor,
+    /// like [`read_npy_dyn`](crate::read_npy_dyn).
+    pub fn read_dyn(&mut self) -> Result<DynNpyArray, ReadNpyError> {
+        self.seek_data(0)?;
+        read_dyn_array_data(&mut self.reader, &self.header)
+    }
+
+    /// Reads the array, converting the elements to type `T` if the conversion
+    /// is allowed by `policy`, like [`read_npy_as`](crate::read_npy_as).
+    pub fn read_as<T, D>(&mut self, policy: CastPolicy) -> Result<Array<T, D>, ReadNpyError>
+    where
+        T: CastElement,
+        D: Dimension,
+    {
+        self.seek_data(0)?;
+        read_array_data_as(&mut self.reader, &self.header, policy)
+    }
+
+    /// Reads the undecoded data, like [`read_npy_raw`](crate::read_npy_raw).
+    pub fn read_raw(&mut self) -> Result<Vec<u8>, ReadNpyError> {
+        self.seek_data(0)?;
+        read_raw_data(&mut self.reader, &self.header)
+    }
+
+    /// Reads the undecoded data of the rows in `rows` (i.e. the indices along
+    /// the first axis), and returns it with a header describing it.
+    fn read_rows_raw(&mut self, rows: Range<usize>) -> Result<(Header, Vec<u8>), ReadNpyError> {
+        let shape = &self.header.shape;
+        assert!(!shape.is_empty(), "the array must have at least one axis");
+        assert!(
+            rows.start <= rows.end && rows.end <= shape[0],
+            "rows {:?} out of bounds for axis of length {}",
+            rows,
+            shape[0],
+        );
+        let item_size = item_size(&self.header.type_descriptor)
+            .ok_or_else(|| ReadNpyError::WrongDescriptor(self.header.type_descriptor.clone()))?;
+        read_data_len(&self.header)?;
+        let num_rows = rows.end - rows.start;
+        let row_len = shape[1..].iter().product::<usize>();
+        // The elements of a row are contiguous in standard layout. In Fortran
+        // layout, each of the `row_len` runs along the first axis is
+        // contiguous.
+        let (runs, run_len, run_stride, run_start) = match self.header.layout {
+            Layout::Standard => (1, num_rows * row_len, 0, rows.start * row_len),
+            Layout::Fortran => (row_len, num_rows, shape[0], rows.start),
+        };
+        let mut data = vec![0; runs * run_len * item_size];
+        if !data.is_empty() {
+            for (run, chunk) in data.chunks_exact_mut(run_len * item_size).enumerate() {
+                let offset = (run * run_stride + run_start) * item_size;
+                self.read_data_at(offset as u64, chunk)?;
+            }
+        }
+        let mut header = self.header.clone();
+        header.shape[0] = num_rows;
+        Ok((header, data))
+    }
+
+    /// Reads the rows in `rows`, i.e. the subarray with the indices in `rows`
+    /// along the first axis, without reading the rest of the data.
+    ///
+    /// This requires the size of an element to be determined from the type
+    /// descriptor, so it isn't supported for object arrays.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array has no axes or if `rows` is out of bounds for the
+    /// first axis.
+    pub fn read_rows<A, D>(&mut self, rows: Range<usize>) -> Result<Array<A, D>, ReadNpyError>
+    where
+        A: ReadableElement,
+        D: Dimension,
+    {
+        let (header, data) = self.read_rows_raw(rows)?;
+        read_array_data(&data[..], &header)
+    }
+
+    /// Reads the rows in `rows`, choosing the element type based on the
+    /// descriptor.
+    ///
+    /// See [`read_rows`](Self::read_rows) for details.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array has no axes or if `rows` is out of bounds for the
+    /// first axis.
+    pub fn read_rows_dyn(&mut self, rows: Range<usize>) -> Result<DynNpyArray, ReadNpyError> {
+        let (header, data) = self.read_rows_raw(rows)?;
+        read_dyn_array_data(&data[..], &header)
+    }
+}
+
+/// Reads `buf.len()` bytes starting at `position` in `reader`.
+fn read_exact_at<R>(reader: &mut R, position: u64, buf: &mut [u8]) -> Result<(), ReadNpyError>
+where
+    R: io::Read + io::Seek,
+{
+    reader.seek(SeekFrom::Start(position))?;
+    reader.read_exact(buf).map_err(|err| {
+        if err.kind() == io::ErrorKind::UnexpectedEof {
+            ReadNpyError::MissingData
+        } else {
+            ReadNpyError::Io(err)
+        }
+    })
+}
+
+impl<R: io::Read + io::Seek> NpyFile<R> {
+    /// Reads the region of the array selected by `info`, e.g. `s![3, ..,
+    /// 10..20]`, without reading the rest of the data.
+    ///
+    /// The selection is converted into the minimal number of contiguous
+    /// ranges of bytes in the file's layout (C or Fortran), and the reader
+    /// seeks past everything else. The data may be in either byte order.
+    /// Selections with negative steps and new axes are supported, with the
+    /// same semantics as [`ArrayRef::slice`](ndarray::ArrayRef::slice).
+    ///
+    /// This requires the size of an element to be determined from the type
+    /// descriptor, so it isn't supported for object arrays. If the number of
+    /// axes selected by `info` doesn't match the array, this returns
+    /// [`ReadNpyError::WrongNdim`].
+    ///
+    /// See [`read_npy_slice`] for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an index or range in `info` is out of bounds, like
+    /// [`ArrayRef::slice`](ndarray::ArrayRef::slice).
+    pub fn read_slice<A, I>(&mut self, info: I) -> Result<Array<A, I::OutDim>, ReadNpyError>
+    where
+        A: ReadableElement,
+        I: SliceArg<IxDyn>,
+    {
+        let in_ndim = info.in_ndim();
+        let data_shape = DataShape::new(&self.header, Some(in_ndim));
+        let ndim = data_shape.shape.ndim();
+        if ndim != in_ndim {
+            return Err(ReadNpyError::WrongNdim(Some(in_ndim), ndim));
+        }
+        read_data_len(&self.header)?;
+
+        // The selected indices along each axis in increasing order, and the
+        // slice of the selected elements which produces the requested array.
+        let mut indices = Vec::with_capacity(ndim);
+        let mut grid_info = Vec::with_capacity(info.as_ref().len());
+        for &elem in info.as_ref() {
+            let reversed = match elem {
+                SliceInfoElem::NewAxis => {
+                    grid_info.push(SliceInfoElem::NewAxis);
+                    continue;
+                }
+                SliceInfoElem::Index(_) => {
+                    grid_info.push(SliceInfoElem::Index(0));
+                    false
+                }
+                SliceInfoElem::Slice { step, .. } => {
+                    grid_info.push(Slice::new(0, None, step.signum()).into());
+                    step < 0
+                }
+            };
+            // Slicing the indices of the axis handles negative indices and
+            // checks the bounds in the same way as slicing an array.
+            let axis_len = data_shape.shape[indices.len()];
+            let mut selected = ArrayD::from_shape_fn(IxDyn(&[axis_len]), |index| index[0])
+                .slice_move(&[elem][..])
+                .into_iter()
+                .collect::<Vec<_>>();
+            if reversed {
+                selected.reverse();
+            }
+            indices.push(selected);
+        }
+
+        let grid = read_grid(
+            &mut self.reader,
+            self.start + self.data_offset,
+            &data_shape,
+            &indices,
+        )?;
+        let out_ndim = info.out_ndim();
+        grid.slice_move(&grid_info[..])
+            .into_dimensionality()
+            .map_err(|_| ReadNpyError::WrongNdim(I::OutDim::NDIM, out_ndim))
+    }
+
+    /// Reads the rows with the given indices along the first axis, e.g. to
+    /// sample a mini-batch, without reading the rest of the data.
+    ///
+    /// The rows are returned in the order of `rows`, which may contain
+    /// duplicates. The indices are sorted and deduplicated, and rows which
+    /// are adjacent in the file are read together, so the reader only seeks
+    /// forward. The data may be in either byte order and layout, but rows
+    /// are contiguous only in standard layout, so that's the most efficient.
+    ///
+    /// This requires the size of an element to be determined from the type
+    /// descriptor, so it isn't supported for object arrays.
+    ///
+    /// See [`gather_rows`] for an example.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array has no axes or if any of the indices are out of
+    /// bounds for the first axis.
+    pub fn gather_rows<A, D>(&mut self, rows: &[usize]) -> Result<Array<A, D>, ReadNpyError>
+    where
+        A: ReadableElement + Clone,
+        D: Dimension,
+    {
+        let data_shape = DataShape::new(&self.header, D::NDIM);
+        let shape = &data_shape.shape;
+        assert!(shape.ndim() > 0, "the array must have at least one axis");
+        read_data_len(&self.header)?;
+
+        let mut unique = rows.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        if let Some(&last) = unique.last() {
+            assert!(
+                last < shape[0],
+                "row index {} out of bounds for axis of length {}",
+                last,
+                shape[0],
+            );
+        }
+        let ndim = shape.ndim();
+        let mut indices = vec![unique];
+        indices.extend(shape.slice()[1..].iter().map(|&len| (0..len).collect()));
+        let grid = read_grid(
+            &mut self.reader,
+            self.start + self.data_offset,
+            &data_shape,
+            &indices,
+        )?;
+        let positions: Vec<usize> = rows
+            .iter()
+            .map(|row| indices[0].binary_search(row).unwrap())
+            .collect();
+        grid.select(Axis(0), &positions)
+            .into_dimensionality()
+            .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, ndim))
+    }
+}
+
+/// Reads the elements at the Cartesian product of `indices`, which contains
+/// the selected indices along each axis in increasing order, without reading
+/// the rest of the data.
+///
+/// The selection is converted into the minimal number of contiguous ranges of
+/// bytes in the file's layout. `data_start` is the position of the start of
+/// the data in `reader`. The caller must first check that the length of the
+/// data doesn't overflow.
+fn read_grid<A, R>(
+    reader: &mut R,
+    data_start: u64,
+    data_shape: &DataShape<'_>,
+    indices: &[Vec<usize>],
+) -> Result<ArrayD<A>, ReadNpyError>
+where
+    A: ReadableElement,
+    R: io::Read + io::Seek,
+{
+    let ndim = data_shape.shape.ndim();
+    let item_size = item_size(&data_shape.type_descriptor).ok_or_else(|| {
+        ReadNpyError::WrongDescriptor(data_shape.type_descriptor.clone().into_owned())
+    })?;
+    let memory_order = data_shape.memory_order();
+    let mut strides = vec![0; ndim];
+    let mut stride = 1;
+    for &axis in &memory_order {
+        strides[axis] = stride;
+        stride *= data_shape.shape[axis];
+    }
+
+    // Merge the fastest-varying axes into runs of adjacent elements, as
+    // long as the selection along each of them is consecutive, and the
+    // selection along the faster ones covers the whole axis.
+    let mut run_len = 1;
+    let mut num_merged = 0;
+    for &axis in &memory_order {
+        let selected = &indices[axis];
+        if !selected.windows(2).all(|pair| pair[1] == pair[0] + 1) {
+            break;
+        }
+        run_len *= selected.len();
+        num_merged += 1;
+        if selected.len() != data_shape.shape[axis] {
+            break;
+        }
+    }
+    let (merged_axes, outer_axes) = memory_order.split_at(num_merged);
+
+    let grid_shape = IxDyn(&indices.iter().map(Vec::len).collect::<Vec<_>>());
+    let len = grid_shape.size();
+    let mut data = vec![0; len * item_size];
+    if len > 0 {
+        let run_start: usize = merged_axes
+            .iter()
+            .map(|&axis| indices[axis][0] * strides[axis])
+            .sum();
+        // Positions along the outer axes, which are iterated in memory
+        // order so that the reader only seeks forward. Consecutive runs
+        // which are adjacent in the file are read together.
+        let mut position = vec![0; outer_axes.len()];
+        let mut pending: Option<(usize, Range<usize>)> = None;
+        let mut buf_start = 0;
+        loop {
+            let offset = run_start
+                + outer_axes
+                    .iter()
+                    .zip(&position)
+                    .map(|(&axis, &i)| indices[axis][i] * strides[axis])
+                    .sum::<usize>();
+            let buf_end = buf_start + run_len * item_size;
+            pending = match pending {
+                Some((file_start, buf_range))
+                    if file_start + buf_range.len() == offset * item_size =>
+                {
+                    Some((file_start, buf_range.start..buf_end))
+                }
+                Some((file_start, buf_range)) => {
+                    read_exact_at(reader, data_start + file_start as u64, &mut data[buf_range])?;
+                    Some((offset * item_size, buf_start..buf_end))
+                }
+                None => Some((offset * item_size, buf_start..buf_end)),
+            };
+            buf_start = buf_end;
+            let next = outer_axes.iter().zip(&mut position).position(|(&axis, i)| {
+                *i += 1;
+                if *i < indices[axis].len() {
+                    true
+                } else {
+                    *i = 0;
+                    false
+                }
+            });
+            if next.is_none() {
+                break;
+            }
+        }
+        if let Some((file_start, buf_range)) = pending {
+            read_exact_at(reader, data_start + file_start as u64, &mut data[buf_range])?;
+        }
+    }
+
+    let elems = A::read_to_end_exact_vec(&data[..], &data_shape.type_descriptor, len)?;
+    let grid_shape = DataShape {
+        type_descriptor: Cow::Borrowed(&data_shape.type_descriptor),
+        shape: grid_shape,
+        ..*data_shape
+    };
+    Ok(ArrayD::from_shape_vec(grid_shape.stride_shape(), elems).unwrap())
+}
+
+/// Reads the region selected by `info`, e.g. `s![3, .., 10..20]`, of the
+/// `.npy` file starting at the current position of `reader`, without reading
+/// the rest of the data.
+///
+/// This is a convenience function for [`NpyFile::new`] followed by
+/// [`NpyFile::read_slice`]; see the latter for details.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::{s, Array};
+/// use ndarray_npy::{read_npy_slice, WriteNpyExt};
+/// use std::io::Cursor;
+///
+/// let arr = Array::from_shape_fn((10, 4, 5), |(t, i, j)| (t * 100 + i * 10 + j) as f32);
+/// let mut buf = Vec::new();
+/// arr.write_npy(&mut buf)?;
+///
+/// let frame = read_npy_slice::<f32, _, _>(Cursor::new(&buf), s![7, .., 1..;2])?;
+/// assert_eq!(frame, arr.slice(s![7, .., 1..;2]));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Panics
+///
+/// Panics if an index or range in `info` is out of bounds, like
+/// [`ArrayRef::slice`](ndarray::ArrayRef::slice).
+pub fn read_npy_slice<A, R, I>(reader: R, info: I) -> Result<Array<A, I::OutDim>, ReadNpyError>
+where
+    A: ReadableElement,
+    R: io::Read + io::Seek,
+    I: SliceArg<IxDyn>,
+{
+    NpyFile::new(reader)?.read_slice(info)
+}
+
+/// Reads the rows with the given indices along the first axis of the `.npy`
+/// file starting at the current position of `reader`, without reading the
+/// rest of the data.
+///
+/// This is a convenience function for [`NpyFile::new`] followed by
+/// [`NpyFile::gather_rows`]; see the latter for details. It only requires
+/// [`Read`](io::Read) and [`Seek`](io::Seek), not memory mapping.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::{array, Array2, Axis};
+/// use ndarray_npy::{gather_rows, WriteNpyExt};
+/// use std::io::Cursor;
+///
+/// let arr = Array2::from_shape_fn((100, 3), |(i, j)| (i * 10 + j) as f32);
+/// let mut buf = Vec::new();
+/// arr.write_npy(&mut buf)?;
+///
+/// let batch: Array2<f32> = gather_rows(Cursor::new(&buf), &[42, 7, 8, 42])?;
+/// assert_eq!(batch, arr.select(Axis(0), &[42, 7, 8, 42]));
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+///
+/// # Panics
+///
+/// Panics if the array has no axes or if any of the indices are out of
+/// bounds for the first axis.
+pub fn gather_rows<A, R, D>(reader: R, rows: &[usize]) -> Result<Array<A, D>, ReadNpyError>
+where
+    A: ReadableElement + Clone,
+    R: io::Read + io::Seek,
+    D: Dimension,
+{
+    NpyFile::new(reader)?.gather_rows(rows)
+}
+
+#[cfg(feature = "memmap2")]
+mod mmap {
+    use super::NpyFile;
+    use crate::npy::header::Header;
+    use crate::npy::view_array_data;
+    use crate::{ViewElement, ViewNpyError};
+    use memmap2::Mmap;
+    use ndarray::{ArrayView, Dimension};
+    use std::fs::File;
+    use std::io::{self, BufReader};
+
+    /// A memory-mapped `.npy` file, created with [`NpyFile::map`].
+    #[derive(Debug)]
+    pub struct NpyMmap {
+        mmap: Mmap,
+        header: Header,
+        data_offset: usize,
+    }
+
+    impl NpyMmap {
+        /// Returns the header of the file.
+        pub fn header(&self) -> &Header {
+            &self.header
+        }
+
+        /// Returns a view of the array, like
+        /// [`ViewNpyExt::view_npy`](crate::ViewNpyExt::view_npy), without
+        /// parsing the header again.
+        pub fn view<A, D>(&self) -> Result<ArrayView<'_, A, D>, ViewNpyError>
+        where
+            A: ViewElement,
+            D: Dimension,
+        {
+            view_array_data(&self.mmap[self.data_offset..], &self.header)
+        }
+    }
+
+    impl NpyFile<BufReader<File>> {
+        /// Memory-maps the file for creating zero-copy views of the array.
+        ///
+        /// This is available with the `memmap2` feature.
+        ///
+        /// # Safety
+        ///
+        /// The file must not be modified (by this or any other process) while
+        /// it's mapped, as described in the docs of
+        /// [`memmap2::Mmap`](https://docs.rs/memmap2/0.9/memmap2/struct.Mmap.html).
+        pub unsafe fn map(&self) -> io::Result<NpyMmap> {
+            let mmap = Mmap::map(self.reader.get_ref())?;
+            let data_offset = (self.start + self.data_offset) as usize;
+            Ok(NpyMmap {
+                mmap,
+                header: self.header.clone(),
+                data_offset,
+            })
+        }
+    }
+}
+
+#[cfg(feature = "memmap2")]
+pub use self::mmap::NpyMmap;
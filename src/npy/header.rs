@@ -3,6 +3,7 @@
 //! In most cases, users do not need this module, since they can use the more convenient,
 //! higher-level functionality instead.
 
+use super::dtype::{normalize_type_descriptor, DType, ParseDTypeError};
 use byteorder::{ByteOrder, LittleEndian, ReadBytesExt};
 use num_traits::ToPrimitive;
 use py_literal::{
@@ -357,10 +358,14 @@ impl Layout {
 }
 
 /// Header of an `.npy` file.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct Header {
     /// A Python literal which can be passed as an argument to the `numpy.dtype` constructor to
     /// create the array's dtype.
+    ///
+    /// When a header is read, the descriptor is normalized to its canonical spelling if it can
+    /// be parsed as a [`DType`], e.g. `'float64'` becomes `'<f8'`. Use [`Header::dtype`] to parse
+    /// it.
     pub type_descriptor: PyValue,
     /// The layout of the array.
     pub layout: Layout,
@@ -423,7 +428,7 @@ impl Header {
                         Layout::Standard
                     };
                     Ok(Header {
-                        type_descriptor,
+                        type_descriptor: normalize_type_descriptor(type_descriptor),
                         layout,
                         shape,
                     })
@@ -437,6 +442,11 @@ impl Header {
         }
     }
 
+    /// Parses the type descriptor as a [`DType`].
+    pub fn dtype(&self) -> Result<DType, ParseDTypeError> {
+        DType::from_type_descriptor(&self.type_descriptor)
+    }
+
     /// Deserializes a header from the provided reader.
     pub fn from_reader<R: io::Read>(reader: &mut R) -> Result<Self, ReadHeaderError> {
         // Check for magic string.
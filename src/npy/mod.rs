@@ -2,15 +2,44 @@
 //!
 //! Most of this functionality is reexported at the top level of the crate.
 
+pub mod cast;
+mod chunks;
+pub mod dtype;
+mod dynamic;
 mod elements;
+mod file;
 pub mod header;
+pub mod packbits;
+mod raw;
+mod stream;
 
+pub use self::chunks::NpyChunks;
+pub use self::dynamic::{read_npy_dyn, DynNpyArray, DynNpyArrayVisitor};
+pub use self::elements::datetime;
+pub use self::elements::enums;
+pub use self::elements::longdouble;
+pub use self::elements::ml_dtypes;
+pub use self::elements::record;
+#[cfg(feature = "pickle")]
+pub use self::elements::PyObject;
+pub use self::elements::{ByteStringTooLongError, FixedBytes, Void};
+#[cfg(feature = "memmap2")]
+pub use self::file::NpyMmap;
+pub use self::file::{gather_rows, read_npy_slice, NpyFile};
+#[cfg(feature = "npz")]
+pub(crate) use self::raw::RawArray;
+pub use self::raw::{read_npy_raw, write_npy_raw, RawDataError};
+pub use self::stream::{NpyStreamReader, NpyStreamWriter};
+
+use self::dtype::DType;
 use self::header::{
     FormatHeaderError, Header, Layout, ParseHeaderError, ReadHeaderError, WriteHeaderError,
 };
+use self::raw::read_data_len;
 use ndarray::prelude::*;
-use ndarray::{Data, DataOwned, IntoDimension};
+use ndarray::{Data, DataOwned, IntoDimension, ShapeBuilder, StrideShape};
 use py_literal::Value as PyValue;
+use std::borrow::Cow;
 use std::convert::TryInto;
 use std::error::Error;
 use std::fmt;
@@ -120,6 +149,10 @@ where
 ///
 /// The data consists of all zeroed bytes, so this function is useful only for
 /// element types for which an all-zero byte-pattern is a valid representation.
+/// The length of the data is computed from `size_of::<A>()`, so the element
+/// type must also have the same size in memory as in the file, which is the
+/// case for all element types implementing [`ViewMutElement`]. (For example,
+/// this function is not useful for `Vec<u8>`.)
 ///
 /// ## Sparse file
 ///
@@ -251,6 +284,10 @@ impl From<io::Error> for WriteDataError {
 /// An array element type that can be written to an `.npy` or `.npz` file.
 pub trait WritableElement: Sized {
     /// Returns a descriptor of the type that can be used in the header.
+    ///
+    /// For types whose size in the file depends on the data (e.g. `Vec<u8>`,
+    /// which is written as a byte string of the width of the longest
+    /// element), this is the descriptor of the narrowest width.
     fn type_descriptor() -> PyValue;
 
     /// Writes a single instance of `Self` to the writer.
@@ -258,6 +295,51 @@ pub trait WritableElement: Sized {
 
     /// Writes a slice of `Self` to the writer.
     fn write_slice<W: io::Write>(slice: &[Self], writer: W) -> Result<(), WriteDataError>;
+
+    /// Returns a descriptor that can be used in the header of an array
+    /// containing the elements `elems`.
+    ///
+    /// The default implementation returns
+    /// [`type_descriptor`](Self::type_descriptor). Types whose size in the
+    /// file depends on the data should override this method, along with
+    /// [`write_with_descriptor`](Self::write_with_descriptor) and
+    /// [`write_slice_with_descriptor`](Self::write_slice_with_descriptor).
+    fn array_type_descriptor<'a, I>(elems: I) -> PyValue
+    where
+        I: IntoIterator<Item = &'a Self>,
+        Self: 'a,
+    {
+        let _ = elems;
+        Self::type_descriptor()
+    }
+
+    /// Writes a single instance of `Self` to the writer in the format
+    /// described by `type_desc`, which was returned by
+    /// [`array_type_descriptor`](Self::array_type_descriptor).
+    ///
+    /// The default implementation calls [`write`](Self::write).
+    fn write_with_descriptor<W: io::Write>(
+        &self,
+        type_desc: &PyValue,
+        writer: W,
+    ) -> Result<(), WriteDataError> {
+        let _ = type_desc;
+        self.write(writer)
+    }
+
+    /// Writes a slice of `Self` to the writer in the format described by
+    /// `type_desc`, which was returned by
+    /// [`array_type_descriptor`](Self::array_type_descriptor).
+    ///
+    /// The default implementation calls [`write_slice`](Self::write_slice).
+    fn write_slice_with_descriptor<W: io::Write>(
+        slice: &[Self],
+        type_desc: &PyValue,
+        writer: W,
+    ) -> Result<(), WriteDataError> {
+        let _ = type_desc;
+        Self::write_slice(slice, writer)
+    }
 }
 
 /// An error writing a `.npy` file.
@@ -357,36 +439,116 @@ where
     A: WritableElement,
     D: Dimension,
 {
-    fn write_npy<W: io::Write>(&self, mut writer: W) -> Result<(), WriteNpyError> {
-        let write_contiguous = |mut writer: W, layout: Layout| {
-            Header {
-                type_descriptor: A::type_descriptor(),
-                layout,
-                shape: self.shape().to_owned(),
-            }
-            .write(&mut writer)?;
-            A::write_slice(self.as_slice_memory_order().unwrap(), &mut writer)?;
-            writer.flush()?;
-            Ok(())
-        };
-        if self.is_standard_layout() {
-            write_contiguous(writer, Layout::Standard)
-        } else if self.view().reversed_axes().is_standard_layout() {
-            write_contiguous(writer, Layout::Fortran)
-        } else {
-            Header {
-                type_descriptor: A::type_descriptor(),
-                layout: Layout::Standard,
-                shape: self.shape().to_owned(),
-            }
-            .write(&mut writer)?;
-            for elem in self.iter() {
-                elem.write(&mut writer)?;
+    fn write_npy<W: io::Write>(&self, writer: W) -> Result<(), WriteNpyError> {
+        let type_descriptor = A::array_type_descriptor(self.iter());
+        write_array_data(
+            self,
+            &type_descriptor,
+            writer,
+            |slice, writer| A::write_slice_with_descriptor(slice, &type_descriptor, writer),
+            |elems, writer| {
+                for elem in elems {
+                    elem.write_with_descriptor(&type_descriptor, &mut *writer)?;
+                }
+                Ok(())
+            },
+        )
+    }
+}
+
+/// Object-safe companion of [`WriteNpyExt`].
+///
+/// [`WriteNpyExt::write_npy`] is generic over the writer, so `dyn WriteNpyExt`
+/// isn't possible. This trait writes to `&mut dyn io::Write` instead, so
+/// arrays with different element types and dimensions can be stored together
+/// as `&dyn DynWriteNpy` or `Box<dyn DynWriteNpy>`. It's implemented for
+/// every type which implements [`WriteNpyExt`], and `dyn DynWriteNpy`
+/// implements [`WriteNpyExt`] in turn, so it can be passed to [`write_npy`]
+/// and the [`NpzWriter`](crate::NpzWriter) methods.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{DynWriteNpy, WriteNpyExt};
+///
+/// let arrays: Vec<Box<dyn DynWriteNpy>> = vec![
+///     Box::new(array![1u8, 2]),
+///     Box::new(array![[0.5f64]]),
+/// ];
+/// for arr in &arrays {
+///     let mut buf = Vec::new();
+///     arr.write_npy(&mut buf)?;
+/// }
+/// # Ok::<_, ndarray_npy::WriteNpyError>(())
+/// ```
+pub trait DynWriteNpy {
+    /// Writes the array to `writer` in `.npy` format, like
+    /// [`WriteNpyExt::write_npy`].
+    fn write_npy_dyn(&self, writer: &mut dyn io::Write) -> Result<(), WriteNpyError>;
+}
+
+impl<T: WriteNpyExt> DynWriteNpy for T {
+    fn write_npy_dyn(&self, writer: &mut dyn io::Write) -> Result<(), WriteNpyError> {
+        self.write_npy(writer)
+    }
+}
+
+macro_rules! impl_write_npy_ext_for_dyn {
+    ($($ty:ty),*) => {
+        $(
+            impl WriteNpyExt for $ty {
+                fn write_npy<W: io::Write>(&self, mut writer: W) -> Result<(), WriteNpyError> {
+                    self.write_npy_dyn(&mut writer)
+                }
             }
-            writer.flush()?;
-            Ok(())
-        }
+        )*
+    };
+}
+
+impl_write_npy_ext_for_dyn!(
+    dyn DynWriteNpy + '_,
+    dyn DynWriteNpy + Send + '_,
+    dyn DynWriteNpy + Send + Sync + '_
+);
+
+/// Writes the header and data of `array` with the given type descriptor.
+///
+/// If the elements are contiguous in standard or Fortran layout, they are
+/// written with `write_slice` in memory order. Otherwise, they are written
+/// with `write_iter` in standard order.
+pub(crate) fn write_array_data<A, D, W, S, I>(
+    array: &ArrayRef<A, D>,
+    type_descriptor: &PyValue,
+    mut writer: W,
+    write_slice: S,
+    write_iter: I,
+) -> Result<(), WriteNpyError>
+where
+    D: Dimension,
+    W: io::Write,
+    S: FnOnce(&[A], &mut W) -> Result<(), WriteDataError>,
+    I: FnOnce(ndarray::iter::Iter<'_, A, D>, &mut W) -> Result<(), WriteDataError>,
+{
+    let layout = if array.is_standard_layout() {
+        Some(Layout::Standard)
+    } else if array.view().reversed_axes().is_standard_layout() {
+        Some(Layout::Fortran)
+    } else {
+        None
+    };
+    Header {
+        type_descriptor: type_descriptor.clone(),
+        layout: layout.unwrap_or(Layout::Standard),
+        shape: array.shape().to_owned(),
+    }
+    .write(&mut writer)?;
+    match layout {
+        Some(_) => write_slice(array.as_slice_memory_order().unwrap(), &mut writer)?,
+        None => write_iter(array.iter(), &mut writer)?,
     }
+    writer.flush()?;
+    Ok(())
 }
 
 impl<A, S, D> WriteNpyExt for ArrayBase<S, D>
@@ -473,6 +635,25 @@ pub trait ReadableElement: Sized {
         type_desc: &PyValue,
         len: usize,
     ) -> Result<Vec<Self>, ReadDataError>;
+
+    /// Reads to the end of the `reader`, creating a `Vec` with the elements
+    /// of an array with the given `shape`, in Fortran order if `fortran` is
+    /// `true` and in standard order otherwise.
+    ///
+    /// The caller must check that the number of elements doesn't overflow.
+    /// The default implementation calls
+    /// [`read_to_end_exact_vec`](Self::read_to_end_exact_vec) with the number
+    /// of elements. It only needs to be overridden by element types whose
+    /// data describes the shape of the array, i.e. object arrays.
+    #[doc(hidden)]
+    fn read_to_end_exact_vec_with_shape<R: io::Read>(
+        reader: R,
+        type_desc: &PyValue,
+        shape: &[usize],
+        _fortran: bool,
+    ) -> Result<Vec<Self>, ReadDataError> {
+        Self::read_to_end_exact_vec(reader, type_desc, shape.iter().product())
+    }
 }
 
 /// An error reading a `.npy` file.
@@ -571,6 +752,13 @@ impl From<ReadDataError> for ReadNpyError {
 
 /// Extension trait for reading `Array` from `.npy` files.
 ///
+/// If the descriptor in the file is a subarray descriptor, e.g. `('<f4',
+/// (3,))`, and the array type has a different number of axes than the shape
+/// in the header (or is dynamic-dimensional), the subarray shape is appended
+/// to the shape in the header. For example, a file with shape `(N, M)` and
+/// descriptor `('<f4', (3,))` can be read as an `Array3<f32>` with shape
+/// `(N, M, 3)`, or as an `Array2<[f32; 3]>`.
+///
 /// # Example
 ///
 /// ```
@@ -592,6 +780,45 @@ pub trait ReadNpyExt: Sized {
     /// [`numpy.load`](https://docs.scipy.org/doc/numpy/reference/generated/numpy.load.html)
     /// for `.npy` files.
     fn read_npy<R: io::Read>(reader: R) -> Result<Self, ReadNpyError>;
+
+    /// Reads one array from `reader` in `.npy` format, leaving the reader
+    /// positioned immediately after its data.
+    ///
+    /// Unlike [`.read_npy()`](ReadNpyExt::read_npy), this doesn't require the
+    /// data to extend to the end of `reader`, so it can be used to read
+    /// several arrays which were written one after another to the same
+    /// stream, e.g. with repeated calls to `numpy.save` on one open file or
+    /// with [`NpyStreamWriter`]. Pass `&mut reader` to keep using the reader
+    /// afterwards. See also [`NpyStreamReader`].
+    ///
+    /// This requires the length of the data to be determined from the
+    /// header, so it isn't supported for object arrays.
+    ///
+    /// The default implementation buffers the header and calls
+    /// [`.read_npy()`](ReadNpyExt::read_npy) with a reader limited to the
+    /// length of the data.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray::{array, Array1, Array2};
+    /// use ndarray_npy::{ReadNpyExt, WriteNpyExt};
+    ///
+    /// let mut buf = Vec::new();
+    /// array![[1u8, 2], [3, 4]].write_npy(&mut buf)?;
+    /// array![0.5f64, 1.5].write_npy(&mut buf)?;
+    ///
+    /// let mut reader = &buf[..];
+    /// let a = Array2::<u8>::read_npy_partial(&mut reader)?;
+    /// let b = Array1::<f64>::read_npy_partial(&mut reader)?;
+    /// assert_eq!(a, array![[1, 2], [3, 4]]);
+    /// assert_eq!(b, array![0.5, 1.5]);
+    /// assert!(reader.is_empty());
+    /// # Ok::<_, Box<dyn std::error::Error>>(())
+    /// ```
+    fn read_npy_partial<R: io::Read>(reader: R) -> Result<Self, ReadNpyError> {
+        stream::read_partial_with_read_npy(reader)
+    }
 }
 
 impl<A, S, D> ReadNpyExt for ArrayBase<S, D>
@@ -602,17 +829,46 @@ where
 {
     fn read_npy<R: io::Read>(mut reader: R) -> Result<Self, ReadNpyError> {
         let header = Header::from_reader(&mut reader)?;
-        let shape = header.shape.into_dimension();
-        let ndim = shape.ndim();
-        let len = shape_length_checked::<A>(&shape).ok_or(ReadNpyError::LengthOverflow)?;
-        let data = A::read_to_end_exact_vec(&mut reader, &header.type_descriptor, len)?;
-        ArrayBase::from_shape_vec(shape.set_f(header.layout.is_fortran()), data)
-            .unwrap()
-            .into_dimensionality()
-            .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, ndim))
+        read_array_data(reader, &header)
+    }
+
+    fn read_npy_partial<R: io::Read>(mut reader: R) -> Result<Self, ReadNpyError> {
+        let header = Header::from_reader(&mut reader)?;
+        let len = read_data_len(&header)?;
+        read_array_data(io::Read::take(reader, len as u64), &header)
     }
 }
 
+/// Reads the data following `header` from `reader` into an array.
+fn read_array_data<A, S, D, R>(
+    mut reader: R,
+    header: &Header,
+) -> Result<ArrayBase<S, D>, ReadNpyError>
+where
+    A: ReadableElement,
+    S: DataOwned<Elem = A>,
+    D: Dimension,
+    R: io::Read,
+{
+    let data_shape = DataShape::new(header, D::NDIM);
+    let ndim = data_shape.shape.ndim();
+    let len = shape_length_checked::<A>(&data_shape.shape).ok_or(ReadNpyError::LengthOverflow)?;
+    let data = if data_shape.subarray_ndim == 0 {
+        A::read_to_end_exact_vec_with_shape(
+            &mut reader,
+            &data_shape.type_descriptor,
+            data_shape.shape.slice(),
+            data_shape.fortran,
+        )?
+    } else {
+        A::read_to_end_exact_vec(&mut reader, &data_shape.type_descriptor, len)?
+    };
+    ArrayBase::from_shape_vec(data_shape.stride_shape(), data)
+        .unwrap()
+        .into_dimensionality()
+        .map_err(|_| ReadNpyError::WrongNdim(D::NDIM, ndim))
+}
+
 /// An error viewing a `.npy` file.
 #[derive(Debug)]
 #[non_exhaustive]
@@ -731,6 +987,11 @@ impl From<ViewDataError> for ViewNpyError {
 ///   implementation iterates over all of the elements when creating the view
 ///   to ensure they have a valid bit pattern.
 ///
+/// - Subarray descriptors are handled the same way as by
+///   [`ReadNpyExt::read_npy`], i.e. the subarray shape is appended to the
+///   shape in the header unless the view type has the same number of axes as
+///   the header's shape.
+///
 /// - Viewing an `.npy` file has more restrictions than reading it, due to
 ///   memory layout. Specifically:
 ///
@@ -846,17 +1107,31 @@ where
     fn view_npy(buf: &'a [u8]) -> Result<Self, ViewNpyError> {
         let mut reader = buf;
         let header = Header::from_reader(&mut reader)?;
-        let shape = header.shape.into_dimension();
-        let ndim = shape.ndim();
-        let len = shape_length_checked::<A>(&shape).ok_or(ViewNpyError::LengthOverflow)?;
-        let data = A::bytes_as_slice(reader, &header.type_descriptor, len)?;
-        ArrayView::from_shape(shape.set_f(header.layout.is_fortran()), data)
-            .unwrap()
-            .into_dimensionality()
-            .map_err(|_| ViewNpyError::WrongNdim(D::NDIM, ndim))
+        view_array_data(reader, &header)
     }
 }
 
+/// Creates a view of the data of an `.npy` file with the given header.
+///
+/// `data` is the portion of the file after the header.
+fn view_array_data<'a, A, D>(
+    data: &'a [u8],
+    header: &Header,
+) -> Result<ArrayView<'a, A, D>, ViewNpyError>
+where
+    A: ViewElement,
+    D: Dimension,
+{
+    let data_shape = DataShape::new(header, D::NDIM);
+    let ndim = data_shape.shape.ndim();
+    let len = shape_length_checked::<A>(&data_shape.shape).ok_or(ViewNpyError::LengthOverflow)?;
+    let data = A::bytes_as_slice(data, &data_shape.type_descriptor, len)?;
+    ArrayView::from_shape(data_shape.stride_shape(), data)
+        .unwrap()
+        .into_dimensionality()
+        .map_err(|_| ViewNpyError::WrongNdim(D::NDIM, ndim))
+}
+
 impl<'a, A, D> ViewMutNpyExt<'a> for ArrayViewMut<'a, A, D>
 where
     A: ViewMutElement,
@@ -865,12 +1140,13 @@ where
     fn view_mut_npy(buf: &'a mut [u8]) -> Result<Self, ViewNpyError> {
         let mut reader = &*buf;
         let header = Header::from_reader(&mut reader)?;
-        let shape = header.shape.into_dimension();
-        let ndim = shape.ndim();
-        let len = shape_length_checked::<A>(&shape).ok_or(ViewNpyError::LengthOverflow)?;
+        let data_shape = DataShape::new(&header, D::NDIM);
+        let ndim = data_shape.shape.ndim();
+        let len =
+            shape_length_checked::<A>(&data_shape.shape).ok_or(ViewNpyError::LengthOverflow)?;
         let mid = buf.len() - reader.len();
-        let data = A::bytes_as_mut_slice(&mut buf[mid..], &header.type_descriptor, len)?;
-        ArrayViewMut::from_shape(shape.set_f(header.layout.is_fortran()), data)
+        let data = A::bytes_as_mut_slice(&mut buf[mid..], &data_shape.type_descriptor, len)?;
+        ArrayViewMut::from_shape(data_shape.stride_shape(), data)
             .unwrap()
             .into_dimensionality()
             .map_err(|_| ViewNpyError::WrongNdim(D::NDIM, ndim))
@@ -975,6 +1251,81 @@ pub trait ViewMutElement: Sized {
     ) -> Result<&'a mut [Self], ViewDataError>;
 }
 
+/// The descriptor of the elements and the shape of the data in an `.npy`
+/// file.
+struct DataShape<'a> {
+    /// Descriptor of the elements.
+    type_descriptor: Cow<'a, PyValue>,
+    /// Shape of the array, including the axes of the subarray shape (if any).
+    shape: IxDyn,
+    /// Whether the axes of the header's shape are in Fortran order.
+    fortran: bool,
+    /// Number of trailing axes from the subarray shape, which are always in C
+    /// order.
+    subarray_ndim: usize,
+}
+
+impl<'a> DataShape<'a> {
+    /// Determines the shape of the data for an array with `ndim` axes (or any
+    /// number of axes, if `ndim` is `None`).
+    ///
+    /// If the header's descriptor is a subarray descriptor, e.g. `('<f4',
+    /// (3,))`, and the header's shape doesn't have `ndim` axes, the subarray
+    /// shape is appended to the header's shape, and the elements are the
+    /// items of the subarrays (e.g. `f32`). Otherwise, the elements are
+    /// described by the header's descriptor (e.g. `[f32; 3]`).
+    fn new(header: &'a Header, ndim: Option<usize>) -> Self {
+        let fortran = header.layout.is_fortran();
+        match DType::from_type_descriptor(&header.type_descriptor) {
+            Ok(DType::Subarray {
+                base,
+                shape: subarray_shape,
+            }) if ndim != Some(header.shape.len()) => DataShape {
+                type_descriptor: Cow::Owned(base.to_type_descriptor()),
+                shape: IxDyn(&[&header.shape[..], &subarray_shape[..]].concat()),
+                fortran,
+                subarray_ndim: subarray_shape.len(),
+            },
+            _ => DataShape {
+                type_descriptor: Cow::Borrowed(&header.type_descriptor),
+                shape: header.shape.clone().into_dimension(),
+                fortran,
+                subarray_ndim: 0,
+            },
+        }
+    }
+
+    /// Returns the shape and strides of the array.
+    ///
+    /// The caller must first check that the length of the array doesn't
+    /// overflow.
+    fn stride_shape(&self) -> StrideShape<IxDyn> {
+        if !self.fortran || self.subarray_ndim == 0 {
+            return self.shape.clone().set_f(self.fortran).into();
+        }
+        let mut strides = IxDyn::zeros(self.shape.ndim());
+        let mut stride = 1;
+        for axis in self.memory_order() {
+            strides[axis] = stride;
+            stride *= self.shape[axis];
+        }
+        self.shape.clone().strides(strides)
+    }
+
+    /// Returns the axes in the order of increasing stride, i.e. starting with
+    /// the axis whose elements are adjacent in the file.
+    fn memory_order(&self) -> Vec<usize> {
+        let ndim = self.shape.ndim();
+        if !self.fortran {
+            return (0..ndim).rev().collect();
+        }
+        // The subarrays are stored contiguously in C order, and the outer
+        // axes are in Fortran order.
+        let outer_ndim = ndim - self.subarray_ndim;
+        (outer_ndim..ndim).rev().chain(0..outer_ndim).collect()
+    }
+}
+
 /// Computes the length associated with the shape (i.e. the product of the axis
 /// lengths), where the element type is `T`.
 ///
@@ -0,0 +1,305 @@
+//! Bit-packed `bool` arrays, compatible with `np.packbits` and
+//! `np.unpackbits`.
+//!
+//! Arrays of `bool` are stored with one byte per element (descriptor `|b1`).
+//! To save space, masks are often stored instead as `|u1` arrays with eight
+//! elements packed into each byte, using `np.packbits`. The functions in
+//! this module pack and unpack along the last axis, like `np.packbits(a,
+//! axis=-1, bitorder=...)` and `np.unpackbits(a, axis=-1, count=...,
+//! bitorder=...)`, so the packed array has the same shape as the original
+//! array except for the last axis, which has length `ceil(n / 8)`.
+//!
+//! The original length `n` of the last axis isn't stored in the packed
+//! array, so it must be passed as the `count` to [`unpack_bits`]. To store it
+//! as well, [`write_packed`] and [`write_packed_npy`] write the shape of the
+//! unpacked array as a second `.npy` array after the packed one, and
+//! [`NpzWriter::add_packed_bits`] adds it to an `.npz` file as
+//! `{name}_shape`. The corresponding reading functions use the stored shape
+//! to unpack the array. With NumPy, a file written by [`write_packed_npy`]
+//! can be read like this:
+//!
+//! ```python
+//! with open(path, 'rb') as f:
+//!     packed = np.load(f)
+//!     shape = np.load(f)
+//! mask = np.unpackbits(packed, axis=-1, count=shape[-1], bitorder='big')
+//! ```
+//!
+//! A file written by NumPy with `np.save(path, np.packbits(mask, axis=-1))`
+//! contains only the packed array; read it with [`read_npy`](crate::read_npy)
+//! and unpack it with [`unpack_bits`].
+//!
+//! There's no integration with the `bitvec` crate, which isn't a dependency
+//! of this crate. To use the packed bits with another bit container, use the
+//! `u8` data of the packed array, which is in standard layout.
+//!
+//! [`NpzWriter::add_packed_bits`]: crate::NpzWriter::add_packed_bits
+//!
+//! # Example
+//!
+//! ```
+//! use ndarray::{array, Array2};
+//! use ndarray_npy::packbits::{pack_bits, unpack_bits, BitOrder};
+//! use ndarray_npy::{ReadNpyExt, WriteNpyExt};
+//!
+//! let mask = array![[true, false, true], [false, false, true]];
+//! let packed = pack_bits(&mask, BitOrder::Big);
+//! assert_eq!(packed, array![[0b1010_0000], [0b0010_0000]]);
+//!
+//! let mut buf = Vec::new();
+//! packed.write_npy(&mut buf)?;
+//! let packed = Array2::<u8>::read_npy(&buf[..])?;
+//! assert_eq!(unpack_bits(&packed, 3, BitOrder::Big), mask);
+//! # Ok::<_, Box<dyn std::error::Error>>(())
+//! ```
+
+use crate::{ReadNpyError, ReadNpyExt, WriteNpyError, WriteNpyExt};
+use ndarray::{Array, Array1, ArrayBase, Axis, Data, Dimension, Zip};
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+/// An error unpacking a packed array which was read from a file.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum UnpackBitsError {
+    /// The packed array has zero dimensions, so it has no axis to unpack.
+    ZeroDimensional,
+    /// The shape of the packed array doesn't match the shape of the unpacked
+    /// array stored with it.
+    WrongShape {
+        /// The shape of the packed array.
+        packed: Vec<usize>,
+        /// The stored shape of the unpacked array.
+        unpacked: Vec<u64>,
+    },
+}
+
+impl Error for UnpackBitsError {}
+
+impl fmt::Display for UnpackBitsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UnpackBitsError::ZeroDimensional => {
+                write!(f, "packed array must have at least one axis")
+            }
+            UnpackBitsError::WrongShape { packed, unpacked } => write!(
+                f,
+                "packed array has shape {:?}, which doesn't match unpacked shape {:?}",
+                packed, unpacked
+            ),
+        }
+    }
+}
+
+impl From<UnpackBitsError> for ReadNpyError {
+    fn from(err: UnpackBitsError) -> ReadNpyError {
+        ReadNpyError::ParseData(Box::new(err))
+    }
+}
+
+/// The order of the bits within each byte (the `bitorder` argument of
+/// `np.packbits` and `np.unpackbits`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BitOrder {
+    /// The first element is stored in the most significant bit (`'big'`,
+    /// the default in NumPy).
+    #[default]
+    Big,
+    /// The first element is stored in the least significant bit
+    /// (`'little'`).
+    Little,
+}
+
+impl BitOrder {
+    /// Returns the mask of the bit of element `index` within its byte.
+    fn mask(self, index: usize) -> u8 {
+        match self {
+            BitOrder::Big => 0x80 >> (index % 8),
+            BitOrder::Little => 1 << (index % 8),
+        }
+    }
+}
+
+/// Returns the last axis of an array with `ndim` dimensions.
+///
+/// **Panics** if `ndim` is zero.
+fn last_axis(ndim: usize) -> Axis {
+    assert!(ndim > 0, "packing bits requires at least one axis");
+    Axis(ndim - 1)
+}
+
+/// Packs the elements of a `bool` array into bits along the last axis, like
+/// `np.packbits(arr, axis=-1, bitorder=...)`.
+///
+/// The result has the same shape as `arr`, except that the length `n` of the
+/// last axis becomes `ceil(n / 8)`. The unused bits of the last byte are zero.
+///
+/// **Panics** if `arr` has zero dimensions.
+pub fn pack_bits<S, D>(arr: &ArrayBase<S, D>, order: BitOrder) -> Array<u8, D>
+where
+    S: Data<Elem = bool>,
+    D: Dimension,
+{
+    let axis = last_axis(arr.ndim());
+    let mut shape = arr.raw_dim();
+    shape[axis.index()] = arr.len_of(axis).div_ceil(8);
+    let mut packed = Array::zeros(shape);
+    Zip::from(packed.lanes_mut(axis))
+        .and(arr.lanes(axis))
+        .for_each(|mut bytes, bits| {
+            for (index, &bit) in bits.iter().enumerate() {
+                if bit {
+                    bytes[index / 8] |= order.mask(index);
+                }
+            }
+        });
+    packed
+}
+
+/// Unpacks bits along the last axis of a `u8` array into `bool` elements,
+/// like `np.unpackbits(packed, axis=-1, count=count, bitorder=...)`.
+///
+/// The result has the same shape as `packed`, except that the last axis has
+/// length `count`. If `count` is less than the number of bits, the remaining
+/// bits are ignored, and if it's greater, the result is padded with `false`,
+/// like NumPy does.
+///
+/// **Panics** if `packed` has zero dimensions.
+pub fn unpack_bits<S, D>(packed: &ArrayBase<S, D>, count: usize, order: BitOrder) -> Array<bool, D>
+where
+    S: Data<Elem = u8>,
+    D: Dimension,
+{
+    let axis = last_axis(packed.ndim());
+    let mut shape = packed.raw_dim();
+    shape[axis.index()] = count;
+    let mut arr = Array::from_elem(shape, false);
+    Zip::from(arr.lanes_mut(axis))
+        .and(packed.lanes(axis))
+        .for_each(|mut bits, bytes| {
+            for (index, bit) in bits.iter_mut().enumerate() {
+                if let Some(&byte) = bytes.get(index / 8) {
+                    *bit = byte & order.mask(index) != 0;
+                }
+            }
+        });
+    arr
+}
+
+/// Returns the shape of `arr` as a `u64` array, for storing it next to the
+/// packed array.
+pub(crate) fn unpacked_shape<S, D>(arr: &ArrayBase<S, D>) -> Array1<u64>
+where
+    S: Data<Elem = bool>,
+    D: Dimension,
+{
+    arr.shape().iter().map(|&len| len as u64).collect()
+}
+
+/// Unpacks a packed array which was read from a file, given the stored shape
+/// of the unpacked array.
+///
+/// The shape must match the shape of the packed array, except for the last
+/// axis, whose length must be `ceil(n / 8)`, where `n` is the last element of
+/// `shape`.
+pub(crate) fn unpack_bits_with_shape<D: Dimension>(
+    packed: &Array<u8, D>,
+    shape: &[u64],
+    order: BitOrder,
+) -> Result<Array<bool, D>, ReadNpyError> {
+    let Some((&packed_len, packed_outer)) = packed.shape().split_last() else {
+        return Err(UnpackBitsError::ZeroDimensional.into());
+    };
+    let count = match shape.split_last() {
+        Some((&count, outer))
+            if outer.len() == packed_outer.len()
+                && outer.iter().zip(packed_outer).all(|(&a, &b)| a == b as u64)
+                && count.div_ceil(8) == packed_len as u64 =>
+        {
+            count
+        }
+        _ => {
+            return Err(UnpackBitsError::WrongShape {
+                packed: packed.shape().to_vec(),
+                unpacked: shape.to_vec(),
+            }
+            .into())
+        }
+    };
+    let count = usize::try_from(count).map_err(|_| ReadNpyError::LengthOverflow)?;
+    Ok(unpack_bits(packed, count, order))
+}
+
+/// Packs a `bool` array with [`pack_bits`] and writes it to `writer`,
+/// followed by the shape of `arr`.
+///
+/// This writes two arrays in `.npy` format one after another: the packed
+/// array, with descriptor `|u1`, and the shape of `arr`, as a
+/// one-dimensional `u64` array. Use [`read_packed`] to read them back. See
+/// the [module-level documentation](self) for reading them with NumPy.
+///
+/// **Panics** if `arr` has zero dimensions.
+pub fn write_packed<W, S, D>(
+    mut writer: W,
+    arr: &ArrayBase<S, D>,
+    order: BitOrder,
+) -> Result<(), WriteNpyError>
+where
+    W: Write,
+    S: Data<Elem = bool>,
+    D: Dimension,
+{
+    pack_bits(arr, order).write_npy(&mut writer)?;
+    unpacked_shape(arr).write_npy(writer)
+}
+
+/// Reads a packed array followed by the shape of the unpacked array from
+/// `reader`, as written by [`write_packed`], and unpacks it.
+///
+/// If the shapes don't match, or the packed array has zero dimensions, this
+/// returns an [`UnpackBitsError`], wrapped in [`ReadNpyError::ParseData`].
+pub fn read_packed<R, D>(mut reader: R, order: BitOrder) -> Result<Array<bool, D>, ReadNpyError>
+where
+    R: Read,
+    D: Dimension,
+{
+    let packed = Array::<u8, D>::read_npy_partial(&mut reader)?;
+    let shape = Array1::<u64>::read_npy(reader)?;
+    unpack_bits_with_shape(&packed, &shape.to_vec(), order)
+}
+
+/// Packs a `bool` array with [`pack_bits`] and writes it to a file at the
+/// specified path, followed by the shape of `arr`, like [`write_packed`].
+///
+/// This function will create the file if it does not exist, or overwrite it if
+/// it does.
+///
+/// **Panics** if `arr` has zero dimensions.
+pub fn write_packed_npy<P, S, D>(
+    path: P,
+    arr: &ArrayBase<S, D>,
+    order: BitOrder,
+) -> Result<(), WriteNpyError>
+where
+    P: AsRef<Path>,
+    S: Data<Elem = bool>,
+    D: Dimension,
+{
+    let mut writer = BufWriter::new(File::create(path)?);
+    write_packed(&mut writer, arr, order)?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Reads a file written by [`write_packed_npy`] at the specified path and
+/// unpacks it, like [`read_packed`].
+pub fn read_packed_npy<P, D>(path: P, order: BitOrder) -> Result<Array<bool, D>, ReadNpyError>
+where
+    P: AsRef<Path>,
+    D: Dimension,
+{
+    read_packed(BufReader::new(File::open(path)?), order)
+}
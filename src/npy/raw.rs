@@ -0,0 +1,195 @@
+//! Reading and writing `.npy` files without decoding the data.
+
+use super::dtype::{DType, Kind};
+use super::header::Header;
+use crate::{ReadNpyError, WriteNpyError, WriteNpyExt};
+use py_literal::Value as PyValue;
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+
+/// An error writing an `.npy` file with [`write_npy_raw`], because the data
+/// doesn't match the header.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum RawDataError {
+    /// The size of an element can't be determined from the type descriptor,
+    /// e.g. because it's an object descriptor.
+    UnsizedDescriptor(PyValue),
+    /// Overflow while computing the length of the data (in bytes) from the
+    /// shape and type descriptor.
+    LengthOverflow,
+    /// The length of the data (in bytes) doesn't match the length expected
+    /// from the shape and type descriptor.
+    WrongLength {
+        /// The expected length.
+        expected: usize,
+        /// The actual length.
+        actual: usize,
+    },
+}
+
+impl Error for RawDataError {}
+
+impl fmt::Display for RawDataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RawDataError::UnsizedDescriptor(desc) => {
+                write!(f, "element size is unknown for descriptor {}", desc)
+            }
+            RawDataError::LengthOverflow => write!(f, "overflow computing length of data"),
+            RawDataError::WrongLength { expected, actual } => write!(
+                f,
+                "data is {} bytes long, but the header describes {} bytes",
+                actual, expected
+            ),
+        }
+    }
+}
+
+impl From<RawDataError> for WriteNpyError {
+    fn from(err: RawDataError) -> WriteNpyError {
+        WriteNpyError::FormatData(Box::new(err))
+    }
+}
+
+/// Returns `true` iff the data type is or contains the object type, whose
+/// data is pickled instead of stored with a fixed size per element.
+fn contains_object(dtype: &DType) -> bool {
+    match dtype {
+        DType::Scalar(scalar) => scalar.kind == Kind::Object,
+        DType::Subarray { base, .. } => contains_object(base),
+        DType::Structured(fields) => fields.iter().any(|field| contains_object(&field.dtype)),
+    }
+}
+
+/// Returns the size in bytes of an element with the given descriptor, if it
+/// has a fixed size.
+pub(crate) fn item_size(type_descriptor: &PyValue) -> Option<usize> {
+    DType::from_type_descriptor(type_descriptor)
+        .ok()
+        .filter(|dtype| !contains_object(dtype))
+        .and_then(|dtype| dtype.item_size())
+}
+
+/// Returns the length in bytes of the data of an array with the given
+/// header.
+pub(crate) fn data_len(header: &Header) -> Result<usize, RawDataError> {
+    let item_size = item_size(&header.type_descriptor)
+        .ok_or_else(|| RawDataError::UnsizedDescriptor(header.type_descriptor.clone()))?;
+    header
+        .shape
+        .iter()
+        .try_fold(item_size, |len, &axis_len| len.checked_mul(axis_len))
+        .filter(|&len| len <= isize::MAX as usize)
+        .ok_or(RawDataError::LengthOverflow)
+}
+
+/// Returns the length in bytes of the data of an array with the given
+/// header, with errors as for reading the data.
+pub(crate) fn read_data_len(header: &Header) -> Result<usize, ReadNpyError> {
+    data_len(header).map_err(|err| match err {
+        RawDataError::LengthOverflow => ReadNpyError::LengthOverflow,
+        _ => ReadNpyError::WrongDescriptor(header.type_descriptor.clone()),
+    })
+}
+
+/// Reads the header and the undecoded data of an `.npy` file from `reader`.
+///
+/// The length of the data is determined from the shape and the type
+/// descriptor in the header, so the descriptor must be parseable as a
+/// [`DType`](crate::dtype::DType) with a fixed size per element; otherwise,
+/// this returns [`ReadNpyError::WrongDescriptor`]. This supports descriptors
+/// which can't be decoded by this crate, such as nested structured types.
+/// The data is returned as stored in the file, in the byte order and layout
+/// described by the header.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::array;
+/// use ndarray_npy::{read_npy_raw, write_npy_raw, WriteNpyExt};
+///
+/// let mut buf = Vec::new();
+/// array![[1u16, 2], [3, 4]].write_npy(&mut buf)?;
+/// let (header, data) = read_npy_raw(&buf[..])?;
+/// assert_eq!(header.shape, [2, 2]);
+/// assert_eq!(data.len(), 8);
+///
+/// let mut copy = Vec::new();
+/// write_npy_raw(&mut copy, &header, &data)?;
+/// assert_eq!(copy, buf);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+pub fn read_npy_raw<R: io::Read>(mut reader: R) -> Result<(Header, Vec<u8>), ReadNpyError> {
+    let header = Header::from_reader(&mut reader)?;
+    let data = read_raw_data(reader, &header)?;
+    Ok((header, data))
+}
+
+/// Reads the undecoded data of an `.npy` file with the given header.
+pub(crate) fn read_raw_data<R: io::Read>(
+    mut reader: R,
+    header: &Header,
+) -> Result<Vec<u8>, ReadNpyError> {
+    let len = read_data_len(header)?;
+    // Reading with `take` avoids allocating the full length up front if the
+    // file is shorter than the header claims.
+    let mut data = Vec::new();
+    (&mut reader).take(len as u64).read_to_end(&mut data)?;
+    if data.len() < len {
+        return Err(ReadNpyError::MissingData);
+    }
+    let num_extra_bytes = reader.read_to_end(&mut Vec::new())?;
+    if num_extra_bytes > 0 {
+        return Err(ReadNpyError::ExtraBytes(num_extra_bytes));
+    }
+    Ok(data)
+}
+
+/// A header and undecoded data which have been checked to match.
+pub(crate) struct RawArray<'a> {
+    header: &'a Header,
+    data: &'a [u8],
+}
+
+impl<'a> RawArray<'a> {
+    /// Checks that the length of `data` matches the header.
+    pub(crate) fn new(header: &'a Header, data: &'a [u8]) -> Result<RawArray<'a>, RawDataError> {
+        let expected = data_len(header)?;
+        if data.len() != expected {
+            return Err(RawDataError::WrongLength {
+                expected,
+                actual: data.len(),
+            });
+        }
+        Ok(RawArray { header, data })
+    }
+}
+
+impl WriteNpyExt for RawArray<'_> {
+    fn write_npy<W: io::Write>(&self, mut writer: W) -> Result<(), WriteNpyError> {
+        self.header.write(&mut writer)?;
+        writer.write_all(self.data)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Writes an `.npy` file with the given header and undecoded data to
+/// `writer`.
+///
+/// The data must be in the byte order and layout described by the header.
+/// Its length is checked against the shape and the type descriptor in the
+/// header before anything is written; if they don't match, or if the size
+/// of an element can't be determined from the descriptor, this returns a
+/// [`WriteNpyError::FormatData`] error containing a [`RawDataError`].
+///
+/// See [`read_npy_raw`] for an example.
+pub fn write_npy_raw<W: io::Write>(
+    writer: W,
+    header: &Header,
+    data: &[u8],
+) -> Result<(), WriteNpyError> {
+    RawArray::new(header, data)?.write_npy(writer)
+}
@@ -0,0 +1,193 @@
+//! Reading and writing several `.npy` arrays one after another in a stream.
+
+use super::header::Header;
+use super::raw::read_data_len;
+use crate::{ReadNpyError, ReadNpyExt, ReadableElement, WriteNpyError, WriteNpyExt};
+use ndarray::{Array, Dimension};
+use std::io::{self, Read};
+use std::marker::PhantomData;
+
+/// A reader which keeps a copy of the bytes read through it.
+struct TeeReader<R> {
+    inner: R,
+    buf: Vec<u8>,
+}
+
+impl<R: Read> Read for TeeReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.inner.read(buf)?;
+        self.buf.extend_from_slice(&buf[..len]);
+        Ok(len)
+    }
+}
+
+/// Implements [`ReadNpyExt::read_npy_partial`] in terms of
+/// [`ReadNpyExt::read_npy`], by buffering the header and limiting the reader
+/// to the length of the data.
+pub(crate) fn read_partial_with_read_npy<T, R>(reader: R) -> Result<T, ReadNpyError>
+where
+    T: ReadNpyExt,
+    R: Read,
+{
+    let mut tee = TeeReader {
+        inner: reader,
+        buf: Vec::new(),
+    };
+    let header = Header::from_reader(&mut tee)?;
+    let len = read_data_len(&header)?;
+    T::read_npy((&tee.buf[..]).chain(tee.inner.take(len as u64)))
+}
+
+/// An iterator which reads `.npy` arrays written one after another to the
+/// same stream, until the end of the stream.
+///
+/// This reads the format written by calling `numpy.save` repeatedly on the
+/// same open file, or by [`NpyStreamWriter`], e.g. to send arrays over a pipe
+/// between processes. Each array is read with
+/// [`ReadNpyExt::read_npy_partial`], so the reader is never read past the
+/// end of the array which is being returned. The iterator ends when the
+/// stream ends at the boundary between arrays; if it ends within an array,
+/// that's an error. After an error, the iterator returns `None`, since the
+/// position in the stream is unknown.
+///
+/// All of the arrays must have the element type `A` and dimension type `D`.
+/// To read arrays of different types, call
+/// [`ReadNpyExt::read_npy_partial`] directly.
+///
+/// # Example
+///
+/// ```
+/// use ndarray::{array, Array1};
+/// use ndarray_npy::{NpyStreamReader, NpyStreamWriter};
+///
+/// let mut writer = NpyStreamWriter::new(Vec::new());
+/// writer.write(&array![1i32, 2, 3])?;
+/// writer.write(&array![4i32])?;
+/// let buf = writer.into_inner();
+///
+/// let arrays = NpyStreamReader::new(&buf[..]).collect::<Result<Vec<Array1<i32>>, _>>()?;
+/// assert_eq!(arrays, [array![1, 2, 3], array![4]]);
+/// # Ok::<_, Box<dyn std::error::Error>>(())
+/// ```
+#[derive(Debug)]
+pub struct NpyStreamReader<A, D, R> {
+    reader: R,
+    /// Whether the iterator has finished, either at the end of the stream or
+    /// because of an error.
+    done: bool,
+    elem: PhantomData<fn() -> Array<A, D>>,
+}
+
+impl<A, D, R> NpyStreamReader<A, D, R>
+where
+    A: ReadableElement,
+    D: Dimension,
+    R: Read,
+{
+    /// Creates an iterator over the arrays in `reader`, starting at its
+    /// current position.
+    pub fn new(reader: R) -> Self {
+        NpyStreamReader {
+            reader,
+            done: false,
+            elem: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Returns the underlying reader, positioned after the last array that
+    /// has been read.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    /// Reads the first byte of the next array, or returns `None` at the end
+    /// of the stream.
+    fn read_first_byte(&mut self) -> io::Result<Option<u8>> {
+        let mut byte = [0];
+        loop {
+            match self.reader.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Ok(_) => return Ok(Some(byte[0])),
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl<A, D, R> Iterator for NpyStreamReader<A, D, R>
+where
+    A: ReadableElement,
+    D: Dimension,
+    R: Read,
+{
+    type Item = Result<Array<A, D>, ReadNpyError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let result = match self.read_first_byte() {
+            Ok(None) => {
+                self.done = true;
+                return None;
+            }
+            Ok(Some(byte)) => Array::read_npy_partial((&[byte][..]).chain(&mut self.reader)),
+            Err(err) => Err(err.into()),
+        };
+        self.done = result.is_err();
+        Some(result)
+    }
+}
+
+/// A writer for `.npy` arrays one after another to the same stream, in the
+/// format read by [`NpyStreamReader`] and by repeated calls to `numpy.load`
+/// on the same open file.
+///
+/// The writer is flushed after each array, so this is suitable for sending
+/// arrays over a pipe between processes.
+///
+/// See [`NpyStreamReader`] for an example.
+#[derive(Debug)]
+pub struct NpyStreamWriter<W> {
+    writer: W,
+}
+
+impl<W: io::Write> NpyStreamWriter<W> {
+    /// Creates a writer which writes arrays to `writer`, starting at its
+    /// current position.
+    pub fn new(writer: W) -> Self {
+        NpyStreamWriter { writer }
+    }
+
+    /// Writes an array (header and data) and flushes the writer.
+    pub fn write<T>(&mut self, array: &T) -> Result<(), WriteNpyError>
+    where
+        T: WriteNpyExt + ?Sized,
+    {
+        array.write_npy(&mut self.writer)?;
+        // `write_npy` implementations aren't required to flush.
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Returns a reference to the underlying writer.
+    pub fn get_ref(&self) -> &W {
+        &self.writer
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.writer
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
@@ -1,6 +1,13 @@
-use crate::{ReadNpyError, ReadNpyExt, ReadableElement, WriteNpyError, WriteNpyExt};
+use crate::cast::{cast_for_writing, read_npy_as, CastElement, CastPolicy, OverflowPolicy};
+use crate::npy::header::Header;
+use crate::npy::packbits::{pack_bits, unpack_bits_with_shape, unpacked_shape, BitOrder};
+use crate::npy::RawArray;
+use crate::{
+    read_npy_dyn, read_npy_raw, DynNpyArray, ReadNpyError, ReadNpyExt, ReadableElement,
+    WritableElement, WriteNpyError, WriteNpyExt,
+};
 use ndarray::prelude::*;
-use ndarray::DataOwned;
+use ndarray::{Data, DataOwned};
 use std::error::Error;
 use std::fmt;
 use std::io::{BufWriter, Read, Seek, Write};
@@ -133,6 +140,110 @@ impl<W: Write + Seek> NpzWriter<W> {
         self.add_array_with_options(name, array, self.options)
     }
 
+    /// Adds several arrays with the specified names to the `.npz` file.
+    ///
+    /// This is the Rust equivalent of `numpy.savez(file, **arrays)`. It calls
+    /// [`add_array`](Self::add_array) for each `(name, array)` pair, stopping
+    /// at the first error. Arrays of different types can be passed together
+    /// as [`&dyn DynWriteNpy`](crate::DynWriteNpy).
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use ndarray::array;
+    /// use ndarray_npy::{DynWriteNpy, NpzWriter};
+    /// use std::io::Cursor;
+    ///
+    /// let arrays: Vec<(String, Box<dyn DynWriteNpy>)> = vec![
+    ///     ("weights".into(), Box::new(array![[0.5f32, 1.5]])),
+    ///     ("step".into(), Box::new(ndarray::arr0(10u64))),
+    /// ];
+    /// let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    /// npz.add_arrays(arrays.iter().map(|(name, arr)| (name.as_str(), &**arr)))?;
+    /// npz.finish()?;
+    /// # Ok::<_, ndarray_npy::WriteNpzError>(())
+    /// ```
+    pub fn add_arrays<'a, I, N, T>(&mut self, arrays: I) -> Result<(), WriteNpzError>
+    where
+        I: IntoIterator<Item = (N, &'a T)>,
+        N: Into<String>,
+        T: WriteNpyExt + ?Sized + 'a,
+    {
+        for (name, array) in arrays {
+            self.add_array(name, array)?;
+        }
+        Ok(())
+    }
+
+    /// Adds an array with the specified `name` to the `.npz` file, converting
+    /// the elements to type `T` while writing them.
+    ///
+    /// This uses the file options passed to the `NpzWriter` constructor.
+    /// Values which are out of the range of `T` are handled according to
+    /// `policy`, like in [`write_npy_as`](crate::write_npy_as).
+    pub fn add_array_as<T>(
+        &mut self,
+        name: impl Into<String>,
+        array: &ArrayBase<impl Data<Elem = impl CastElement>, impl Dimension>,
+        policy: OverflowPolicy,
+    ) -> Result<(), WriteNpzError>
+    where
+        T: CastElement + WritableElement,
+    {
+        let array = cast_for_writing::<T, _, _, _>(array, policy);
+        // Check the values before starting the file, so that an error doesn't leave an empty
+        // array in the `.npz` file.
+        array.check().map_err(WriteNpyError::from)?;
+        self.add_array(name, &array)
+    }
+
+    /// Packs a `bool` array with [`pack_bits`](crate::packbits::pack_bits)
+    /// and adds it to the `.npz` file, together with the shape of the
+    /// unpacked array.
+    ///
+    /// This adds two arrays: `name`, containing the packed bits with
+    /// descriptor `|u1`, and `{name}_shape`, a one-dimensional `u64` array
+    /// containing the shape of `array`. Use
+    /// [`NpzReader::by_name_packed_bits`] to read it back. In NumPy, the
+    /// array can be unpacked with `np.unpackbits(f[name], axis=-1,
+    /// count=f[name + '_shape'][-1], bitorder=...)`.
+    ///
+    /// **Panics** if `array` has zero dimensions.
+    pub fn add_packed_bits<N, S, D>(
+        &mut self,
+        name: N,
+        array: &ArrayBase<S, D>,
+        order: BitOrder,
+    ) -> Result<(), WriteNpzError>
+    where
+        N: Into<String>,
+        S: Data<Elem = bool>,
+        D: Dimension,
+    {
+        let name = name.into();
+        let packed = pack_bits(array, order);
+        self.add_array(format!("{name}_shape"), &unpacked_shape(array))?;
+        self.add_array(name, &packed)
+    }
+
+    /// Adds an array with the specified `name` to the `.npz` file, given its
+    /// header and undecoded data.
+    ///
+    /// This uses the file options passed to the `NpzWriter` constructor. The
+    /// data is checked like in [`write_npy_raw`](crate::write_npy_raw).
+    pub fn add_array_raw<N>(
+        &mut self,
+        name: N,
+        header: &Header,
+        data: &[u8],
+    ) -> Result<(), WriteNpzError>
+    where
+        N: Into<String>,
+    {
+        let array = RawArray::new(header, data).map_err(WriteNpyError::from)?;
+        self.add_array(name, &array)
+    }
+
     /// Adds an array with the specified `name` and options to the `.npz` file.
     ///
     /// The specified options override those passed to the [`NpzWriter`] constructor (if any).
@@ -314,4 +425,95 @@ impl<R: Read + Seek> NpzReader<R> {
     {
         Ok(ArrayBase::<S, D>::read_npy(self.zip.by_index(index)?)?)
     }
+
+    /// Reads an array by name, choosing the element type based on the
+    /// descriptor in the array's header.
+    ///
+    /// This is like [`by_name`](Self::by_name), but uses [`read_npy_dyn`]
+    /// instead of [`ReadNpyExt::read_npy`].
+    pub fn by_name_dyn(&mut self, name: &str) -> Result<DynNpyArray, ReadNpzError> {
+        match self.zip.by_name(name) {
+            Ok(file) => return Ok(read_npy_dyn(file)?),
+            Err(ZipError::FileNotFound) => {}
+            Err(err) => return Err(err.into()),
+        };
+        Ok(read_npy_dyn(self.zip.by_name(&format!("{name}.npy"))?)?)
+    }
+
+    /// Reads an array by name, converting the elements to type `T` if the
+    /// conversion is allowed by `policy`.
+    ///
+    /// This is like [`by_name`](Self::by_name), but uses
+    /// [`read_npy_as`](crate::read_npy_as) instead of
+    /// [`ReadNpyExt::read_npy`].
+    pub fn by_name_as<T, D>(
+        &mut self,
+        name: &str,
+        policy: CastPolicy,
+    ) -> Result<Array<T, D>, ReadNpzError>
+    where
+        T: CastElement,
+        D: Dimension,
+    {
+        match self.zip.by_name(name) {
+            Ok(file) => return Ok(read_npy_as(file, policy)?),
+            Err(ZipError::FileNotFound) => {}
+            Err(err) => return Err(err.into()),
+        };
+        Ok(read_npy_as(
+            self.zip.by_name(&format!("{name}.npy"))?,
+            policy,
+        )?)
+    }
+
+    /// Reads a packed `bool` array which was added with
+    /// [`NpzWriter::add_packed_bits`], and unpacks it along its last axis.
+    ///
+    /// This reads the packed `|u1` array `name` and the shape of the unpacked
+    /// array from `{name}_shape`. If the shapes don't match, or the packed
+    /// array has zero dimensions, this returns an
+    /// [`UnpackBitsError`](crate::packbits::UnpackBitsError), wrapped in
+    /// [`ReadNpyError::ParseData`].
+    pub fn by_name_packed_bits<D>(
+        &mut self,
+        name: &str,
+        order: BitOrder,
+    ) -> Result<Array<bool, D>, ReadNpzError>
+    where
+        D: Dimension,
+    {
+        let shape: Array1<u64> = self.by_name(&format!("{name}_shape"))?;
+        let packed: Array<u8, D> = self.by_name(name)?;
+        Ok(unpack_bits_with_shape(
+            &packed,
+            shape.as_slice().unwrap(),
+            order,
+        )?)
+    }
+
+    /// Reads an array by index in the `.npz` file, choosing the element type
+    /// based on the descriptor in the array's header.
+    pub fn by_index_dyn(&mut self, index: usize) -> Result<DynNpyArray, ReadNpzError> {
+        Ok(read_npy_dyn(self.zip.by_index(index)?)?)
+    }
+
+    /// Reads the header and undecoded data of an array by name.
+    ///
+    /// This is like [`by_name`](Self::by_name), but uses
+    /// [`read_npy_raw`](crate::read_npy_raw) instead of
+    /// [`ReadNpyExt::read_npy`].
+    pub fn by_name_raw(&mut self, name: &str) -> Result<(Header, Vec<u8>), ReadNpzError> {
+        match self.zip.by_name(name) {
+            Ok(file) => return Ok(read_npy_raw(file)?),
+            Err(ZipError::FileNotFound) => {}
+            Err(err) => return Err(err.into()),
+        };
+        Ok(read_npy_raw(self.zip.by_name(&format!("{name}.npy"))?)?)
+    }
+
+    /// Reads the header and undecoded data of an array by index in the
+    /// `.npz` file.
+    pub fn by_index_raw(&mut self, index: usize) -> Result<(Header, Vec<u8>), ReadNpzError> {
+        Ok(read_npy_raw(self.zip.by_index(index)?)?)
+    }
 }
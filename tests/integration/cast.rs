@@ -0,0 +1,387 @@
+//! Tests for `read_npy_as` and `write_npy_as`.
+
+use ndarray::prelude::*;
+use ndarray_npy::cast::{CastElement, CastError, CastPolicy, OverflowError, OverflowPolicy};
+use ndarray_npy::{
+    read_npy_as, write_npy_as, ReadNpyError, ReadNpyExt, WritableElement, WriteNpyError,
+    WriteNpyExt,
+};
+#[cfg(feature = "num-complex-0_4")]
+use num_complex_0_4::Complex;
+
+fn npy<A: WritableElement>(arr: &ArrayD<A>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    buf
+}
+
+/// Returns the error of a disallowed conversion.
+#[track_caller]
+fn cast_error(result: Result<ArrayD<impl Sized>, ReadNpyError>) -> String {
+    match result {
+        Err(ReadNpyError::ParseData(err)) => {
+            assert!(err.is::<CastError>());
+            err.to_string()
+        }
+        _ => panic!("expected a cast error"),
+    }
+}
+
+#[test]
+fn lossless() {
+    let ints = npy(&array![1i32, -2, 3].into_dyn());
+    let arr: Array1<f64> = read_npy_as(&ints[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![1., -2., 3.]);
+    let arr: Array1<i64> = read_npy_as(&ints[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![1, -2, 3]);
+    let arr: Array1<i32> = read_npy_as(&ints[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![1, -2, 3]);
+    assert_eq!(
+        cast_error(read_npy_as::<f32, _, _>(&ints[..], CastPolicy::Lossless)),
+        "cannot convert from int32 to float32 with the lossless casting policy",
+    );
+    assert_eq!(
+        cast_error(read_npy_as::<u64, _, _>(&ints[..], CastPolicy::Lossless)),
+        "cannot convert from int32 to uint64 with the lossless casting policy",
+    );
+
+    let floats = npy(&array![0.5f32, -1.25].into_dyn());
+    let arr: Array1<f64> = read_npy_as(&floats[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![0.5, -1.25]);
+    cast_error(read_npy_as::<i64, _, _>(&floats[..], CastPolicy::Lossless));
+
+    let bools = npy(&array![true, false].into_dyn());
+    let arr: Array1<u8> = read_npy_as(&bools[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![1, 0]);
+
+    let small = npy(&array![200u8].into_dyn());
+    let arr: Array1<i16> = read_npy_as(&small[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![200]);
+    cast_error(read_npy_as::<i8, _, _>(&small[..], CastPolicy::Lossless));
+    let large = npy(&array![u64::MAX].into_dyn());
+    let arr: Array1<f64> = read_npy_as(&large[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![u64::MAX as f64]);
+}
+
+#[test]
+fn same_kind() {
+    let floats = npy(&array![[0.1f64, 1e300]].into_dyn());
+    let arr: Array2<f32> = read_npy_as(&floats[..], CastPolicy::SameKind).unwrap();
+    assert_eq!(arr, array![[0.1f32, f32::INFINITY]]);
+    assert_eq!(
+        cast_error(read_npy_as::<i64, _, _>(&floats[..], CastPolicy::SameKind)),
+        "cannot convert from float64 to int64 with the same-kind casting policy",
+    );
+
+    let ints = npy(&array![300i64, -1].into_dyn());
+    let arr: Array1<i8> = read_npy_as(&ints[..], CastPolicy::SameKind).unwrap();
+    assert_eq!(arr, array![44, -1]);
+    cast_error(read_npy_as::<u64, _, _>(&ints[..], CastPolicy::SameKind));
+    cast_error(read_npy_as::<bool, _, _>(&ints[..], CastPolicy::SameKind));
+
+    let uints = npy(&array![255u64].into_dyn());
+    let arr: Array1<i8> = read_npy_as(&uints[..], CastPolicy::SameKind).unwrap();
+    assert_eq!(arr, array![-1]);
+}
+
+#[test]
+fn unsafe_casts() {
+    let floats = npy(&array![2.9f64, -2.9, 1e10, f64::NAN].into_dyn());
+    let arr: Array1<i32> = read_npy_as(&floats[..], CastPolicy::Unsafe).unwrap();
+    assert_eq!(arr, array![2, -2, i32::MAX, 0]);
+    let arr: Array1<bool> = read_npy_as(&floats[..], CastPolicy::Unsafe).unwrap();
+    assert_eq!(arr, array![true, true, true, true]);
+    let arr: Array1<u8> = read_npy_as(&floats[..], CastPolicy::Unsafe).unwrap();
+    assert_eq!(arr, array![2, 0, 255, 0]);
+
+    let ints = npy(&array![-1i16, 0].into_dyn());
+    let arr: Array1<u16> = read_npy_as(&ints[..], CastPolicy::Unsafe).unwrap();
+    assert_eq!(arr, array![u16::MAX, 0]);
+}
+
+#[cfg(feature = "num-complex-0_4")]
+#[test]
+fn complex() {
+    let floats = npy(&array![1.5f32].into_dyn());
+    let arr: Array1<Complex<f64>> = read_npy_as(&floats[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![Complex::new(1.5, 0.)]);
+    let ints = npy(&array![3i32].into_dyn());
+    cast_error(read_npy_as::<Complex<f32>, _, _>(
+        &ints[..],
+        CastPolicy::Lossless,
+    ));
+    let arr: Array1<Complex<f32>> = read_npy_as(&ints[..], CastPolicy::SameKind).unwrap();
+    assert_eq!(arr, array![Complex::new(3., 0.)]);
+
+    let complex = npy(&array![Complex::new(1.5f64, -2.)].into_dyn());
+    cast_error(read_npy_as::<f64, _, _>(&complex[..], CastPolicy::SameKind));
+    let arr: Array1<f64> = read_npy_as(&complex[..], CastPolicy::Unsafe).unwrap();
+    assert_eq!(arr, array![1.5]);
+}
+
+#[test]
+fn non_numeric() {
+    let strings = npy(&array!["1".to_string()].into_dyn());
+    assert_eq!(
+        cast_error(read_npy_as::<f64, _, _>(&strings[..], CastPolicy::Unsafe)),
+        "cannot convert from str to float64 with the unsafe casting policy",
+    );
+}
+
+#[test]
+fn wrong_ndim() {
+    let ints = npy(&array![[1u8]].into_dyn());
+    assert!(matches!(
+        read_npy_as::<u16, _, Ix1>(&ints[..], CastPolicy::Lossless),
+        Err(ReadNpyError::WrongNdim(Some(1), 2)),
+    ));
+}
+
+#[test]
+fn read_large() {
+    // The elements are converted in chunks, so use more than one chunk.
+    let arr = Array::from_shape_fn((3, 5000), |(i, j)| (i * 5000 + j) as i32 - 7000);
+    let expected = arr.mapv(f64::from);
+    let buf = npy(&arr.clone().into_dyn());
+    let read: Array2<f64> = read_npy_as(&buf[..], CastPolicy::Lossless).unwrap();
+    assert_eq!(read, expected);
+
+    let buf = npy(&arr.t().to_owned().into_dyn());
+    let buf_fortran = npy(&arr.reversed_axes().into_dyn());
+    for buf in [buf, buf_fortran] {
+        let read: Array2<f64> = read_npy_as(&buf[..], CastPolicy::Lossless).unwrap();
+        assert_eq!(read, expected.t());
+    }
+}
+
+#[test]
+fn read_missing_and_extra_data() {
+    let mut buf = npy(&Array::from_iter(0..5000u16).into_dyn());
+    buf.push(0);
+    assert!(matches!(
+        read_npy_as::<u32, _, Ix1>(&buf[..], CastPolicy::Lossless),
+        Err(ReadNpyError::ExtraBytes(1)),
+    ));
+    buf.truncate(buf.len() - 3);
+    assert!(matches!(
+        read_npy_as::<u32, _, Ix1>(&buf[..], CastPolicy::Lossless),
+        Err(ReadNpyError::MissingData),
+    ));
+    // The conversion is checked before reading the data.
+    let _ = cast_error(read_npy_as::<u8, _, IxDyn>(&buf[..], CastPolicy::Lossless));
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn npz_by_name_as() {
+    use ndarray_npy::{NpzReader, NpzWriter};
+    use std::io::Cursor;
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    npz.add_array("a", &array![1u16, 2]).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    let arr: Array1<f32> = npz.by_name_as("a", CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, array![1., 2.]);
+    assert!(npz
+        .by_name_as::<u8, Ix1>("a", CastPolicy::Lossless)
+        .is_err());
+}
+
+/// Writes `arr` with element type `T` and reads it back.
+#[track_caller]
+fn write_as<T, A, D>(arr: &Array<A, D>, policy: OverflowPolicy) -> Array<T, D>
+where
+    T: CastElement + WritableElement,
+    A: CastElement,
+    D: Dimension,
+{
+    let mut buf = Vec::new();
+    write_npy_as::<T>(&mut buf, arr, policy).unwrap();
+    Array::read_npy(&buf[..]).unwrap()
+}
+
+/// Returns the error of writing with an out-of-range value.
+#[track_caller]
+fn overflow_error(result: Result<(), WriteNpyError>) -> OverflowError {
+    match result {
+        Err(WriteNpyError::FormatData(err)) => err.downcast_ref::<OverflowError>().unwrap().clone(),
+        _ => panic!("expected an overflow error"),
+    }
+}
+
+#[test]
+fn write_narrower_ints() {
+    let arr = array![1i64, -1, 300, -300];
+    assert_eq!(
+        write_as::<i8, _, _>(&arr, OverflowPolicy::Saturate),
+        array![1, -1, 127, -128],
+    );
+    assert_eq!(
+        write_as::<i8, _, _>(&arr, OverflowPolicy::Wrap),
+        array![1, -1, 44, -44],
+    );
+    assert_eq!(
+        write_as::<u16, _, _>(&arr, OverflowPolicy::Saturate),
+        array![1, 0, 300, 0],
+    );
+
+    let mut buf = Vec::new();
+    let err = overflow_error(write_npy_as::<i8>(&mut buf, &arr, OverflowPolicy::Error));
+    assert_eq!(err.value(), "300");
+    assert_eq!(err.index(), 2);
+    assert_eq!(err.to_type(), "int8");
+    assert_eq!(
+        err.to_string(),
+        "value 300 at index 2 is out of range for int8"
+    );
+    // Nothing is written on error.
+    assert!(buf.is_empty());
+
+    let indices = array![[0i64, 5], [7, 1 << 20]];
+    assert_eq!(
+        write_as::<i32, _, _>(&indices, OverflowPolicy::Error),
+        array![[0, 5], [7, 1 << 20]],
+    );
+}
+
+#[test]
+fn write_narrower_floats() {
+    let arr = array![0.1f64, -1e300, f64::INFINITY, f64::NAN];
+    let out = write_as::<f32, _, _>(&arr, OverflowPolicy::Saturate);
+    assert_eq!(out.slice(s![..3]), array![0.1f32, f32::MIN, f32::INFINITY]);
+    assert!(out[3].is_nan());
+    let out = write_as::<f32, _, _>(&arr, OverflowPolicy::Wrap);
+    assert_eq!(out[1], f32::NEG_INFINITY);
+    let err = overflow_error(write_npy_as::<f32>(Vec::new(), &arr, OverflowPolicy::Error));
+    assert_eq!(err.value(), "-1e300");
+    assert_eq!(err.index(), 1);
+
+    // Values which round to the maximum aren't out of range.
+    let arr = array![f32::MAX as f64 * (1. + 1e-9)];
+    assert_eq!(
+        write_as::<f32, _, _>(&arr, OverflowPolicy::Error),
+        array![f32::MAX],
+    );
+}
+
+#[test]
+fn write_floats_as_ints() {
+    let arr = array![2.9f64, -2.9, 1e10, -1e300, f64::NAN];
+    assert_eq!(
+        write_as::<i32, _, _>(&arr, OverflowPolicy::Saturate),
+        array![2, -2, i32::MAX, i32::MIN, 0],
+    );
+    assert_eq!(
+        write_as::<i32, _, _>(&arr, OverflowPolicy::Wrap),
+        array![2, -2, 1410065408, 0, 0],
+    );
+    let err = overflow_error(write_npy_as::<i32>(Vec::new(), &arr, OverflowPolicy::Error));
+    assert_eq!(
+        err.to_string(),
+        "value 10000000000.0 at index 2 is out of range for int32"
+    );
+    let err = overflow_error(write_npy_as::<u8>(
+        Vec::new(),
+        &array![1f32, f32::NAN],
+        OverflowPolicy::Error,
+    ));
+    assert_eq!(err.value(), "NaN");
+}
+
+#[test]
+fn write_non_standard_layouts() {
+    let arr = Array::from_shape_fn((3, 4), |(i, j)| (i * 4 + j) as f64 - 5.5);
+    let expected = arr.mapv(|x| x as f32);
+    let mut fortran = Array2::zeros((3, 4).f());
+    fortran.assign(&arr);
+    assert_eq!(
+        write_as::<f32, _, _>(&fortran, OverflowPolicy::Error),
+        expected,
+    );
+    let sliced = arr.slice(s![.., ..;2]);
+    let mut buf = Vec::new();
+    write_npy_as::<f32>(&mut buf, &sliced, OverflowPolicy::Error).unwrap();
+    assert_eq!(
+        Array2::<f32>::read_npy(&buf[..]).unwrap(),
+        expected.slice(s![.., ..;2]),
+    );
+
+    // The index in the error counts the elements in standard order.
+    fortran[[1, 2]] = 1e100;
+    let err = overflow_error(write_npy_as::<f32>(
+        Vec::new(),
+        &fortran,
+        OverflowPolicy::Error,
+    ));
+    assert_eq!(err.index(), 6);
+}
+
+#[test]
+fn write_large() {
+    let arr = Array::from_iter((0..10_000i64).map(|x| x * 3 - 15_000));
+    assert_eq!(
+        write_as::<i16, _, _>(&arr, OverflowPolicy::Error),
+        arr.mapv(|x| x as i16),
+    );
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn write_half() {
+    use half::f16;
+
+    let arr = array![1.5f64, 70000., -70000.];
+    assert_eq!(
+        write_as::<f16, _, _>(&arr, OverflowPolicy::Saturate),
+        array![f16::from_f32(1.5), f16::MAX, f16::MIN],
+    );
+    let arr = array![65504i64, 100_000];
+    let err = overflow_error(write_npy_as::<f16>(Vec::new(), &arr, OverflowPolicy::Error));
+    assert_eq!(
+        err.to_string(),
+        "value 100000 at index 1 is out of range for float16"
+    );
+}
+
+#[cfg(feature = "num-complex-0_4")]
+#[test]
+fn write_complex() {
+    let arr = array![Complex::new(1.5f64, 1e300)];
+    assert_eq!(
+        write_as::<Complex<f32>, _, _>(&arr, OverflowPolicy::Saturate),
+        array![Complex::new(1.5, f32::MAX)],
+    );
+    assert_eq!(
+        write_as::<f32, _, _>(&arr, OverflowPolicy::Error),
+        array![1.5],
+    );
+    let err = overflow_error(write_npy_as::<Complex<f32>>(
+        Vec::new(),
+        &arr,
+        OverflowPolicy::Error,
+    ));
+    assert_eq!(err.value(), "(1.5+1e300j)");
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn npz_add_array_as() {
+    use ndarray_npy::{NpzReader, NpzWriter, WriteNpzError};
+    use std::io::Cursor;
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    npz.add_array_as::<f32>("a", &array![[0.5f64, 2.]], OverflowPolicy::Error)
+        .unwrap();
+    assert!(matches!(
+        npz.add_array_as::<u8>("b", &array![256u16], OverflowPolicy::Error),
+        Err(WriteNpzError::Npy(WriteNpyError::FormatData(_))),
+    ));
+    npz.add_array_as::<u8>("c", &array![256u16], OverflowPolicy::Wrap)
+        .unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    assert_eq!(npz.names().unwrap(), ["a", "c"]);
+    let arr: Array2<f32> = npz.by_name("a").unwrap();
+    assert_eq!(arr, array![[0.5, 2.]]);
+    let arr: Array1<u8> = npz.by_name("c").unwrap();
+    assert_eq!(arr, array![0]);
+}
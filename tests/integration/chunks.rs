@@ -0,0 +1,149 @@
+//! Tests for `NpyChunks`.
+
+use ndarray::concatenate;
+use ndarray::prelude::*;
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{NpyChunks, ReadNpyError, ReadNpyExt};
+use py_literal::Value as PyValue;
+use std::fs::File;
+
+use crate::npy;
+
+/// Reads all of the chunks, panicking on errors.
+fn read_chunks<A, D>(buf: &[u8], chunk_len: usize) -> Vec<Array<A, D>>
+where
+    A: ndarray_npy::ReadableElement,
+    D: Dimension,
+{
+    NpyChunks::new(buf, chunk_len)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+#[test]
+fn standard() {
+    let arr = Array::from_shape_fn((7, 2, 3), |(i, j, k)| (i * 100 + j * 10 + k) as u32);
+    let buf = npy(&arr);
+    for chunk_len in [1, 2, 3, 7, 100] {
+        let chunks: Vec<Array3<u32>> = read_chunks(&buf, chunk_len);
+        assert_eq!(chunks.len(), 7usize.div_ceil(chunk_len));
+        for (i, chunk) in chunks.iter().enumerate() {
+            let start = i * chunk_len;
+            let end = (start + chunk_len).min(7);
+            assert_eq!(chunk, arr.slice(s![start..end, .., ..]));
+        }
+    }
+    let chunks: Vec<ArrayD<u32>> = read_chunks(&buf, 4);
+    assert_eq!(chunks[1], arr.slice(s![4.., .., ..]).into_dyn());
+}
+
+#[test]
+fn fortran() {
+    let arr = Array::from_shape_fn((2, 3, 5), |(i, j, k)| (i * 100 + j * 10 + k) as i16);
+    let mut fortran = Array3::zeros((2, 3, 5).f());
+    fortran.assign(&arr);
+    let buf = npy(&fortran);
+    let iter = NpyChunks::<i16, Ix3, _>::new(&buf[..], 2).unwrap();
+    assert_eq!(iter.axis(), Some(Axis(2)));
+    let chunks: Vec<Array3<i16>> = iter.collect::<Result<_, _>>().unwrap();
+    let views: Vec<_> = chunks.iter().map(|chunk| chunk.view()).collect();
+    assert_eq!(chunks.len(), 3);
+    assert_eq!(chunks[2], arr.slice(s![.., .., 4..]));
+    assert_eq!(concatenate(Axis(2), &views).unwrap(), arr);
+}
+
+#[test]
+fn big_endian() {
+    for path in [
+        "resources/example_f64_big_endian_standard.npy",
+        "resources/example_f64_big_endian_fortran.npy",
+    ] {
+        let arr = Array3::<f64>::read_npy(File::open(path).unwrap()).unwrap();
+        let iter = NpyChunks::<f64, Ix3, _>::open(path, 1).unwrap();
+        let axis = iter.axis().unwrap();
+        let chunks: Vec<Array3<f64>> = iter.collect::<Result<_, _>>().unwrap();
+        let views: Vec<_> = chunks.iter().map(|chunk| chunk.view()).collect();
+        assert_eq!(concatenate(axis, &views).unwrap(), arr);
+    }
+}
+
+#[test]
+fn subarray_fortran() {
+    let header = Header {
+        type_descriptor: PyValue::Tuple(vec![
+            PyValue::String("<u2".into()),
+            PyValue::Tuple(vec![PyValue::Integer(2.into())]),
+        ]),
+        layout: Layout::Fortran,
+        shape: vec![2, 3],
+    };
+    let mut buf = header.to_bytes().unwrap();
+    for j in 0..3u16 {
+        for i in 0..2u16 {
+            for k in 0..2 {
+                buf.extend_from_slice(&(i * 100 + j * 10 + k).to_le_bytes());
+            }
+        }
+    }
+    let arr = Array::from_shape_fn((2, 3, 2), |(i, j, k)| (i * 100 + j * 10 + k) as u16);
+    let iter = NpyChunks::<u16, Ix3, _>::new(&buf[..], 2).unwrap();
+    assert_eq!(iter.axis(), Some(Axis(1)));
+    let chunks: Vec<Array3<u16>> = iter.collect::<Result<_, _>>().unwrap();
+    assert_eq!(chunks[0], arr.slice(s![.., ..2, ..]));
+    assert_eq!(chunks[1], arr.slice(s![.., 2.., ..]));
+    let chunks: Vec<Array2<[u16; 2]>> = read_chunks(&buf, 3);
+    assert_eq!(chunks[0][[1, 2]], [120, 121]);
+}
+
+#[test]
+fn empty_and_scalar() {
+    let chunks: Vec<Array2<f32>> = read_chunks(&npy(&Array2::<f32>::zeros((0, 3))), 2);
+    assert!(chunks.is_empty());
+    let chunks: Vec<Array0<f32>> = read_chunks(&npy(&arr0(1.5f32)), 2);
+    assert_eq!(chunks, [arr0(1.5)]);
+}
+
+#[test]
+fn strings() {
+    let arr = array!["a".to_string(), "bc".into(), "def".into()];
+    let chunks: Vec<Array1<String>> = read_chunks(&npy(&arr), 2);
+    assert_eq!(chunks, [arr.slice(s![..2]), arr.slice(s![2..])]);
+}
+
+#[test]
+fn missing_data() {
+    let mut buf = npy(&array![[1u8, 2], [3, 4], [5, 6]]);
+    buf.pop();
+    let mut iter = NpyChunks::<u8, Ix2, _>::new(&buf[..], 2).unwrap();
+    assert_eq!(iter.next().unwrap().unwrap(), array![[1, 2], [3, 4]]);
+    assert!(matches!(iter.next(), Some(Err(ReadNpyError::MissingData))));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn extra_bytes() {
+    let mut buf = npy(&array![[1u8, 2], [3, 4], [5, 6]]);
+    buf.extend_from_slice(&[0; 3]);
+    let mut iter = NpyChunks::<u8, Ix2, _>::new(&buf[..], 2).unwrap();
+    assert!(iter.next().unwrap().is_ok());
+    assert!(iter.next().unwrap().is_ok());
+    assert!(matches!(
+        iter.next(),
+        Some(Err(ReadNpyError::ExtraBytes(3)))
+    ));
+    assert!(iter.next().is_none());
+}
+
+#[test]
+fn checked_up_front() {
+    let buf = npy(&array![[1u8, 2]]);
+    assert!(matches!(
+        NpyChunks::<u8, Ix1, _>::new(&buf[..], 1),
+        Err(ReadNpyError::WrongNdim(Some(1), 2)),
+    ));
+    assert!(matches!(
+        NpyChunks::<f32, Ix2, _>::new(&buf[..], 1),
+        Err(ReadNpyError::WrongDescriptor(_)),
+    ));
+}
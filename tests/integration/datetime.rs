@@ -0,0 +1,124 @@
+//! Tests for the `datetime64` and `timedelta64` element types.
+
+use ndarray_npy::datetime::{DateTime64, Days, Milliseconds, Nanoseconds, TimeDelta64, TimeUnit};
+use ndarray_npy::{ReadDataError, ReadableElement, ViewDataError, ViewElement, WritableElement};
+use py_literal::Value as PyValue;
+
+#[test]
+fn descriptors() {
+    #[cfg(target_endian = "little")]
+    {
+        assert_eq!(
+            DateTime64::<Nanoseconds>::type_descriptor(),
+            PyValue::String("<M8[ns]".into()),
+        );
+        assert_eq!(
+            TimeDelta64::<Milliseconds>::type_descriptor(),
+            PyValue::String("<m8[ms]".into()),
+        );
+    }
+    assert_eq!(
+        TimeUnit::from_type_descriptor(&PyValue::String(">M8[D]".into())),
+        Some(TimeUnit::Days),
+    );
+    assert_eq!(
+        TimeUnit::from_type_descriptor(&PyValue::String("<m8[us]".into())),
+        Some(TimeUnit::Microseconds),
+    );
+    assert_eq!(
+        TimeUnit::from_type_descriptor(&PyValue::String("<M8[10ms]".into())),
+        None,
+    );
+    assert_eq!(
+        TimeUnit::from_type_descriptor(&PyValue::String("<i8".into())),
+        None,
+    );
+}
+
+#[test]
+fn read_datetime_both_endians() {
+    let values = [0i64, -1, i64::MIN, 1_600_000_000_000_000_000];
+    let little: Vec<u8> = values.iter().flat_map(|x| x.to_le_bytes()).collect();
+    let big: Vec<u8> = values.iter().flat_map(|x| x.to_be_bytes()).collect();
+    for (desc, data) in [("<M8[ns]", little), (">M8[ns]", big)] {
+        let out = DateTime64::<Nanoseconds>::read_to_end_exact_vec(
+            &data[..],
+            &PyValue::String(desc.into()),
+            values.len(),
+        )
+        .unwrap();
+        assert_eq!(out.iter().map(|x| x.raw()).collect::<Vec<_>>(), values);
+        assert!(out[2].is_nat());
+        assert_eq!(out[2].get(), None);
+    }
+}
+
+#[test]
+fn read_wrong_unit_or_kind() {
+    let data = [0u8; 8];
+    for desc in ["<M8[us]", "<m8[ns]", "<i8"] {
+        let out = DateTime64::<Nanoseconds>::read_to_end_exact_vec(
+            &data[..],
+            &PyValue::String(desc.into()),
+            1,
+        );
+        assert!(matches!(out, Err(ReadDataError::WrongDescriptor(_))));
+    }
+}
+
+#[test]
+fn view_timedelta() {
+    let values: Vec<TimeDelta64<Days>> = vec![TimeDelta64::new(3), TimeDelta64::NAT];
+    let mut buf = Vec::new();
+    TimeDelta64::write_slice(&values, &mut buf).unwrap();
+    let aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 8);
+    let view = TimeDelta64::<Days>::bytes_as_slice(
+        &aligned,
+        &TimeDelta64::<Days>::type_descriptor(),
+        values.len(),
+    )
+    .unwrap();
+    assert_eq!(view, &values[..]);
+
+    #[cfg(target_endian = "little")]
+    let non_native = PyValue::String(">m8[D]".into());
+    #[cfg(target_endian = "big")]
+    let non_native = PyValue::String("<m8[D]".into());
+    let out = TimeDelta64::<Days>::bytes_as_slice(&aligned, &non_native, values.len());
+    assert!(matches!(out, Err(ViewDataError::NonNativeEndian)));
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn chrono_conversions() {
+    use chrono::{NaiveDate, TimeDelta};
+    use ndarray_npy::datetime::{Months, Seconds, Years};
+
+    let date_time = NaiveDate::from_ymd_opt(1969, 12, 31)
+        .unwrap()
+        .and_hms_nano_opt(23, 59, 59, 500_000_000)
+        .unwrap();
+    let ms = DateTime64::<Milliseconds>::from_naive_date_time(date_time).unwrap();
+    assert_eq!(ms.get(), Some(-500));
+    assert_eq!(ms.to_naive_date_time(), Some(date_time));
+    // Conversion to a coarser unit truncates toward negative infinity.
+    let s = DateTime64::<Seconds>::from_naive_date_time(date_time).unwrap();
+    assert_eq!(s.get(), Some(-1));
+    let months = DateTime64::<Months>::from_naive_date_time(date_time).unwrap();
+    assert_eq!(months.get(), Some(-1));
+    assert_eq!(
+        months.to_naive_date_time(),
+        NaiveDate::from_ymd_opt(1969, 12, 1)
+            .unwrap()
+            .and_hms_opt(0, 0, 0),
+    );
+    let years = DateTime64::<Years>::from_naive_date_time(date_time).unwrap();
+    assert_eq!(years.get(), Some(-1));
+    assert_eq!(DateTime64::<Seconds>::NAT.to_naive_date_time(), None);
+
+    let delta = TimeDelta::milliseconds(-1500);
+    let ns = TimeDelta64::<Nanoseconds>::from_time_delta(delta).unwrap();
+    assert_eq!(ns.get(), Some(-1_500_000_000));
+    assert_eq!(ns.to_time_delta(), Some(delta));
+    assert_eq!(TimeDelta64::<Months>::from_time_delta(delta), None);
+}
@@ -0,0 +1,241 @@
+//! Tests for `DType` and the normalization of descriptors.
+
+use ndarray::{array, Array1, Array2};
+use ndarray_npy::dtype::{ByteOrder, DType, Field, Kind, ScalarDType};
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{ReadNpyError, ReadNpyExt};
+use py_literal::Value as PyValue;
+use std::mem;
+
+fn string(s: &str) -> PyValue {
+    PyValue::String(s.into())
+}
+
+fn parse(s: &str) -> DType {
+    DType::from_type_descriptor(&string(s)).unwrap()
+}
+
+/// Returns the canonical spelling of a descriptor.
+fn canonical(s: &str) -> PyValue {
+    parse(s).to_type_descriptor()
+}
+
+/// Returns the descriptor with the native byte order character.
+fn native(s: &str) -> PyValue {
+    string(&format!("{}{}", ByteOrder::native().to_char(), s))
+}
+
+#[test]
+fn normalize_spellings() {
+    for s in [
+        "<f8", ">f8", "|b1", "|i1", "|u1", "|S5", "<U3", "|V4", "<M8[ns]", "|O",
+    ] {
+        assert_eq!(canonical(s), string(s));
+    }
+    for s in ["f8", "=f8", "|f8", "d", "=d", "float64", "double", "float"] {
+        assert_eq!(canonical(s), native("f8"), "{}", s);
+    }
+    assert_eq!(canonical("!f8"), string(">f8"));
+    assert_eq!(canonical(">d"), string(">f8"));
+    assert_eq!(canonical("<i1"), string("|i1"));
+    assert_eq!(canonical("b"), string("|i1"));
+    assert_eq!(canonical("B"), string("|u1"));
+    assert_eq!(canonical("?"), string("|b1"));
+    assert_eq!(canonical("bool"), string("|b1"));
+    assert_eq!(canonical("uint8"), string("|u1"));
+    assert_eq!(canonical("h"), native("i2"));
+    assert_eq!(canonical("q"), native("i8"));
+    assert_eq!(canonical("int64"), native("i8"));
+    assert_eq!(canonical("e"), native("f2"));
+    assert_eq!(canonical("F"), native("c8"));
+    assert_eq!(canonical("complex128"), native("c16"));
+    assert_eq!(canonical("a5"), string("|S5"));
+    assert_eq!(canonical("S5"), string("|S5"));
+    assert_eq!(canonical("U3"), native("U3"));
+    assert_eq!(canonical("O"), string("|O"));
+    assert_eq!(canonical("object"), string("|O"));
+    let intp = format!("i{}", mem::size_of::<isize>());
+    assert_eq!(canonical("int"), native(&intp));
+    assert_eq!(canonical("intp"), native(&intp));
+    assert_eq!(
+        canonical("uint"),
+        native(&format!("u{}", mem::size_of::<usize>()))
+    );
+    assert_eq!(canonical("str"), native("U0"));
+    assert_eq!(canonical("bytes"), string("|S0"));
+    assert_eq!(canonical("datetime64[ms]"), native("M8[ms]"));
+    assert_eq!(canonical(">m8[μs]"), string(">m8[us]"));
+    assert_eq!(canonical("M8"), native("M8"));
+    // Void types keep an explicit byte order.
+    assert_eq!(canonical("<V2"), string("<V2"));
+    assert_eq!(canonical("V2"), string("|V2"));
+}
+
+#[test]
+fn parse_scalar() {
+    assert_eq!(
+        parse(">U3"),
+        DType::Scalar(ScalarDType {
+            byte_order: ByteOrder::Big,
+            kind: Kind::Unicode,
+            item_size: 12,
+            time_unit: None,
+        }),
+    );
+    assert_eq!(parse("<c16").item_size(), Some(16));
+    assert_eq!(parse("|O").item_size(), Some(mem::size_of::<usize>()));
+}
+
+#[test]
+fn parse_invalid() {
+    for desc in [
+        string("<f3"),
+        string("i16"),
+        string("<x4"),
+        string("S"),
+        string("<M8[xs]"),
+        string(">float32"),
+        PyValue::Integer(4.into()),
+        PyValue::Tuple(vec![string("<f4")]),
+        PyValue::List(vec![PyValue::Tuple(vec![string("x")])]),
+    ] {
+        assert!(DType::from_type_descriptor(&desc).is_err(), "{}", desc);
+    }
+}
+
+#[test]
+fn subarray_and_structured() {
+    let desc = PyValue::Tuple(vec![
+        PyValue::Tuple(vec![string("f4"), PyValue::Integer(3.into())]),
+        PyValue::Tuple(vec![PyValue::Integer(2.into())]),
+    ]);
+    let dtype = DType::try_from(&desc).unwrap();
+    assert_eq!(
+        dtype,
+        DType::Subarray {
+            base: Box::new(parse("f4")),
+            shape: vec![2, 3],
+        },
+    );
+    assert_eq!(dtype.item_size(), Some(24));
+    assert_eq!(
+        PyValue::from(&dtype),
+        PyValue::Tuple(vec![
+            native("f4"),
+            PyValue::Tuple(vec![PyValue::Integer(2.into()), PyValue::Integer(3.into())]),
+        ]),
+    );
+    let desc = PyValue::Tuple(vec![string("<f4"), PyValue::Tuple(vec![])]);
+    assert_eq!(DType::try_from(&desc).unwrap(), parse("<f4"));
+
+    let desc = PyValue::List(vec![
+        PyValue::Tuple(vec![
+            PyValue::Tuple(vec![string("Position"), string("pos")]),
+            string("d"),
+            PyValue::Integer(3.into()),
+        ]),
+        PyValue::Tuple(vec![string(""), string("V4")]),
+        PyValue::Tuple(vec![string("id"), string("uint64")]),
+    ]);
+    let dtype = DType::try_from(&desc).unwrap();
+    assert_eq!(
+        dtype,
+        DType::Structured(vec![
+            Field {
+                name: "pos".into(),
+                title: Some("Position".into()),
+                dtype: DType::Subarray {
+                    base: Box::new(parse("f8")),
+                    shape: vec![3],
+                },
+            },
+            Field {
+                name: "".into(),
+                title: None,
+                dtype: parse("|V4"),
+            },
+            Field {
+                name: "id".into(),
+                title: None,
+                dtype: parse("u8"),
+            },
+        ]),
+    );
+    assert_eq!(dtype.item_size(), Some(36));
+    assert_eq!(
+        dtype.to_type_descriptor(),
+        PyValue::List(vec![
+            PyValue::Tuple(vec![
+                PyValue::Tuple(vec![string("Position"), string("pos")]),
+                native("f8"),
+                PyValue::Tuple(vec![PyValue::Integer(3.into())]),
+            ]),
+            PyValue::Tuple(vec![string(""), string("|V4")]),
+            PyValue::Tuple(vec![string("id"), native("u8")]),
+        ]),
+    );
+}
+
+/// Returns an `.npy` file with the given descriptor and data.
+fn npy(type_descriptor: PyValue, shape: Vec<usize>, data: &[u8]) -> Vec<u8> {
+    let header = Header {
+        type_descriptor,
+        layout: Layout::Standard,
+        shape,
+    };
+    let mut buf = header.to_bytes().unwrap();
+    buf.extend_from_slice(data);
+    buf
+}
+
+#[test]
+fn read_alternative_spellings() {
+    let data = [1., -2.5f64]
+        .iter()
+        .flat_map(|x| x.to_be_bytes())
+        .collect::<Vec<_>>();
+    for desc in [">f8", "!f8", ">d"] {
+        let buf = npy(string(desc), vec![2], &data);
+        assert_eq!(Array1::<f64>::read_npy(&buf[..]).unwrap(), array![1., -2.5]);
+    }
+    let data = [1., -2.5f64]
+        .iter()
+        .flat_map(|x| x.to_ne_bytes())
+        .collect::<Vec<_>>();
+    for desc in ["=f8", "f8", "d", "float64", "double"] {
+        let buf = npy(string(desc), vec![2], &data);
+        assert_eq!(Array1::<f64>::read_npy(&buf[..]).unwrap(), array![1., -2.5]);
+        let header = Header::from_reader(&mut &buf[..]).unwrap();
+        assert_eq!(header.type_descriptor, native("f8"));
+        assert_eq!(header.dtype().unwrap(), parse("f8"));
+    }
+
+    let buf = npy(string("?"), vec![2], &[1, 0]);
+    assert_eq!(
+        Array1::<bool>::read_npy(&buf[..]).unwrap(),
+        array![true, false]
+    );
+    let buf = npy(string("<u1"), vec![1], &[7]);
+    assert_eq!(Array1::<u8>::read_npy(&buf[..]).unwrap(), array![7]);
+
+    let desc = PyValue::Tuple(vec![string("uint16"), PyValue::Integer(2.into())]);
+    let data = [3u16, 4]
+        .iter()
+        .flat_map(|x| x.to_ne_bytes())
+        .collect::<Vec<_>>();
+    let buf = npy(desc, vec![1], &data);
+    assert_eq!(Array2::<u16>::read_npy(&buf[..]).unwrap(), array![[3, 4]]);
+}
+
+#[test]
+fn read_unparseable_descriptor() {
+    // Descriptors which can't be parsed are passed through unchanged.
+    let buf = npy(string("<x4"), vec![0], &[]);
+    let header = Header::from_reader(&mut &buf[..]).unwrap();
+    assert_eq!(header.type_descriptor, string("<x4"));
+    assert!(header.dtype().is_err());
+    assert!(matches!(
+        Array1::<f32>::read_npy(&buf[..]),
+        Err(ReadNpyError::WrongDescriptor(desc)) if desc == string("<x4"),
+    ));
+}
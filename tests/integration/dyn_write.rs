@@ -0,0 +1,69 @@
+//! Tests for `DynWriteNpy`.
+
+use ndarray::prelude::*;
+use ndarray_npy::{read_npy_dyn, DynNpyArray, DynWriteNpy, WriteNpyExt};
+use std::io::Write;
+
+#[test]
+fn write_boxed() {
+    let arrays: Vec<Box<dyn DynWriteNpy>> = vec![
+        Box::new(array![1u8, 2]),
+        Box::new(array![[0.5f64], [1.5]]),
+        Box::new(array!["a".to_string()].into_dyn()),
+        Box::new(DynNpyArray::from(array![-1i16].into_dyn())),
+    ];
+    let expected: Vec<DynNpyArray> = vec![
+        array![1u8, 2].into_dyn().into(),
+        array![[0.5f64], [1.5]].into_dyn().into(),
+        array!["a".to_string()].into_dyn().into(),
+        array![-1i16].into_dyn().into(),
+    ];
+    for (arr, expected) in arrays.iter().zip(expected) {
+        let mut buf = Vec::new();
+        arr.write_npy(&mut buf).unwrap();
+        assert_eq!(read_npy_dyn(&buf[..]).unwrap(), expected);
+
+        let mut buf2 = Vec::new();
+        let writer: &mut dyn Write = &mut buf2;
+        arr.write_npy_dyn(writer).unwrap();
+        assert_eq!(buf2, buf);
+    }
+}
+
+#[test]
+fn write_send() {
+    let arr: Box<dyn DynWriteNpy + Send + Sync> = Box::new(array![[1i32, 2]].reversed_axes());
+    let buf = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        arr.write_npy(&mut buf).unwrap();
+        buf
+    })
+    .join()
+    .unwrap();
+    assert_eq!(
+        read_npy_dyn(&buf[..]).unwrap(),
+        DynNpyArray::I32(array![[1], [2]].into_dyn()),
+    );
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn npz_add_arrays() {
+    use ndarray_npy::{NpzReader, NpzWriter};
+    use std::io::Cursor;
+
+    let a = array![1u8, 2];
+    let b = array![[0.5f32]];
+    let arrays: [(&str, &dyn DynWriteNpy); 2] = [("a", &a), ("b", &b)];
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    npz.add_arrays(arrays).unwrap();
+    // Arrays of a single type don't need to be trait objects.
+    npz.add_arrays([("c".to_string(), &a), ("d".to_string(), &a)])
+        .unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    assert_eq!(npz.names().unwrap(), ["a", "b", "c", "d"]);
+    let arr: Array2<f32> = npz.by_name("b").unwrap();
+    assert_eq!(arr, b);
+    let arr: Array1<u8> = npz.by_name("d").unwrap();
+    assert_eq!(arr, a);
+}
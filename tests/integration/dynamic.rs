@@ -0,0 +1,145 @@
+//! Tests for `DynNpyArray` and `read_npy_dyn`.
+
+use ndarray::prelude::*;
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{
+    read_npy_dyn, DynNpyArray, DynNpyArrayVisitor, ReadNpyError, ReadNpyExt, ReadableElement,
+    WritableElement, WriteNpyExt,
+};
+use py_literal::Value as PyValue;
+use std::fmt::Debug;
+use std::fs::File;
+
+#[test]
+fn read_examples() {
+    let arr = read_npy_dyn(File::open("resources/array.npy").unwrap()).unwrap();
+    assert_eq!(arr.type_name(), "int32");
+    assert_eq!(
+        arr,
+        DynNpyArray::I32(array![[0, 1, 2], [3, 4, 5]].into_dyn())
+    );
+
+    let arr =
+        read_npy_dyn(File::open("resources/example_f64_big_endian_fortran.npy").unwrap()).unwrap();
+    let expected = Array3::<f64>::read_npy(
+        File::open("resources/example_f64_big_endian_fortran.npy").unwrap(),
+    )
+    .unwrap();
+    assert_eq!(arr, DynNpyArray::F64(expected.into_dyn()));
+    assert_eq!(arr.shape(), [2, 3, 4]);
+    assert_eq!(arr.ndim(), 3);
+    assert_eq!(arr.len(), 24);
+
+    let arr = read_npy_dyn(File::open("resources/example_bool_standard.npy").unwrap()).unwrap();
+    assert!(matches!(arr, DynNpyArray::Bool(_)));
+
+    #[cfg(feature = "num-complex-0_4")]
+    {
+        let arr =
+            read_npy_dyn(File::open("resources/example_c64_little_endian_standard.npy").unwrap())
+                .unwrap();
+        assert_eq!(arr.type_name(), "complex128");
+        assert!(matches!(arr, DynNpyArray::Complex64(_)));
+    }
+}
+
+#[test]
+fn round_trip() {
+    let arrays: Vec<DynNpyArray> = vec![
+        array![true, false].into_dyn().into(),
+        array![[-1i8, 2]].into_dyn().into(),
+        array![3u16].into_dyn().into(),
+        array![u64::MAX].into_dyn().into(),
+        arr0(1.5f32).into_dyn().into(),
+        Array2::<f64>::zeros((0, 3)).into_dyn().into(),
+        array![b"ab".to_vec(), b"".to_vec()].into_dyn().into(),
+        array!["a".to_string(), "bcd".to_string()].into_dyn().into(),
+    ];
+    for arr in arrays {
+        let mut buf = Vec::new();
+        arr.write_npy(&mut buf).unwrap();
+        assert_eq!(read_npy_dyn(&buf[..]).unwrap(), arr);
+    }
+}
+
+#[test]
+fn read_subarray() {
+    let header = Header {
+        type_descriptor: PyValue::Tuple(vec![
+            PyValue::String("<u2".into()),
+            PyValue::Tuple(vec![PyValue::Integer(2.into())]),
+        ]),
+        layout: Layout::Standard,
+        shape: vec![1],
+    };
+    let mut buf = header.to_bytes().unwrap();
+    buf.extend_from_slice(&[1, 0, 2, 0]);
+    assert_eq!(
+        read_npy_dyn(&buf[..]).unwrap(),
+        DynNpyArray::U16(array![[1, 2]].into_dyn()),
+    );
+}
+
+#[test]
+fn read_unsupported_descriptor() {
+    for desc in ["|O", "<M8[s]", "|V4"] {
+        let header = Header {
+            type_descriptor: PyValue::String(desc.into()),
+            layout: Layout::Standard,
+            shape: vec![0],
+        };
+        let buf = header.to_bytes().unwrap();
+        assert!(matches!(
+            read_npy_dyn(&buf[..]),
+            Err(ReadNpyError::WrongDescriptor(PyValue::String(s))) if s == desc,
+        ));
+    }
+}
+
+#[test]
+fn visit() {
+    struct Sum;
+
+    impl DynNpyArrayVisitor for Sum {
+        type Output = Option<f64>;
+
+        fn visit<A>(self, array: &ArrayD<A>) -> Option<f64>
+        where
+            A: WritableElement + ReadableElement + Clone + Debug + PartialEq,
+        {
+            array
+                .iter()
+                .map(|elem| format!("{:?}", elem).parse::<f64>().ok())
+                .sum()
+        }
+    }
+
+    let arr = DynNpyArray::from(array![1u8, 2, 3].into_dyn());
+    assert_eq!(arr.visit(Sum), Some(6.));
+    let arr = DynNpyArray::from(array![0.5f32, 0.25].into_dyn());
+    assert_eq!(arr.visit(Sum), Some(0.75));
+    let arr = DynNpyArray::from(array!["x".to_string()].into_dyn());
+    assert_eq!(arr.visit(Sum), None);
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn npz_by_name_dyn() {
+    use ndarray_npy::{NpzReader, NpzWriter};
+    use std::io::Cursor;
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    npz.add_array("a", &array![1i64, 2]).unwrap();
+    npz.add_array("b", &DynNpyArray::from(array![[0.5f32]].into_dyn()))
+        .unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    assert_eq!(
+        npz.by_name_dyn("a").unwrap(),
+        DynNpyArray::I64(array![1, 2].into_dyn()),
+    );
+    assert_eq!(
+        npz.by_name_dyn("b.npy").unwrap(),
+        DynNpyArray::F32(array![[0.5]].into_dyn()),
+    );
+    assert_eq!(npz.by_index_dyn(0).unwrap().type_name(), "int64");
+}
@@ -0,0 +1,142 @@
+//! Tests for enums with `#[derive(NpyEnum)]`.
+
+use ndarray::{array, Array1, ArrayView1, ArrayViewMut1};
+use ndarray_npy::{
+    NpyEnum, NpyRecord, ReadDataError, ReadNpyError, ReadNpyExt, ReadableElement, ViewDataError,
+    ViewElement, ViewMutNpyExt, ViewNpyExt, WritableElement, WriteNpyExt,
+};
+use py_literal::Value as PyValue;
+
+#[derive(Clone, Copy, Debug, PartialEq, NpyEnum)]
+#[repr(i32)]
+enum Species {
+    Cat = -1,
+    Dog = 7,
+    Bird,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, NpyEnum)]
+#[repr(u8)]
+enum Flag {
+    Off,
+    On,
+}
+
+#[test]
+fn descriptors() {
+    assert_eq!(Species::type_descriptor(), i32::type_descriptor());
+    assert_eq!(Flag::type_descriptor(), PyValue::String("|u1".into()));
+}
+
+#[test]
+fn round_trip() {
+    let arr = array![Species::Dog, Species::Cat, Species::Bird, Species::Dog];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(
+        Array1::<i32>::read_npy(&buf[..]).unwrap(),
+        array![7, -1, 8, 7]
+    );
+    assert_eq!(Array1::<Species>::read_npy(&buf[..]).unwrap(), arr);
+
+    let mut buf = Vec::new();
+    array![Flag::On, Flag::Off].write_npy(&mut buf).unwrap();
+    assert_eq!(
+        Array1::<Flag>::read_npy(&buf[..]).unwrap(),
+        array![Flag::On, Flag::Off],
+    );
+}
+
+#[test]
+fn read_non_native_endian() {
+    let data: Vec<u8> = [7i32, -1].iter().flat_map(|x| x.to_be_bytes()).collect();
+    assert_eq!(
+        Species::read_to_end_exact_vec(&data[..], &PyValue::String(">i4".into()), 2).unwrap(),
+        [Species::Dog, Species::Cat],
+    );
+}
+
+#[test]
+fn read_invalid_code() {
+    let mut buf = Vec::new();
+    array![7i32, 8, 3, 8].write_npy(&mut buf).unwrap();
+    match Array1::<Species>::read_npy(&buf[..]) {
+        Err(ReadNpyError::ParseData(err)) => assert_eq!(
+            err.to_string(),
+            "invalid value 3 for enum Species in element 2",
+        ),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn read_wrong_descriptor() {
+    let data = [0u8; 4];
+    assert!(matches!(
+        Species::read_to_end_exact_vec(&data[..], &PyValue::String("<u4".into()), 1),
+        Err(ReadDataError::WrongDescriptor(_)),
+    ));
+}
+
+#[test]
+fn view() {
+    let mut buf = Vec::new();
+    array![-1i32, 8, 7].write_npy(&mut buf).unwrap();
+    let mut aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    assert_eq!(
+        ArrayView1::<Species>::view_npy(&aligned).unwrap(),
+        array![Species::Cat, Species::Bird, Species::Dog],
+    );
+    let mut view = ArrayViewMut1::<Species>::view_mut_npy(&mut aligned).unwrap();
+    view[0] = Species::Dog;
+    assert_eq!(
+        ArrayView1::<i32>::view_npy(&aligned).unwrap(),
+        array![7, 8, 7],
+    );
+
+    let mut buf = Vec::new();
+    array![0u8, 1, 2].write_npy(&mut buf).unwrap();
+    let mut aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    match ArrayView1::<Flag>::view_npy(&aligned) {
+        Err(err) => assert!(
+            err.to_string()
+                .contains("invalid value 2 for enum Flag in element 2"),
+            "{}",
+            err,
+        ),
+        Ok(_) => panic!("expected an error"),
+    }
+    assert!(ArrayViewMut1::<Flag>::view_mut_npy(&mut aligned).is_err());
+
+    #[cfg(target_endian = "little")]
+    assert!(matches!(
+        Species::bytes_as_slice(&[0; 4], &PyValue::String(">i4".into()), 1),
+        Err(ViewDataError::NonNativeEndian),
+    ));
+}
+
+#[test]
+fn record_with_enum_field() {
+    #[derive(Clone, Copy, Debug, PartialEq, NpyRecord)]
+    #[repr(C)]
+    struct Pet {
+        species: Species,
+        age: f32,
+    }
+
+    let arr = array![
+        Pet {
+            species: Species::Bird,
+            age: 1.5,
+        },
+        Pet {
+            species: Species::Cat,
+            age: 3.,
+        },
+    ];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(Array1::<Pet>::read_npy(&buf[..]).unwrap(), arr);
+    let aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    assert_eq!(ArrayView1::<Pet>::view_npy(&aligned).unwrap(), arr);
+}
@@ -33,7 +33,7 @@ fn write_f64_standard() {
         *elem = i as f64;
     }
     assert_written_is_correct(&arr, &correct);
-    let arr_ref: &ArrayRef3<f64> = &*arr;
+    let arr_ref: &ArrayRef3<f64> = &arr;
     assert_written_is_correct(arr_ref, &correct);
 }
 
@@ -53,7 +53,7 @@ fn write_c64_standard() {
         *elem = Complex::new(i as f64, -(i as f64) + 0.);
     }
     assert_written_is_correct(&arr, &correct);
-    let arr_ref: &ArrayRef3<Complex<f64>> = &*arr;
+    let arr_ref: &ArrayRef3<Complex<f64>> = &arr;
     assert_written_is_correct(arr_ref, &correct);
 }
 
@@ -70,7 +70,7 @@ fn write_f64_fortran() {
         *elem = i as f64;
     }
     assert_written_is_correct(&arr, &correct);
-    let arr_ref: &ArrayRef3<f64> = &*arr;
+    let arr_ref: &ArrayRef3<f64> = &arr;
     assert_written_is_correct(arr_ref, &correct);
 }
 
@@ -90,7 +90,7 @@ fn write_c64_fortran() {
         *elem = Complex::new(i as f64, -(i as f64) + 0.);
     }
     assert_written_is_correct(&arr, &correct);
-    let arr_ref: &ArrayRef3<Complex<f64>> = &*arr;
+    let arr_ref: &ArrayRef3<Complex<f64>> = &arr;
     assert_written_is_correct(arr_ref, &correct);
 }
 
@@ -109,7 +109,7 @@ fn write_f64_discontiguous() {
         *elem = i as f64;
     }
     assert_written_is_correct(&arr, &correct);
-    let arr_ref: &ArrayRef3<f64> = &*arr;
+    let arr_ref: &ArrayRef3<f64> = &arr;
     assert_written_is_correct(arr_ref, &correct);
 }
 
@@ -131,7 +131,7 @@ fn write_c64_discontiguous() {
         *elem = Complex::new(i as f64, -(i as f64) + 0.);
     }
     assert_written_is_correct(&arr, &correct);
-    let arr_ref: &ArrayRef3<Complex<f64>> = &*arr;
+    let arr_ref: &ArrayRef3<Complex<f64>> = &arr;
     assert_written_is_correct(arr_ref, &correct);
 }
 
@@ -0,0 +1,154 @@
+//! Tests for `NpyFile`.
+
+use ndarray::prelude::*;
+use ndarray_npy::cast::CastPolicy;
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{DynNpyArray, NpyFile, ReadNpyError, ReadNpyExt, WriteNpyExt};
+use py_literal::Value as PyValue;
+use std::fs::File;
+use std::io::Cursor;
+
+#[test]
+fn open() {
+    let mut file = NpyFile::open("resources/array.npy").unwrap();
+    assert_eq!(file.shape(), [2, 3]);
+    assert_eq!(file.type_descriptor(), &PyValue::String("<i4".into()));
+    assert_eq!(file.layout(), Layout::Standard);
+    assert_eq!(file.data_offset(), 128);
+    assert_eq!(file.data_len(), Some(24));
+    assert_eq!(file.dtype().unwrap().item_size(), Some(4));
+
+    let expected = array![[0, 1, 2], [3, 4, 5]];
+    // The data can be read any number of times, in any way.
+    for _ in 0..2 {
+        let arr: Array2<i32> = file.read().unwrap();
+        assert_eq!(arr, expected);
+    }
+    assert_eq!(
+        file.read_dyn().unwrap(),
+        DynNpyArray::I32(expected.clone().into_dyn()),
+    );
+    let arr: Array2<i64> = file.read_as(CastPolicy::Lossless).unwrap();
+    assert_eq!(arr, expected.mapv(i64::from));
+    assert_eq!(
+        file.read_raw().unwrap(),
+        expected
+            .iter()
+            .flat_map(|x| x.to_le_bytes())
+            .collect::<Vec<_>>(),
+    );
+    let rows: Array2<i32> = file.read_rows(1..2).unwrap();
+    assert_eq!(rows, array![[3, 4, 5]]);
+    assert!(matches!(
+        file.read::<i32, Ix1>(),
+        Err(ReadNpyError::WrongNdim(Some(1), 2)),
+    ));
+}
+
+#[test]
+fn read_rows_fortran() {
+    let path = "resources/example_f64_big_endian_fortran.npy";
+    let expected = Array3::<f64>::read_npy(File::open(path).unwrap()).unwrap();
+    let mut file = NpyFile::open(path).unwrap();
+    assert_eq!(file.layout(), Layout::Fortran);
+    for (start, end) in [(0, 2), (1, 2), (0, 1), (1, 1)] {
+        let rows: Array3<f64> = file.read_rows(start..end).unwrap();
+        assert_eq!(rows, expected.slice(s![start..end, .., ..]));
+    }
+    let rows = file.read_rows_dyn(1..2).unwrap();
+    assert_eq!(
+        rows,
+        DynNpyArray::F64(expected.slice(s![1..2, .., ..]).to_owned().into_dyn()),
+    );
+}
+
+#[test]
+fn read_rows_standard() {
+    let arr = Array::from_shape_fn((5, 2, 3), |(i, j, k)| (i * 100 + j * 10 + k) as u16);
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    let mut file = NpyFile::new(Cursor::new(buf)).unwrap();
+    for (start, end) in [(0, 5), (2, 4), (4, 5), (3, 3)] {
+        let rows: Array3<u16> = file.read_rows(start..end).unwrap();
+        assert_eq!(rows, arr.slice(s![start..end, .., ..]));
+    }
+    let rows: Array3<u16> = file.read_rows(2..2).unwrap();
+    assert_eq!(rows.shape(), [0, 2, 3]);
+}
+
+#[test]
+fn read_rows_subarray() {
+    let header = Header {
+        type_descriptor: PyValue::Tuple(vec![
+            PyValue::String("<u2".into()),
+            PyValue::Tuple(vec![PyValue::Integer(2.into())]),
+        ]),
+        layout: Layout::Fortran,
+        shape: vec![3, 2],
+    };
+    let mut buf = header.to_bytes().unwrap();
+    // Elements in Fortran order, each a subarray of two `u16` values.
+    for (i, j) in [(0u16, 0u16), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)] {
+        for k in 0..2 {
+            buf.extend_from_slice(&(i * 100 + j * 10 + k as u16).to_le_bytes());
+        }
+    }
+    let expected = Array::from_shape_fn((3, 2, 2), |(i, j, k)| (i * 100 + j * 10 + k) as u16);
+    let mut file = NpyFile::new(Cursor::new(buf)).unwrap();
+    assert_eq!(file.data_len(), Some(24));
+    assert_eq!(file.read::<u16, Ix3>().unwrap(), expected);
+    let rows: Array3<u16> = file.read_rows(1..3).unwrap();
+    assert_eq!(rows, expected.slice(s![1..3, .., ..]));
+    let rows: Array2<[u16; 2]> = file.read_rows(2..3).unwrap();
+    assert_eq!(
+        rows,
+        Array2::from_shape_vec((1, 2), vec![[200, 201], [210, 211]]).unwrap(),
+    );
+}
+
+#[test]
+fn start_offset() {
+    let mut buf = b"prefix".to_vec();
+    array![1.5f32, 2.5].write_npy(&mut buf).unwrap();
+    let mut cursor = Cursor::new(buf);
+    cursor.set_position(6);
+    let mut file = NpyFile::new(cursor).unwrap();
+    assert_eq!(file.data_offset(), 128);
+    assert_eq!(file.read::<f32, Ix1>().unwrap(), array![1.5, 2.5]);
+    let rows: Array1<f32> = file.read_rows(1..2).unwrap();
+    assert_eq!(rows, array![2.5]);
+}
+
+#[test]
+fn missing_data() {
+    let mut buf = Vec::new();
+    array![[1u32, 2], [3, 4]].write_npy(&mut buf).unwrap();
+    buf.truncate(buf.len() - 1);
+    let mut file = NpyFile::new(Cursor::new(buf)).unwrap();
+    assert_eq!(file.read_rows::<u32, Ix2>(0..1).unwrap(), array![[1, 2]]);
+    assert!(matches!(
+        file.read_rows::<u32, Ix2>(1..2),
+        Err(ReadNpyError::MissingData),
+    ));
+    assert!(matches!(
+        file.read::<u32, Ix2>(),
+        Err(ReadNpyError::MissingData)
+    ));
+}
+
+#[test]
+#[should_panic(expected = "out of bounds")]
+fn read_rows_out_of_bounds() {
+    let mut file = NpyFile::open("resources/array.npy").unwrap();
+    let _ = file.read_rows::<i32, Ix2>(1..3);
+}
+
+#[cfg(feature = "memmap2")]
+#[test]
+fn map() {
+    let file = NpyFile::open("resources/array.npy").unwrap();
+    let mmap = unsafe { file.map() }.unwrap();
+    assert_eq!(mmap.header(), file.header());
+    let view = mmap.view::<i32, Ix2>().unwrap();
+    assert_eq!(view, array![[0, 1, 2], [3, 4, 5]]);
+}
@@ -0,0 +1,128 @@
+//! Tests for `gather_rows`.
+
+use ndarray::prelude::*;
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{gather_rows, NpyFile, ReadNpyError, ReadNpyExt};
+use py_literal::Value as PyValue;
+use std::fs::File;
+use std::io::Cursor;
+
+use crate::{npy, RecordingReader};
+
+#[test]
+fn standard_and_fortran() {
+    let arr = Array::from_shape_fn((6, 3, 4), |(i, j, k)| (i * 100 + j * 10 + k) as i32);
+    let mut fortran = Array3::zeros((6, 3, 4).f());
+    fortran.assign(&arr);
+    for buf in [npy(&arr), npy(&fortran)] {
+        for rows in [
+            &[][..],
+            &[0],
+            &[5, 0, 3],
+            &[2, 2, 1, 2],
+            &[0, 1, 2, 3, 4, 5],
+        ] {
+            let actual: Array3<i32> = gather_rows(Cursor::new(&buf), rows).unwrap();
+            assert_eq!(actual, arr.select(Axis(0), rows), "{:?}", rows);
+        }
+        let actual: ArrayD<i32> = gather_rows(Cursor::new(&buf), &[4, 1]).unwrap();
+        assert_eq!(actual, arr.select(Axis(0), &[4, 1]).into_dyn());
+    }
+}
+
+#[test]
+fn one_dimensional() {
+    let arr = array![10u64, 11, 12, 13];
+    let actual: Array1<u64> = gather_rows(Cursor::new(npy(&arr)), &[3, 0, 3]).unwrap();
+    assert_eq!(actual, array![13, 10, 13]);
+}
+
+#[test]
+fn coalesced_reads() {
+    let arr = Array::from_shape_fn((100, 5), |(i, j)| (i * 10 + j) as f32);
+    let buf = npy(&arr);
+    let header_len = buf.len() - arr.len() * 4;
+
+    let rows = [41, 7, 40, 42, 90, 8, 41];
+    let mut file = NpyFile::new(RecordingReader::new(buf)).unwrap();
+    let batch: Array2<f32> = file.gather_rows(&rows).unwrap();
+    assert_eq!(batch, arr.select(Axis(0), &rows));
+    let reader = file.into_inner();
+    let data_reads: Vec<_> = reader
+        .reads
+        .iter()
+        .filter(|&&(position, _)| position >= header_len as u64)
+        .copied()
+        .collect();
+    let row_len = 5 * 4;
+    let at = |row: usize| (header_len + row * row_len) as u64;
+    assert_eq!(
+        data_reads,
+        [
+            (at(7), 2 * row_len),
+            (at(40), 3 * row_len),
+            (at(90), row_len)
+        ],
+    );
+}
+
+#[test]
+fn big_endian() {
+    for path in [
+        "resources/example_f64_big_endian_standard.npy",
+        "resources/example_f64_big_endian_fortran.npy",
+    ] {
+        let arr = Array3::<f64>::read_npy(File::open(path).unwrap()).unwrap();
+        let rows = [1, 0, 1];
+        let actual: Array3<f64> = gather_rows(File::open(path).unwrap(), &rows).unwrap();
+        assert_eq!(actual, arr.select(Axis(0), &rows));
+    }
+}
+
+#[test]
+fn subarray() {
+    let header = Header {
+        type_descriptor: PyValue::Tuple(vec![
+            PyValue::String(">u2".into()),
+            PyValue::Tuple(vec![PyValue::Integer(2.into())]),
+        ]),
+        layout: Layout::Standard,
+        shape: vec![3],
+    };
+    let mut buf = header.to_bytes().unwrap();
+    for x in 0..6u16 {
+        buf.extend_from_slice(&x.to_be_bytes());
+    }
+    let actual: Array1<[u16; 2]> = gather_rows(Cursor::new(&buf), &[2, 0]).unwrap();
+    assert_eq!(actual, Array1::from(vec![[4, 5], [0, 1]]));
+}
+
+#[test]
+fn wrong_ndim() {
+    let buf = npy(&array![[1u8, 2]]);
+    assert!(matches!(
+        gather_rows::<u8, _, Ix3>(Cursor::new(&buf), &[0]),
+        Err(ReadNpyError::WrongNdim(Some(3), 2)),
+    ));
+}
+
+#[test]
+fn unsized_descriptor() {
+    let header = Header {
+        type_descriptor: PyValue::String("|O".into()),
+        layout: Layout::Standard,
+        shape: vec![2],
+    };
+    let buf = header.to_bytes().unwrap();
+    assert!(matches!(
+        gather_rows::<u8, _, Ix1>(Cursor::new(&buf), &[0]),
+        Err(ReadNpyError::WrongDescriptor(_)),
+    ));
+}
+
+#[test]
+#[should_panic]
+fn out_of_bounds() {
+    let buf = npy(&array![[1u8, 2], [3, 4]]);
+    let _ = gather_rows::<u8, _, Ix2>(Cursor::new(&buf), &[0, 2]);
+}
@@ -0,0 +1,148 @@
+//! Tests for the `long double` element types.
+
+use ndarray::Array1;
+use ndarray_npy::longdouble::{Exact, LongDouble};
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{ReadDataError, ReadNpyError, ReadNpyExt, ReadableElement};
+use py_literal::Value as PyValue;
+
+/// Encodes an x87 extended precision value padded to `size` bytes.
+fn x87(negative: bool, exp: u16, mantissa: u64, size: usize) -> Vec<u8> {
+    let mut out = mantissa.to_le_bytes().to_vec();
+    out.extend_from_slice(&(exp | (u16::from(negative) << 15)).to_le_bytes());
+    out.resize(size, 0);
+    out
+}
+
+/// Exponent bias of the x87 format.
+const BIAS: u16 = 16383;
+
+/// Integer bit of the x87 mantissa.
+const ONE: u64 = 1 << 63;
+
+fn read<T: ReadableElement>(desc: &str, values: &[Vec<u8>]) -> Result<Vec<T>, ReadDataError> {
+    T::read_to_end_exact_vec(
+        &values.concat()[..],
+        &PyValue::String(desc.into()),
+        values.len(),
+    )
+}
+
+#[test]
+fn read_exact_values() {
+    let values = [
+        x87(false, BIAS, ONE, 16),
+        x87(true, BIAS + 1, 0b101 << 61, 16),
+        x87(false, 0, 0, 16),
+        x87(true, 0, 0, 16),
+        // The smallest positive subnormal `f64`.
+        x87(false, BIAS - 1074, ONE, 16),
+        x87(false, 0x7fff, ONE, 16),
+        x87(true, 0x7fff, ONE, 16),
+    ];
+    let out: Vec<f64> = read::<LongDouble<Exact>>("<f16", &values)
+        .unwrap()
+        .into_iter()
+        .map(LongDouble::get)
+        .collect();
+    assert_eq!(
+        out,
+        [
+            1.,
+            -2.5,
+            0.,
+            -0.,
+            f64::from_bits(1),
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+        ],
+    );
+    assert!(out[3].is_sign_negative());
+
+    let nan = read::<LongDouble<Exact>>("<f16", &[x87(false, 0x7fff, ONE | 1, 16)]).unwrap();
+    assert!(nan[0].get().is_nan());
+}
+
+#[test]
+fn read_rounded_values() {
+    let values = [
+        // 1 + 2^-60 rounds down to 1.
+        x87(false, BIAS, ONE | 1 << 3, 12),
+        // 1 + 2^-53 is halfway between 1 and the next `f64`, so it rounds to
+        // even (down).
+        x87(false, BIAS, ONE | 1 << 10, 12),
+        // 1 + 3 * 2^-53 is halfway, and rounds to even (up).
+        x87(false, BIAS, ONE | 3 << 10, 12),
+        // All ones in the mantissa rounds up to the next power of two.
+        x87(false, BIAS, u64::MAX, 12),
+        // Too large for `f64`.
+        x87(true, BIAS + 2000, ONE, 12),
+        // Too small for `f64`.
+        x87(false, BIAS - 2000, ONE, 12),
+        // x87 denormals are far too small for `f64`.
+        x87(false, 0, 1, 12),
+    ];
+    let out: Vec<f64> = read::<LongDouble>("<f12", &values)
+        .unwrap()
+        .into_iter()
+        .map(LongDouble::get)
+        .collect();
+    assert_eq!(
+        out,
+        [1., 1., 1. + 2f64.powi(-51), 2., f64::NEG_INFINITY, 0., 0.],
+    );
+
+    for (index, value) in values.iter().enumerate() {
+        let mut input = vec![x87(false, BIAS, ONE, 12)];
+        input.push(value.clone());
+        match read::<LongDouble<Exact>>("<f12", &input) {
+            Err(ReadDataError::ParseData(err)) => {
+                assert!(err.to_string().contains("element 1"), "{}", index)
+            }
+            other => panic!("unexpected result {:?} for value {}", other, index),
+        }
+    }
+}
+
+#[test]
+fn read_wrong_descriptor() {
+    for desc in ["<f8", ">f16", "<f10", "<c32"] {
+        assert!(matches!(
+            read::<LongDouble>(desc, &[x87(false, BIAS, ONE, 16)]),
+            Err(ReadDataError::WrongDescriptor(_)),
+        ));
+    }
+}
+
+#[test]
+fn read_npy_file() {
+    let header = Header {
+        type_descriptor: PyValue::String("<f16".into()),
+        layout: Layout::Standard,
+        shape: vec![2],
+    };
+    let mut buf = header.to_bytes().unwrap();
+    buf.extend(x87(false, BIAS + 3, 0b11 << 62, 16));
+    buf.extend(x87(false, BIAS, ONE | 1, 16));
+    let arr = Array1::<LongDouble>::read_npy(&buf[..]).unwrap();
+    assert_eq!(arr.mapv(LongDouble::get), ndarray::array![12., 1.]);
+    assert!(matches!(
+        Array1::<LongDouble<Exact>>::read_npy(&buf[..]),
+        Err(ReadNpyError::ParseData(_)),
+    ));
+}
+
+#[cfg(feature = "num-complex-0_4")]
+#[test]
+fn read_complex() {
+    use ndarray_npy::longdouble::ComplexLongDouble;
+    use num_complex_0_4::Complex;
+
+    let values = [[x87(false, BIAS, ONE, 16), x87(true, BIAS - 1, ONE, 16)].concat()];
+    let out = read::<ComplexLongDouble>("<c32", &values).unwrap();
+    assert_eq!(out[0].get(), Complex::new(1., -0.5));
+    assert!(matches!(
+        read::<ComplexLongDouble>("<f16", &values),
+        Err(ReadDataError::WrongDescriptor(_)),
+    ));
+}
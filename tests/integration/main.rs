@@ -1,15 +1,85 @@
 //! Integration tests.
 
 use memmap2::{Mmap, MmapMut};
+use ndarray::{ArrayRef, Dimension};
+use ndarray_npy::{WritableElement, WriteNpyExt};
 use std::fs::File;
-use std::io::{self, Read};
+use std::io::{self, Cursor, Read, Seek, SeekFrom};
 use std::ops::{Deref, DerefMut};
 
+mod cast;
+mod chunks;
+mod datetime;
+mod dtype;
+mod dyn_write;
+mod dynamic;
+#[cfg(feature = "derive")]
+mod enums;
 mod examples;
+mod file;
+mod gather;
+mod longdouble;
+mod ml_dtypes;
+mod newtype;
 #[cfg(feature = "npz")]
 mod npz;
+#[cfg(feature = "ordered-float")]
+mod ordered_float;
+mod packbits;
+#[cfg(feature = "pickle")]
+mod pickle;
 mod primitive;
+mod raw;
+#[cfg(feature = "derive")]
+mod record;
 mod round_trip;
+mod slice;
+mod stream;
+mod strings;
+mod subarray;
+
+/// Returns the array in `.npy` format.
+pub fn npy<A: WritableElement, D: Dimension>(arr: &ArrayRef<A, D>) -> Vec<u8> {
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    buf
+}
+
+/// A reader which records the ranges of bytes which are read.
+pub struct RecordingReader {
+    inner: Cursor<Vec<u8>>,
+    /// The position and length of each read.
+    pub reads: Vec<(u64, usize)>,
+}
+
+impl RecordingReader {
+    pub fn new(data: Vec<u8>) -> Self {
+        RecordingReader {
+            inner: Cursor::new(data),
+            reads: Vec::new(),
+        }
+    }
+
+    /// Returns the total number of bytes read.
+    pub fn bytes_read(&self) -> usize {
+        self.reads.iter().map(|&(_, len)| len).sum()
+    }
+}
+
+impl Read for RecordingReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let position = self.inner.position();
+        let len = self.inner.read(buf)?;
+        self.reads.push((position, len));
+        Ok(len)
+    }
+}
+
+impl Seek for RecordingReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.inner.seek(pos)
+    }
+}
 
 /// A contiguous block of bytes which may be aligned.
 pub struct MaybeAlignedBytes {
@@ -0,0 +1,188 @@
+//! Tests for the `ml_dtypes` element types.
+
+use ndarray::{array, Array1, ArrayView1, ArrayViewMut1};
+use ndarray_npy::ml_dtypes::{read_npy_to_f32, BFloat16, Float8E4M3Fn, Float8E5M2, MlFloat};
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{
+    ReadDataError, ReadNpyExt, ReadableElement, ViewMutNpyExt, ViewNpyError, ViewNpyExt,
+    WritableElement, WriteNpyExt,
+};
+use py_literal::Value as PyValue;
+
+/// Returns an `.npy` file with the given descriptor and data.
+fn npy(type_desc: &str, data: &[u8], len: usize) -> Vec<u8> {
+    let header = Header {
+        type_descriptor: PyValue::String(type_desc.into()),
+        layout: Layout::Standard,
+        shape: vec![len],
+    };
+    let mut buf = header.to_bytes().unwrap();
+    buf.extend_from_slice(data);
+    buf
+}
+
+#[test]
+fn decode_float8_e4m3fn() {
+    let cases: [(u8, f32); 9] = [
+        (0x00, 0.),
+        (0x38, 1.),
+        (0xc4, -3.),
+        (0x7e, 448.),
+        (0xfe, -448.),
+        // The smallest subnormal and the largest subnormal.
+        (0x01, 2f32.powi(-9)),
+        (0x07, 7. * 2f32.powi(-9)),
+        // The smallest normal value.
+        (0x08, 2f32.powi(-6)),
+        // The exponent bits are all ones, but this isn't NaN.
+        (0x78, 256.),
+    ];
+    for (bits, value) in cases {
+        assert_eq!(
+            Float8E4M3Fn::from_bits(bits).to_f32(),
+            value,
+            "{:#04x}",
+            bits
+        );
+    }
+    assert!(Float8E4M3Fn::from_bits(0x7f).to_f32().is_nan());
+    assert!(Float8E4M3Fn::from_bits(0xff).to_f32().is_nan());
+    assert!(Float8E4M3Fn::from_bits(0x80).to_f32().is_sign_negative());
+}
+
+#[test]
+fn decode_float8_e5m2() {
+    let cases: [(u8, f32); 7] = [
+        (0x3c, 1.),
+        (0xc2, -3.),
+        (0x7b, 57344.),
+        (0x01, 2f32.powi(-16)),
+        (0x04, 2f32.powi(-14)),
+        (0x7c, f32::INFINITY),
+        (0xfc, f32::NEG_INFINITY),
+    ];
+    for (bits, value) in cases {
+        assert_eq!(Float8E5M2::from_bits(bits).to_f32(), value, "{:#04x}", bits);
+    }
+    for bits in [0x7d, 0x7e, 0xff] {
+        assert!(Float8E5M2::from_bits(bits).to_f32().is_nan());
+    }
+}
+
+#[test]
+fn decode_bfloat16() {
+    assert_eq!(BFloat16::from_bits(0x3fc0).to_f32(), 1.5);
+    assert_eq!(f32::from(BFloat16::from_bits(0xc080)), -4.);
+    assert!(BFloat16::from_bits(0x7fc0).to_f32().is_nan());
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn bfloat16_and_half_bf16() {
+    use half::bf16;
+
+    let value = bf16::from_f32(-2.75);
+    assert_eq!(BFloat16::from(value).to_bits(), value.to_bits());
+    assert_eq!(bf16::from(BFloat16::from_bits(0x3fc0)), bf16::from_f32(1.5));
+
+    // Both types use the same descriptor, so either reads the other's files.
+    let halves = array![bf16::from_f32(1.5), bf16::NEG_INFINITY];
+    let mut buf = Vec::new();
+    halves.write_npy(&mut buf).unwrap();
+    let read = Array1::<BFloat16>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read, halves.mapv(BFloat16::from));
+    let mut buf = Vec::new();
+    read.write_npy(&mut buf).unwrap();
+    assert_eq!(Array1::<bf16>::read_npy(&buf[..]).unwrap(), halves);
+}
+
+#[test]
+fn read_as_declared_format() {
+    // The same `|V1` data can be interpreted as either float8 variant.
+    let buf = npy("|V1", &[0x38, 0x3c], 2);
+    assert_eq!(
+        read_npy_to_f32::<Float8E4M3Fn, _, _>(&buf[..]).unwrap(),
+        array![1., 1.5],
+    );
+    assert_eq!(
+        read_npy_to_f32::<Float8E5M2, _, _>(&buf[..]).unwrap(),
+        array![0.5, 1.],
+    );
+    assert_eq!(
+        Array1::<Float8E5M2>::read_npy(&buf[..]).unwrap(),
+        array![Float8E5M2::from_bits(0x38), Float8E5M2::from_bits(0x3c)],
+    );
+
+    let data = [0xc0, 0x3f, 0x80, 0xc0];
+    for desc in ["<V2", "|V2"] {
+        assert_eq!(
+            read_npy_to_f32::<BFloat16, _, _>(&npy(desc, &data, 2)[..]).unwrap(),
+            array![1.5, -4.],
+        );
+    }
+    let data = [0x3f, 0xc0, 0xc0, 0x80];
+    assert_eq!(
+        read_npy_to_f32::<BFloat16, _, _>(&npy(">V2", &data, 2)[..]).unwrap(),
+        array![1.5, -4.],
+    );
+}
+
+#[test]
+fn read_wrong_descriptor() {
+    for desc in ["|u1", "|V2", "|S1"] {
+        assert!(matches!(
+            Float8E4M3Fn::read_to_end_exact_vec(&[0, 0][..], &PyValue::String(desc.into()), 1),
+            Err(ReadDataError::WrongDescriptor(_)),
+        ));
+    }
+    assert!(matches!(
+        BFloat16::read_to_end_exact_vec(&[0, 0][..], &PyValue::String("<f2".into()), 1),
+        Err(ReadDataError::WrongDescriptor(_)),
+    ));
+}
+
+#[test]
+fn write_and_view() {
+    assert_eq!(
+        Float8E4M3Fn::type_descriptor(),
+        PyValue::String("|V1".into())
+    );
+    #[cfg(target_endian = "little")]
+    assert_eq!(BFloat16::type_descriptor(), PyValue::String("<V2".into()));
+
+    let arr = array![BFloat16::from_bits(0x3fc0), BFloat16::from_bits(0xc080)];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    let mut aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    assert_eq!(ArrayView1::<BFloat16>::view_npy(&aligned).unwrap(), arr);
+    ArrayViewMut1::<BFloat16>::view_mut_npy(&mut aligned).unwrap()[0] = BFloat16::from_bits(0x4000);
+    assert_eq!(
+        read_npy_to_f32::<BFloat16, _, _>(&aligned[..]).unwrap(),
+        array![2., -4.],
+    );
+
+    let mut buf = Vec::new();
+    array![Float8E5M2::from_bits(0x3c)]
+        .write_npy(&mut buf)
+        .unwrap();
+    let mut aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    assert_eq!(
+        ArrayView1::<Float8E4M3Fn>::view_npy(&aligned).unwrap()[0].to_f32(),
+        1.5,
+    );
+    ArrayViewMut1::<Float8E5M2>::view_mut_npy(&mut aligned).unwrap()[0] =
+        Float8E5M2::from_bits(0xc2);
+    assert_eq!(
+        ArrayView1::<Float8E5M2>::view_npy(&aligned).unwrap()[0].to_f32(),
+        -3.,
+    );
+
+    #[cfg(target_endian = "little")]
+    {
+        let aligned = crate::MaybeAlignedBytes::aligned_from_bytes(npy(">V2", &[0, 0], 1), 64);
+        assert!(matches!(
+            ArrayView1::<BFloat16>::view_npy(&aligned),
+            Err(ViewNpyError::NonNativeEndian),
+        ));
+    }
+}
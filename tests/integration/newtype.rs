@@ -0,0 +1,90 @@
+//! Tests for `impl_newtype_element!`.
+
+use ndarray::{array, Array1, ArrayView1, ArrayViewMut1};
+use ndarray_npy::{
+    impl_newtype_element, ReadNpyError, ReadNpyExt, ViewMutNpyExt, ViewNpyExt, WritableElement,
+    WriteNpyExt,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[repr(transparent)]
+struct Meters(f64);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PixelId(u32);
+
+#[derive(Clone, Debug, PartialEq)]
+struct Label(String);
+
+impl_newtype_element!(Meters(f64));
+impl_newtype_element!(PixelId(u32));
+impl_newtype_element!(Label(String): WritableElement, ReadableElement);
+
+#[test]
+fn round_trip() {
+    assert_eq!(Meters::type_descriptor(), f64::type_descriptor());
+    let arr = array![PixelId(3), PixelId(u32::MAX)];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(
+        Array1::<u32>::read_npy(&buf[..]).unwrap(),
+        array![3, u32::MAX]
+    );
+    assert_eq!(Array1::<PixelId>::read_npy(&buf[..]).unwrap(), arr);
+    assert!(matches!(
+        Array1::<Meters>::read_npy(&buf[..]),
+        Err(ReadNpyError::WrongDescriptor(_)),
+    ));
+}
+
+#[test]
+fn round_trip_data_dependent_descriptor() {
+    // The width of the string descriptor depends on the data, which the
+    // newtype must delegate to `String`.
+    let arr = array![Label("a".into()), Label("longer".into())];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(
+        Array1::<String>::read_npy(&buf[..]).unwrap(),
+        array!["a".to_string(), "longer".to_string()],
+    );
+    assert_eq!(Array1::<Label>::read_npy(&buf[..]).unwrap(), arr);
+}
+
+#[test]
+fn view() {
+    let mut buf = Vec::new();
+    array![1.5, 2.5].write_npy(&mut buf).unwrap();
+    let mut aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    assert_eq!(
+        ArrayView1::<Meters>::view_npy(&aligned).unwrap(),
+        array![Meters(1.5), Meters(2.5)],
+    );
+    ArrayViewMut1::<Meters>::view_mut_npy(&mut aligned).unwrap()[1] = Meters(-1.);
+    assert_eq!(
+        ArrayView1::<f64>::view_npy(&aligned).unwrap(),
+        array![1.5, -1.]
+    );
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn record_with_newtype_field() {
+    use ndarray_npy::NpyRecord;
+
+    #[derive(Clone, Copy, Debug, PartialEq, NpyRecord)]
+    #[repr(C)]
+    struct Sample {
+        pixel: PixelId,
+        depth: Meters,
+    }
+
+    let arr = array![Sample {
+        pixel: PixelId(7),
+        depth: Meters(0.25),
+    }];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    let aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    assert_eq!(ArrayView1::<Sample>::view_npy(&aligned).unwrap(), arr);
+}
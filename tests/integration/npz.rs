@@ -1,6 +1,6 @@
 //! .npz examples.
 
-use ndarray::{array, Array2, ArrayRef2};
+use ndarray::{array, Array1, Array2, ArrayRef2};
 use ndarray_npy::{NpzReader, NpzWriter};
 use std::{error::Error, io::Cursor};
 
@@ -60,3 +60,18 @@ fn round_trip_npz() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+fn byte_strings_npz() -> Result<(), Box<dyn Error>> {
+    let mut buf = Vec::<u8>::new();
+    let labels = array![b"cat".to_vec(), b"mouse".to_vec()];
+    {
+        let mut writer = NpzWriter::new(Cursor::new(&mut buf));
+        writer.add_array("labels", &labels)?;
+        writer.finish()?;
+    }
+    let mut reader = NpzReader::new(Cursor::new(&buf))?;
+    let read: Array1<Vec<u8>> = reader.by_name("labels")?;
+    assert_eq!(read, labels);
+    Ok(())
+}
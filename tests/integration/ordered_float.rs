@@ -0,0 +1,82 @@
+//! Tests for the `ordered-float` element types.
+
+use ndarray::{array, Array1, ArrayView1, ArrayViewMut1};
+use ndarray_npy::{ReadNpyError, ReadNpyExt, ViewMutNpyExt, ViewNpyError, ViewNpyExt, WriteNpyExt};
+use ordered_float::{NotNan, OrderedFloat};
+
+fn not_nan(value: f64) -> NotNan<f64> {
+    NotNan::new(value).unwrap()
+}
+
+#[test]
+fn round_trip() {
+    let arr = array![OrderedFloat(1.5f32), OrderedFloat(f32::NAN)];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    let read = Array1::<f32>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read[0], 1.5);
+    assert!(read[1].is_nan());
+    assert_eq!(
+        Array1::<OrderedFloat<f32>>::read_npy(&buf[..]).unwrap(),
+        arr
+    );
+
+    let arr = array![not_nan(-2.), not_nan(f64::INFINITY)];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(
+        Array1::<f64>::read_npy(&buf[..]).unwrap(),
+        array![-2., f64::INFINITY]
+    );
+    assert_eq!(Array1::<NotNan<f64>>::read_npy(&buf[..]).unwrap(), arr);
+}
+
+#[test]
+fn read_nan() {
+    let mut buf = Vec::new();
+    array![1f64, 2., f64::NAN, f64::NAN]
+        .write_npy(&mut buf)
+        .unwrap();
+    assert_eq!(
+        Array1::<OrderedFloat<f64>>::read_npy(&buf[..]).unwrap()[0],
+        OrderedFloat(1.),
+    );
+    match Array1::<NotNan<f64>>::read_npy(&buf[..]) {
+        Err(ReadNpyError::ParseData(err)) => {
+            assert_eq!(err.to_string(), "NaN value in element 2")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(matches!(
+        Array1::<NotNan<f32>>::read_npy(&buf[..]),
+        Err(ReadNpyError::WrongDescriptor(_)),
+    ));
+}
+
+#[test]
+fn view() {
+    let mut buf = Vec::new();
+    array![1f32, 2., 3.].write_npy(&mut buf).unwrap();
+    let mut aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    assert_eq!(
+        ArrayView1::<NotNan<f32>>::view_npy(&aligned).unwrap(),
+        array![1f32, 2., 3.].mapv(|x| NotNan::new(x).unwrap()),
+    );
+    let mut view = ArrayViewMut1::<OrderedFloat<f32>>::view_mut_npy(&mut aligned).unwrap();
+    view[1] = OrderedFloat(f32::NAN);
+    assert!(
+        ArrayView1::<OrderedFloat<f32>>::view_npy(&aligned).unwrap()[1]
+            .0
+            .is_nan()
+    );
+    match ArrayView1::<NotNan<f32>>::view_npy(&aligned) {
+        Err(ViewNpyError::InvalidData(err)) => {
+            assert_eq!(err.to_string(), "NaN value in element 1")
+        }
+        other => panic!("unexpected result {:?}", other),
+    }
+    assert!(matches!(
+        ArrayViewMut1::<NotNan<f32>>::view_mut_npy(&mut aligned),
+        Err(ViewNpyError::InvalidData(_)),
+    ));
+}
@@ -0,0 +1,152 @@
+//! Tests for bit-packed `bool` arrays.
+
+use ndarray::{arr0, array, Array1, Array2, Array3, ArrayD, Ix3, IxDyn};
+use ndarray_npy::packbits::{
+    pack_bits, read_packed, read_packed_npy, unpack_bits, write_packed, write_packed_npy, BitOrder,
+    UnpackBitsError,
+};
+use ndarray_npy::{ReadNpyError, ReadNpyExt, WriteNpyExt};
+use std::fs::File;
+
+#[test]
+fn pack_big_and_little() {
+    // The expected values are from `np.packbits(a, axis=-1, bitorder=...)`.
+    let arr = array![true, true, false, false, true, false, true, true, false, true];
+    assert_eq!(pack_bits(&arr, BitOrder::Big), array![0xcb, 0x40]);
+    assert_eq!(pack_bits(&arr, BitOrder::Little), array![0xd3, 0x02]);
+    for order in [BitOrder::Big, BitOrder::Little] {
+        assert_eq!(unpack_bits(&pack_bits(&arr, order), 10, order), arr);
+    }
+}
+
+#[test]
+fn unpack_count() {
+    let packed = array![0b1010_0001u8];
+    assert_eq!(
+        unpack_bits(&packed, 3, BitOrder::Big),
+        array![true, false, true]
+    );
+    // Larger counts are padded with `false`, like NumPy does.
+    assert_eq!(
+        unpack_bits(&packed, 10, BitOrder::Little),
+        array![true, false, false, false, false, true, false, true, false, false],
+    );
+    assert_eq!(
+        unpack_bits(&Array1::<u8>::zeros(0), 2, BitOrder::Big),
+        array![false, false]
+    );
+}
+
+#[test]
+fn pack_along_last_axis() {
+    let arr = Array3::from_shape_fn((2, 3, 9), |(i, j, k)| (i + j * k) % 3 == 0);
+    let packed = pack_bits(&arr, BitOrder::Big);
+    assert_eq!(packed.shape(), [2, 3, 2]);
+    assert_eq!(unpack_bits(&packed, 9, BitOrder::Big), arr);
+
+    // Non-standard layouts are packed along the logical last axis.
+    let transposed = arr.t();
+    let packed = pack_bits(&transposed, BitOrder::Little);
+    assert_eq!(packed.shape(), [9, 3, 1]);
+    assert_eq!(unpack_bits(&packed, 2, BitOrder::Little), transposed);
+}
+
+#[test]
+fn write_and_read_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("mask.npy");
+    let arr = ArrayD::from_shape_fn(IxDyn(&[4, 13]), |idx| idx[0] * idx[1] % 5 == 1);
+    write_packed_npy(&path, &arr, BitOrder::Little).unwrap();
+    assert_eq!(
+        read_packed_npy::<_, IxDyn>(&path, BitOrder::Little).unwrap(),
+        arr
+    );
+
+    // The file contains the packed array followed by the shape.
+    let mut file = File::open(&path).unwrap();
+    let packed = Array2::<u8>::read_npy_partial(&mut file).unwrap();
+    assert_eq!(packed.into_dyn(), pack_bits(&arr, BitOrder::Little));
+    let shape = Array1::<u64>::read_npy(file).unwrap();
+    assert_eq!(shape, array![4, 13]);
+}
+
+#[test]
+fn write_and_read_stream() {
+    let arr = Array3::from_shape_fn((2, 3, 10), |(i, j, k)| (i + j + k) % 4 == 0);
+    let mut buf = Vec::new();
+    write_packed(&mut buf, &arr, BitOrder::Big).unwrap();
+    assert_eq!(read_packed::<_, Ix3>(&buf[..], BitOrder::Big).unwrap(), arr);
+}
+
+/// Returns the error of reading a packed array and the shape.
+fn read_packed_error(packed: &ArrayD<u8>, shape: &Array1<u64>) -> UnpackBitsError {
+    let mut buf = Vec::new();
+    packed.write_npy(&mut buf).unwrap();
+    shape.write_npy(&mut buf).unwrap();
+    match read_packed::<_, IxDyn>(&buf[..], BitOrder::Big) {
+        Err(ReadNpyError::ParseData(err)) => *err.downcast().unwrap(),
+        other => panic!("unexpected result {:?}", other),
+    }
+}
+
+#[test]
+fn read_wrong_shape() {
+    assert!(matches!(
+        read_packed_error(&arr0(0xff).into_dyn(), &array![]),
+        UnpackBitsError::ZeroDimensional,
+    ));
+    // The last axis of the packed array is too long for 8 bits.
+    assert!(matches!(
+        read_packed_error(&Array2::zeros((1, 2)).into_dyn(), &array![1, 8]),
+        UnpackBitsError::WrongShape { .. },
+    ));
+    assert!(matches!(
+        read_packed_error(&Array2::zeros((1, 2)).into_dyn(), &array![2, 9]),
+        UnpackBitsError::WrongShape { .. },
+    ));
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn npz_round_trip() {
+    use ndarray_npy::{NpzReader, NpzWriter};
+    use std::io::Cursor;
+
+    let arr = Array3::from_shape_fn((3, 2, 11), |(i, j, k)| (i * j + k) % 3 == 1);
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    npz.add_packed_bits("mask", &arr, BitOrder::Little).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    assert_eq!(npz.names().unwrap(), ["mask_shape", "mask"]);
+    let shape: Array1<u64> = npz.by_name("mask_shape").unwrap();
+    assert_eq!(shape, array![3, 2, 11]);
+    let packed: Array3<u8> = npz.by_name("mask").unwrap();
+    assert_eq!(packed.shape(), [3, 2, 2]);
+    assert_eq!(
+        npz.by_name_packed_bits::<IxDyn>("mask", BitOrder::Little)
+            .unwrap(),
+        arr.into_dyn()
+    );
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn npz_wrong_shape() {
+    use ndarray_npy::{NpzReader, NpzWriter, ReadNpzError};
+    use std::io::Cursor;
+
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    // The last axis of the packed array is too long for 8 bits.
+    npz.add_array("mask", &array![[0u8, 0]]).unwrap();
+    npz.add_array("mask_shape", &array![1u64, 8]).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    let err = npz
+        .by_name_packed_bits::<ndarray::Ix2>("mask", BitOrder::Big)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        ReadNpzError::Npy(ReadNpyError::ParseData(err)) if matches!(
+            err.downcast_ref(),
+            Some(UnpackBitsError::WrongShape { .. }),
+        ),
+    ));
+}
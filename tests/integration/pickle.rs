@@ -0,0 +1,315 @@
+//! Tests for reading object arrays with the `pickle` feature.
+
+use ndarray::{array, Array1, Array2};
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{PyObject, ReadDataError, ReadNpyError, ReadNpyExt, ReadableElement};
+use py_literal::Value as PyValue;
+
+/// Returns a `BINUNICODE` opcode for `s`.
+fn unicode(s: &str) -> Vec<u8> {
+    let mut out = vec![b'X'];
+    out.extend_from_slice(&(s.len() as u32).to_le_bytes());
+    out.extend_from_slice(s.as_bytes());
+    out
+}
+
+/// Returns the pickle that NumPy writes (with protocol 3) for an object array
+/// with the given shape, whose elements are pickled as `elements`.
+fn pickle_array(shape: &[u8], fortran: bool, elements: &[u8]) -> Vec<u8> {
+    let shape: Vec<u8> = shape.iter().flat_map(|&n| [b'K', n]).collect();
+    pickle_array_with_shape(&shape, fortran, elements)
+}
+
+/// Returns the pickle of an object array like [`pickle_array`], with the
+/// axis lengths given as pickled integers.
+fn pickle_array_with_shape(shape: &[u8], fortran: bool, elements: &[u8]) -> Vec<u8> {
+    let mut out =
+        b"\x80\x03cnumpy.core.multiarray\n_reconstruct\nq\x00cnumpy\nndarray\nq\x01".to_vec();
+    out.extend_from_slice(b"K\x00\x85q\x02C\x01bq\x03\x87q\x04Rq\x05(K\x01(");
+    out.extend_from_slice(shape);
+    out.extend_from_slice(b"tq\x06cnumpy\ndtype\nq\x07");
+    out.extend(unicode("O8"));
+    out.extend_from_slice(b"q\x08\x89\x88\x87q\tRq\n(K\x03");
+    out.extend(unicode("|"));
+    out.extend_from_slice(b"q\x0bNNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK?tq\x0cb");
+    out.push(if fortran { 0x88 } else { 0x89 });
+    out.extend_from_slice(b"]q\r(");
+    out.extend_from_slice(elements);
+    out.extend_from_slice(b"etq\x0eb.");
+    out
+}
+
+/// Returns an `.npy` file containing an object array.
+fn npy(shape: &[u8], fortran: bool, elements: &[u8]) -> Vec<u8> {
+    let header = Header {
+        type_descriptor: PyValue::String("|O".into()),
+        layout: if fortran {
+            Layout::Fortran
+        } else {
+            Layout::Standard
+        },
+        shape: shape.iter().map(|&n| n.into()).collect(),
+    };
+    let mut buf = header.to_bytes().unwrap();
+    buf.extend(pickle_array(shape, fortran, elements));
+    buf
+}
+
+fn read(data: &[u8], len: usize) -> Result<Vec<PyObject>, ReadDataError> {
+    PyObject::read_to_end_exact_vec(data, &PyValue::String("|O".into()), len)
+}
+
+#[test]
+fn read_scalars() {
+    let mut elements = unicode("héllo");
+    // -5, 300, 2**40, -(2**63)
+    elements.extend_from_slice(b"J\xfb\xff\xff\xffM\x2c\x01\x8a\x06\x00\x00\x00\x00\x00\x01");
+    elements.extend_from_slice(b"\x8a\x08\x00\x00\x00\x00\x00\x00\x00\x80");
+    elements.extend_from_slice(b"G\x3f\xf8\x00\x00\x00\x00\x00\x00N\x88C\x02\x00\xff");
+    let arr = Array1::<PyObject>::read_npy(&npy(&[9], false, &elements)[..]).unwrap();
+    assert_eq!(
+        arr,
+        array![
+            PyObject::Str("héllo".into()),
+            PyObject::Int(-5),
+            PyObject::Int(300),
+            PyObject::Int(1 << 40),
+            PyObject::Int(i64::MIN),
+            PyObject::Float(1.5),
+            PyObject::None,
+            PyObject::Bool(true),
+            PyObject::Bytes(vec![0, 0xff]),
+        ],
+    );
+}
+
+#[test]
+fn read_nested_lists_and_memo() {
+    // A list which is memoized before its items are appended and then
+    // referenced again, and a memoized string which is referenced again.
+    let mut elements = b"]q\x0f(G\x3f\xf0\x00\x00\x00\x00\x00\x00]q\x10K\x02aeh\x0f".to_vec();
+    elements.extend(unicode("a"));
+    elements.extend_from_slice(b"q\x11h\x11K\x02\x86");
+    let arr = Array2::<PyObject>::read_npy(&npy(&[2, 2], false, &elements)[..]).unwrap();
+    let list = PyObject::List(vec![
+        PyObject::Float(1.),
+        PyObject::List(vec![PyObject::Int(2)]),
+    ]);
+    assert_eq!(
+        arr,
+        array![
+            [list.clone(), list],
+            [
+                PyObject::Str("a".into()),
+                PyObject::Tuple(vec![PyObject::Str("a".into()), PyObject::Int(2)]),
+            ],
+        ],
+    );
+}
+
+#[test]
+fn read_fortran() {
+    let elements: Vec<u8> = (0..6).flat_map(|i| [b'K', i]).collect();
+    let arr = Array2::<PyObject>::read_npy(&npy(&[2, 3], true, &elements)[..]).unwrap();
+    assert_eq!(
+        arr,
+        Array2::from_shape_fn((2, 3), |(i, j)| PyObject::Int((i * 3 + j) as i64)),
+    );
+}
+
+#[test]
+fn read_protocol_4() {
+    // NumPy 2 with protocol 4 uses `FRAME`, `STACK_GLOBAL`, and `MEMOIZE`.
+    let mut data = b"\x80\x04\x95\x00\x01\x00\x00\x00\x00\x00\x00".to_vec();
+    for s in ["numpy._core.multiarray", "_reconstruct"] {
+        data.extend_from_slice(&[0x8c, s.len() as u8]);
+        data.extend_from_slice(s.as_bytes());
+        data.push(0x94);
+    }
+    data.extend_from_slice(b"\x93\x94\x8c\x05numpy\x94\x8c\x07ndarray\x94\x93\x94");
+    data.extend_from_slice(b"K\x00\x85\x94C\x01b\x94\x87\x94R\x94(K\x01K\x01\x85\x94");
+    data.extend_from_slice(
+        b"\x8c\x05numpy\x94\x8c\x05dtype\x94\x93\x94\x8c\x02O8\x94\x89\x88\x87\x94R\x94",
+    );
+    data.extend_from_slice(
+        b"(K\x03\x8c\x01|\x94NNNJ\xff\xff\xff\xffJ\xff\xff\xff\xffK?t\x94b\x89]\x94",
+    );
+    data.extend_from_slice(b"\x8c\x01x\x94at\x94b.");
+    assert_eq!(read(&data, 1).unwrap(), [PyObject::Str("x".into())]);
+}
+
+#[test]
+fn read_protocol_2_bytes() {
+    // Protocol 2 pickles `bytes` with `_codecs.encode`.
+    let elements =
+        b"c_codecs\nencode\nq\x0fX\x03\x00\x00\x00a\xc3\xbfX\x06\x00\x00\x00latin1\x86Rq\x10";
+    let data = pickle_array(&[1], false, elements);
+    assert_eq!(read(&data, 1).unwrap(), [PyObject::Bytes(vec![b'a', 0xff])]);
+}
+
+#[test]
+fn reject_unsupported() {
+    let check = |data: &[u8], msg: &str| match read(data, 1) {
+        Err(ReadDataError::ParseData(err)) => {
+            assert!(err.to_string().contains(msg), "{}", err)
+        }
+        other => panic!("unexpected result {:?}", other),
+    };
+    // A global which would execute code.
+    check(
+        &pickle_array(&[1], false, b"cos\nsystem\nX\x02\x00\x00\x00ls\x85R"),
+        "unsupported pickle global os.system",
+    );
+    // Dictionaries (`EMPTY_DICT`) aren't supported.
+    let data = pickle_array(&[1], false, b"}");
+    let offset = data.len() - 7;
+    check(
+        &data,
+        &format!("unsupported pickle opcode 0x7d at offset {}", offset),
+    );
+    // Integers which don't fit in `i64`.
+    check(
+        &pickle_array(&[1], false, b"\x8a\x09\x00\x00\x00\x00\x00\x00\x00\x80\x00"),
+        "does not fit in i64",
+    );
+    // The number of elements must match.
+    check(&pickle_array(&[2], false, b"NN"), "has 2 elements");
+    // Truncated data.
+    let data = pickle_array(&[1], false, b"N");
+    assert!(matches!(
+        read(&data[..data.len() - 1], 1),
+        Err(ReadDataError::MissingData),
+    ));
+}
+
+#[test]
+fn reject_exponential_expansion() {
+    let check = |data: &[u8], len: usize| match read(data, len) {
+        Err(ReadDataError::ParseData(err)) => {
+            assert!(err.to_string().contains("too large"), "{}", err)
+        }
+        other => panic!("unexpected result {:?}", other),
+    };
+    // Each list contains the previous one twice, `L[k + 1] = [L[k], L[k]]`,
+    // so the last one expands to 2^40 lists.
+    let mut elements = b"]q\x10".to_vec();
+    for k in 0x10..0x38 {
+        elements.extend_from_slice(&[b']', b'(', b'h', k, b'h', k, b'e', b'q', k + 1]);
+    }
+    check(&pickle_array(&[41], false, &elements), 41);
+    // The same with tuples, `T[k + 1] = (T[k], T[k])`.
+    let mut elements = b")q\x10".to_vec();
+    for k in 0x10..0x38 {
+        elements.extend_from_slice(&[b'h', k, 0x86, b'q', k + 1]);
+    }
+    check(&pickle_array(&[1], false, &elements), 1);
+    // A long string which is referenced many times.
+    let mut elements = vec![b'B'];
+    elements.extend_from_slice(&(1u32 << 16).to_le_bytes());
+    elements.extend(vec![b'x'; 1 << 16]);
+    elements.extend_from_slice(b"q\x10");
+    for _ in 0..254 {
+        elements.extend_from_slice(b"h\x10h\x10h\x10\x87");
+    }
+    check(&pickle_array(&[255], false, &elements), 255);
+}
+
+#[test]
+fn reject_wrong_shape() {
+    // The shape is compared with the header's before reordering the elements.
+    let elements: Vec<u8> = (0..6).flat_map(|i| [b'K', i]).collect();
+    let mut buf = npy(&[2, 3], true, &[]);
+    buf.truncate(buf.len() - pickle_array(&[2, 3], true, &[]).len());
+    buf.extend(pickle_array(&[3, 2], true, &elements));
+    match Array2::<PyObject>::read_npy(&buf[..]) {
+        Err(ReadNpyError::ParseData(err)) => assert_eq!(
+            err.to_string(),
+            "pickled array has shape [3, 2], but the header specifies [2, 3]",
+        ),
+        other => panic!("unexpected result {:?}", other),
+    }
+
+    // The number of elements of the pickled shape (2^62 + 1, 4) overflows,
+    // and would wrap around to 4.
+    let mut shape = b"\x8a\x08\x01\x00\x00\x00\x00\x00\x00\x40".to_vec();
+    shape.extend_from_slice(b"K\x04");
+    let data = pickle_array_with_shape(&shape, true, b"NNNN");
+    assert!(matches!(read(&data, 4), Err(ReadDataError::ParseData(_))));
+}
+
+#[test]
+fn reject_deep_nesting() {
+    let check = |elements: &[u8]| match read(&pickle_array(&[1], false, elements), 1) {
+        Err(ReadDataError::ParseData(err)) => {
+            assert_eq!(err.to_string(), "objects are nested too deeply")
+        }
+        other => panic!("unexpected result {:?}", other),
+    };
+    // `TUPLE1` repeated a million times.
+    let mut elements = b"N".to_vec();
+    elements.extend(vec![0x85; 1 << 20]);
+    check(&elements);
+    // A million empty lists, each appended to the previous one.
+    let mut elements = vec![b']'; 1 << 20];
+    elements.extend(vec![b'a'; (1 << 20) - 1]);
+    check(&elements);
+}
+
+#[test]
+fn read_sparse_memo_indices() {
+    // Large `LONG_BINPUT` indices don't allocate the memo up to the index.
+    let mut elements = b"N".to_vec();
+    for i in 1..=4096u32 {
+        elements.push(b'r');
+        elements.extend_from_slice(&(i << 20).to_le_bytes());
+    }
+    elements.extend_from_slice(b"j\x00\x00\x00\xff\x86");
+    let data = pickle_array(&[1], false, &elements);
+    assert_eq!(
+        read(&data, 1).unwrap(),
+        [PyObject::Tuple(vec![PyObject::None, PyObject::None])],
+    );
+}
+
+#[test]
+fn read_self_referencing_list() {
+    // A list which contains itself.
+    let data = pickle_array(&[1], false, b"]q\x10h\x10a");
+    assert!(matches!(read(&data, 1), Err(ReadDataError::ParseData(_))));
+}
+
+#[test]
+fn read_wrong_descriptor() {
+    let data = pickle_array(&[1], false, b"N");
+    assert!(matches!(
+        PyObject::read_to_end_exact_vec(&data[..], &PyValue::String("<f8".into()), 1),
+        Err(ReadDataError::WrongDescriptor(_)),
+    ));
+    let buf = npy(&[1], false, b"N");
+    assert!(matches!(
+        Array1::<f64>::read_npy(&buf[..]),
+        Err(ReadNpyError::WrongDescriptor(_)),
+    ));
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn read_npz() {
+    use ndarray_npy::NpzReader;
+    use std::io::{Cursor, Write};
+    use zip::write::{SimpleFileOptions, ZipWriter};
+
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let options = SimpleFileOptions::default().compression_method(zip::CompressionMethod::Stored);
+    zip.start_file("names.npy", options).unwrap();
+    zip.write_all(&npy(&[2], false, &[unicode("a"), unicode("b")].concat()))
+        .unwrap();
+    let buf = zip.finish().unwrap().into_inner();
+
+    let mut npz = NpzReader::new(Cursor::new(buf)).unwrap();
+    let arr: Array1<PyObject> = npz.by_name("names").unwrap();
+    assert_eq!(
+        arr,
+        array![PyObject::Str("a".into()), PyObject::Str("b".into())],
+    );
+}
@@ -108,3 +108,55 @@ fn read_bool_bad_value() {
         _ => panic!(),
     }
 }
+
+#[cfg(feature = "half")]
+#[test]
+fn read_f16_both_endians() {
+    use half::f16;
+    let elems = [f16::from_f32(1.5), f16::from_f32(-2.), f16::MAX];
+    let little: Vec<u8> = elems.iter().flat_map(|x| x.to_le_bytes()).collect();
+    let big: Vec<u8> = elems.iter().flat_map(|x| x.to_be_bytes()).collect();
+    let type_desc = PyValue::String(String::from("<f2"));
+    let out = <f16>::read_to_end_exact_vec(Cursor::new(little), &type_desc, elems.len()).unwrap();
+    assert_eq!(out, elems);
+    let type_desc = PyValue::String(String::from(">f2"));
+    let out = <f16>::read_to_end_exact_vec(Cursor::new(big), &type_desc, elems.len()).unwrap();
+    assert_eq!(out, elems);
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn read_bf16_void_descriptor() {
+    use half::bf16;
+    let elems = [bf16::from_f32(1.5), bf16::from_f32(-2.), bf16::MAX];
+    let little: Vec<u8> = elems.iter().flat_map(|x| x.to_le_bytes()).collect();
+    for desc in ["<V2", "|V2"] {
+        let type_desc = PyValue::String(String::from(desc));
+        let out =
+            <bf16>::read_to_end_exact_vec(Cursor::new(&little), &type_desc, elems.len()).unwrap();
+        assert_eq!(out, elems);
+    }
+    let type_desc = PyValue::String(String::from("<f2"));
+    let out = <bf16>::read_to_end_exact_vec(Cursor::new(&little), &type_desc, elems.len());
+    assert!(matches!(out, Err(ReadDataError::WrongDescriptor(_))));
+}
+
+#[cfg(all(feature = "half", target_endian = "little"))]
+#[test]
+fn view_bf16_void_descriptor() {
+    use half::bf16;
+    let elems = [bf16::from_f32(1.5), bf16::from_f32(-2.), bf16::MAX];
+    let buf: Vec<u8> = elems.iter().flat_map(|x| x.to_le_bytes()).collect();
+    let mut aligned = MaybeAlignedBytes::aligned_from_bytes(buf, mem::align_of::<bf16>());
+    for desc in ["<V2", "|V2"] {
+        let type_desc = PyValue::String(String::from(desc));
+        let out: &[bf16] = <bf16>::bytes_as_slice(&aligned, &type_desc, elems.len()).unwrap();
+        assert_eq!(out, elems);
+        let out: &mut [bf16] =
+            <bf16>::bytes_as_mut_slice(&mut aligned, &type_desc, elems.len()).unwrap();
+        assert_eq!(out, elems);
+    }
+    let type_desc = PyValue::String(String::from(">V2"));
+    let out = <bf16>::bytes_as_slice(&aligned, &type_desc, elems.len());
+    assert!(matches!(out, Err(ViewDataError::NonNativeEndian)));
+}
@@ -0,0 +1,172 @@
+//! Tests for `read_npy_raw` and `write_npy_raw`.
+
+use ndarray::prelude::*;
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{
+    read_npy_raw, write_npy_raw, RawDataError, ReadNpyError, ReadNpyExt, WriteNpyError, WriteNpyExt,
+};
+use py_literal::Value as PyValue;
+
+fn string(s: &str) -> PyValue {
+    PyValue::String(s.into())
+}
+
+fn header(type_descriptor: PyValue, layout: Layout, shape: Vec<usize>) -> Header {
+    Header {
+        type_descriptor,
+        layout,
+        shape,
+    }
+}
+
+/// Returns the data length error of writing `data` with `header`.
+#[track_caller]
+fn write_error(header: &Header, data: &[u8]) -> RawDataError {
+    let mut buf = Vec::new();
+    match write_npy_raw(&mut buf, header, data) {
+        Err(WriteNpyError::FormatData(err)) => {
+            assert!(buf.is_empty());
+            *err.downcast::<RawDataError>().unwrap()
+        }
+        _ => panic!("expected a raw data error"),
+    }
+}
+
+#[test]
+fn round_trip() {
+    let arr = array![[1i32, -2, 3], [4, 5, -6]];
+    let mut buf = Vec::new();
+    arr.t().write_npy(&mut buf).unwrap();
+    let (header, data) = read_npy_raw(&buf[..]).unwrap();
+    assert_eq!(header.layout, Layout::Fortran);
+    assert_eq!(header.shape, [3, 2]);
+    assert_eq!(
+        data,
+        arr.iter().flat_map(|x| x.to_ne_bytes()).collect::<Vec<_>>(),
+    );
+    let mut copy = Vec::new();
+    write_npy_raw(&mut copy, &header, &data).unwrap();
+    assert_eq!(copy, buf);
+}
+
+#[test]
+fn exotic_descriptor() {
+    // A nested structured type, which can't be decoded by this crate.
+    let desc = PyValue::List(vec![
+        PyValue::Tuple(vec![
+            string("inner"),
+            PyValue::List(vec![
+                PyValue::Tuple(vec![string("a"), string("<u2")]),
+                PyValue::Tuple(vec![string("b"), string("|V3")]),
+            ]),
+        ]),
+        PyValue::Tuple(vec![
+            string("c"),
+            string(">f4"),
+            PyValue::Tuple(vec![PyValue::Integer(2.into())]),
+        ]),
+    ]);
+    let header = header(desc, Layout::Standard, vec![2]);
+    let data = (0..26).collect::<Vec<u8>>();
+    let mut buf = Vec::new();
+    write_npy_raw(&mut buf, &header, &data).unwrap();
+    assert_eq!(read_npy_raw(&buf[..]).unwrap(), (header.clone(), data));
+
+    assert!(matches!(
+        write_error(&header, &[0; 27]),
+        RawDataError::WrongLength {
+            expected: 26,
+            actual: 27,
+        },
+    ));
+}
+
+#[test]
+fn rewrap_data() {
+    // Data from another source, stored big-endian.
+    let data = [1u8, 0, 0, 2];
+    let header = header(string(">u2"), Layout::Standard, vec![2, 1]);
+    let mut buf = Vec::new();
+    write_npy_raw(&mut buf, &header, &data).unwrap();
+    assert_eq!(
+        Array2::<u16>::read_npy(&buf[..]).unwrap(),
+        array![[256], [2]],
+    );
+}
+
+#[test]
+fn empty() {
+    let header = header(string("<f8"), Layout::Standard, vec![3, 0]);
+    let mut buf = Vec::new();
+    write_npy_raw(&mut buf, &header, &[]).unwrap();
+    assert_eq!(read_npy_raw(&buf[..]).unwrap(), (header, vec![]));
+}
+
+#[test]
+fn read_wrong_length() {
+    let header = header(string("<i4"), Layout::Standard, vec![2]);
+    let mut buf = header.to_bytes().unwrap();
+    buf.extend_from_slice(&[0; 7]);
+    assert!(matches!(
+        read_npy_raw(&buf[..]),
+        Err(ReadNpyError::MissingData)
+    ));
+    buf.extend_from_slice(&[0; 3]);
+    assert!(matches!(
+        read_npy_raw(&buf[..]),
+        Err(ReadNpyError::ExtraBytes(2))
+    ));
+}
+
+#[test]
+fn unsized_descriptor() {
+    for desc in [string("|O"), string("<x4")] {
+        let header = header(desc.clone(), Layout::Standard, vec![1]);
+        let buf = header.to_bytes().unwrap();
+        assert!(matches!(
+            read_npy_raw(&buf[..]),
+            Err(ReadNpyError::WrongDescriptor(d)) if d == desc,
+        ));
+        assert!(matches!(
+            write_error(&header, &[0; 8]),
+            RawDataError::UnsizedDescriptor(d) if d == desc,
+        ));
+    }
+}
+
+#[test]
+fn length_overflow() {
+    let header = header(string("<f8"), Layout::Standard, vec![usize::MAX / 4, 2]);
+    let buf = header.to_bytes().unwrap();
+    assert!(matches!(
+        read_npy_raw(&buf[..]),
+        Err(ReadNpyError::LengthOverflow)
+    ));
+    assert!(matches!(
+        write_error(&header, &[]),
+        RawDataError::LengthOverflow
+    ));
+}
+
+#[cfg(feature = "npz")]
+#[test]
+fn npz_raw() {
+    use ndarray_npy::{NpzReader, NpzWriter, WriteNpzError};
+    use std::io::Cursor;
+
+    let header = header(string("<c8"), Layout::Fortran, vec![1, 2]);
+    let data = (0..16).collect::<Vec<u8>>();
+    let mut npz = NpzWriter::new(Cursor::new(Vec::new()));
+    npz.add_array_raw("a", &header, &data).unwrap();
+    assert!(matches!(
+        npz.add_array_raw("b", &header, &data[1..]),
+        Err(WriteNpzError::Npy(WriteNpyError::FormatData(_))),
+    ));
+    npz.add_array("c", &array![1u8]).unwrap();
+    let mut npz = NpzReader::new(npz.finish().unwrap()).unwrap();
+    assert_eq!(npz.names().unwrap(), ["a", "c"]);
+    assert_eq!(npz.by_name_raw("a").unwrap(), (header, data));
+    let (header, data) = npz.by_index_raw(1).unwrap();
+    assert_eq!(header.type_descriptor, string("|u1"));
+    assert_eq!(data, [1]);
+}
@@ -0,0 +1,222 @@
+//! Tests for structs with `#[derive(NpyRecord)]`.
+
+use ndarray::{array, Array1};
+use ndarray_npy::record::RecordLayout;
+use ndarray_npy::{
+    NpyRecord, ReadDataError, ReadNpyExt, ReadableElement, ViewDataError, ViewElement,
+    WritableElement, WriteNpyExt,
+};
+use py_literal::Value as PyValue;
+
+#[derive(Clone, Debug, PartialEq, NpyRecord)]
+struct Row {
+    x: f32,
+    #[npy(rename = "label")]
+    name: String,
+    id: u64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, NpyRecord)]
+#[repr(C)]
+struct Padded {
+    a: u8,
+    b: i32,
+    c: u16,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, NpyRecord)]
+#[repr(C, packed)]
+struct Packed {
+    a: u8,
+    b: i32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, NpyRecord)]
+#[repr(C)]
+struct Nested {
+    flag: bool,
+    inner: Padded,
+}
+
+fn desc(s: &str) -> PyValue {
+    s.parse().unwrap()
+}
+
+#[test]
+fn descriptors() {
+    #[cfg(target_endian = "little")]
+    {
+        assert_eq!(
+            Padded::type_descriptor(),
+            desc("[('a', '|u1'), ('', '|V3'), ('b', '<i4'), ('c', '<u2'), ('', '|V2')]"),
+        );
+        assert_eq!(
+            Packed::type_descriptor(),
+            desc("[('a', '|u1'), ('b', '<i4')]"),
+        );
+        let rows = [
+            Row {
+                x: 1.,
+                name: "a".into(),
+                id: 1,
+            },
+            Row {
+                x: 2.,
+                name: "abc".into(),
+                id: 2,
+            },
+        ];
+        assert_eq!(
+            Row::array_type_descriptor(&rows),
+            desc("[('x', '<f4'), ('label', '<U3'), ('id', '<u8')]"),
+        );
+    }
+}
+
+#[test]
+fn layout_from_descriptor() {
+    let layout =
+        RecordLayout::from_type_descriptor(&desc("[('a', '|u1'), ('', '|V3'), ('b', '<i4')]"))
+            .unwrap();
+    assert_eq!(layout.itemsize(), 8);
+    assert_eq!(layout.fields().len(), 2);
+    assert_eq!(layout.field("b").unwrap().offset, 4);
+    assert_eq!(
+        layout.to_type_descriptor(),
+        desc("[('a', '|u1'), ('', '|V3'), ('b', '<i4')]"),
+    );
+    assert_eq!(RecordLayout::from_type_descriptor(&desc("'<f4'")), None);
+}
+
+#[test]
+fn round_trip() {
+    let rows = array![
+        Row {
+            x: 1.5,
+            name: "first".into(),
+            id: 7,
+        },
+        Row {
+            x: -2.,
+            name: "".into(),
+            id: u64::MAX,
+        },
+    ];
+    let mut buf = Vec::new();
+    rows.write_npy(&mut buf).unwrap();
+    assert_eq!(Array1::<Row>::read_npy(&buf[..]).unwrap(), rows);
+
+    let padded = array![[Padded { a: 1, b: -2, c: 3 }, Padded { a: 4, b: 5, c: 6 }]];
+    let mut buf = Vec::new();
+    padded.t().write_npy(&mut buf).unwrap();
+    assert_eq!(
+        ndarray::Array2::<Padded>::read_npy(&buf[..]).unwrap(),
+        padded.t()
+    );
+
+    let nested = array![Nested {
+        flag: true,
+        inner: Padded { a: 1, b: 2, c: 3 },
+    }];
+    let mut buf = Vec::new();
+    nested.write_npy(&mut buf).unwrap();
+    assert_eq!(Array1::<Nested>::read_npy(&buf[..]).unwrap(), nested);
+}
+
+#[test]
+fn read_mixed_byte_order_and_field_order() {
+    // Fields in a different order than the struct, with padding and mixed
+    // byte orders.
+    let type_desc = desc("[('c', '>u2'), ('', '|V2'), ('b', '>i4'), ('a', '|u1')]");
+    let mut data = Vec::new();
+    for (a, b, c) in [(1u8, -2i32, 3u16), (4, 5, 6)] {
+        data.extend_from_slice(&c.to_be_bytes());
+        data.extend_from_slice(&[0xff, 0xff]);
+        data.extend_from_slice(&b.to_be_bytes());
+        data.push(a);
+    }
+    let out = Padded::read_to_end_exact_vec(&data[..], &type_desc, 2).unwrap();
+    assert_eq!(
+        out,
+        [Padded { a: 1, b: -2, c: 3 }, Padded { a: 4, b: 5, c: 6 }],
+    );
+}
+
+#[test]
+fn read_wrong_fields() {
+    for type_desc in [
+        "[('a', '|u1'), ('b', '<i4')]",
+        "[('a', '|u1'), ('b', '<i4'), ('c', '<u2'), ('d', '<u2')]",
+        "[('a', '|u1'), ('b', '<f4'), ('c', '<u2')]",
+        "'<i4'",
+    ] {
+        let type_desc = desc(type_desc);
+        let data = [0; 7];
+        match Padded::read_to_end_exact_vec(&data[..], &type_desc, 1) {
+            Err(ReadDataError::WrongDescriptor(d)) => assert_eq!(d, type_desc),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+}
+
+#[test]
+fn view() {
+    let type_desc = Padded::type_descriptor();
+    let values = [Padded { a: 1, b: -2, c: 3 }, Padded { a: 4, b: 5, c: 6 }];
+    let mut data = Vec::new();
+    Padded::write_slice(&values, &mut data).unwrap();
+    let bytes = crate::MaybeAlignedBytes::aligned_from_bytes(data, 4);
+    assert_eq!(
+        Padded::bytes_as_slice(&bytes, &type_desc, 2).unwrap(),
+        values
+    );
+
+    // Packed structs don't need any alignment.
+    let type_desc = Packed::type_descriptor();
+    let values = [Packed { a: 1, b: 2 }, Packed { a: 3, b: 4 }];
+    let mut data = Vec::new();
+    Packed::write_slice(&values, &mut data).unwrap();
+    let bytes = crate::MaybeAlignedBytes::misaligned_from_bytes(data, 4);
+    assert_eq!(
+        Packed::bytes_as_slice(&bytes, &type_desc, 2).unwrap(),
+        values
+    );
+}
+
+#[test]
+fn view_invalid() {
+    let type_desc = Nested::type_descriptor();
+    let mut data = Vec::new();
+    Nested::write(
+        &Nested {
+            flag: true,
+            inner: Padded { a: 1, b: 2, c: 3 },
+        },
+        &mut data,
+    )
+    .unwrap();
+    data[0] = 2;
+    let bytes = crate::MaybeAlignedBytes::aligned_from_bytes(data, 4);
+    assert!(matches!(
+        Nested::bytes_as_slice(&bytes, &type_desc, 1),
+        Err(ViewDataError::InvalidData(_)),
+    ));
+
+    // Fields at different offsets than in memory.
+    let type_desc = desc("[('a', '|u1'), ('b', '<i4'), ('c', '<u2'), ('', '|V5')]");
+    let bytes = crate::MaybeAlignedBytes::aligned_zeros(12, 4);
+    assert!(matches!(
+        Padded::bytes_as_slice(&bytes, &type_desc, 1),
+        Err(ViewDataError::WrongDescriptor(_)),
+    ));
+
+    // Non-native byte order of a field.
+    #[cfg(target_endian = "little")]
+    let type_desc = desc("[('a', '|u1'), ('', '|V3'), ('b', '>i4'), ('c', '<u2'), ('', '|V2')]");
+    #[cfg(target_endian = "big")]
+    let type_desc = desc("[('a', '|u1'), ('', '|V3'), ('b', '<i4'), ('c', '>u2'), ('', '|V2')]");
+    assert!(matches!(
+        Padded::bytes_as_slice(&bytes, &type_desc, 1),
+        Err(ViewDataError::NonNativeEndian),
+    ));
+}
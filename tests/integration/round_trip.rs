@@ -1,6 +1,8 @@
 //! Tests that read/viewed data match the original written data.
 
 use crate::MaybeAlignedBytes;
+#[cfg(feature = "half")]
+use half::{bf16, f16};
 use ndarray::array;
 use ndarray::prelude::*;
 use ndarray_npy::{
@@ -150,6 +152,28 @@ fn round_trip_f64() {
     );
 }
 
+#[cfg(feature = "half")]
+#[test]
+fn round_trip_f16() {
+    let original = array![[1.5f32, -0.25, 65504.], [-3., 0.1, 7.]].mapv(f16::from_f32);
+    let modified = array![[1.5f32, 12., 65504.], [-3., 0.1, 42.]].mapv(f16::from_f32);
+    test_round_trip_multiple_layouts(original.view(), modified.view(), |mut v| {
+        v[[0, 1]] = f16::from_f32(12.);
+        v[[1, 2]] = f16::from_f32(42.);
+    });
+}
+
+#[cfg(feature = "half")]
+#[test]
+fn round_trip_bf16() {
+    let original = array![[1.5f32, -0.25, 3e38], [-3., 0.1, 7.]].mapv(bf16::from_f32);
+    let modified = array![[1.5f32, 12., 3e38], [-3., 0.1, 42.]].mapv(bf16::from_f32);
+    test_round_trip_multiple_layouts(original.view(), modified.view(), |mut v| {
+        v[[0, 1]] = bf16::from_f32(12.);
+        v[[1, 2]] = bf16::from_f32(42.);
+    });
+}
+
 #[cfg(feature = "num-complex-0_4")]
 #[test]
 fn round_trip_c32() {
@@ -187,6 +211,19 @@ fn round_trip_c32() {
     );
 }
 
+#[test]
+fn round_trip_datetime() {
+    use ndarray_npy::datetime::{DateTime64, Nanoseconds};
+    let original = array![[1_600_000_000_000_000_000i64, -5], [i64::MIN, 0]]
+        .mapv(DateTime64::<Nanoseconds>::new);
+    let modified = array![[1_600_000_000_000_000_000i64, 12], [i64::MIN, 42]]
+        .mapv(DateTime64::<Nanoseconds>::new);
+    test_round_trip_multiple_layouts(original.view(), modified.view(), |mut v| {
+        v[[0, 1]] = DateTime64::new(12);
+        v[[1, 1]] = DateTime64::new(42);
+    });
+}
+
 #[test]
 fn round_trip_bool() {
     test_round_trip_multiple_layouts(
@@ -0,0 +1,161 @@
+//! Tests for `read_npy_slice`.
+
+use ndarray::prelude::*;
+use ndarray::{SliceArg, SliceInfoElem};
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{read_npy_slice, NpyFile, ReadNpyError, ReadNpyExt};
+use py_literal::Value as PyValue;
+use std::fs::File;
+use std::io::Cursor;
+
+use crate::{npy, RecordingReader};
+
+/// Checks that reading `info` from the file matches slicing `arr`.
+#[track_caller]
+fn check<I>(buf: &[u8], arr: &Array3<i32>, info: I)
+where
+    I: SliceArg<IxDyn> + SliceArg<Ix3, OutDim = <I as SliceArg<IxDyn>>::OutDim> + Clone,
+    <I as SliceArg<IxDyn>>::OutDim: Dimension,
+{
+    let expected = arr.slice(info.clone()).to_owned();
+    let actual = read_npy_slice::<i32, _, _>(Cursor::new(buf), info).unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn standard_and_fortran() {
+    let arr = Array::from_shape_fn((4, 3, 5), |(i, j, k)| (i * 100 + j * 10 + k) as i32);
+    let mut fortran = Array3::zeros((4, 3, 5).f());
+    fortran.assign(&arr);
+    for buf in [npy(&arr), npy(&fortran)] {
+        check(&buf, &arr, s![.., .., ..]);
+        check(&buf, &arr, s![2, .., ..]);
+        check(&buf, &arr, s![-1, 1, ..]);
+        check(&buf, &arr, s![.., 1, 3]);
+        check(&buf, &arr, s![1..3, .., 1..4]);
+        check(&buf, &arr, s![.., ..;2, 1..;2]);
+        check(&buf, &arr, s![..;-1, 1.., ..;-2]);
+        check(&buf, &arr, s![-3..-1;-1, -1, NewAxis, 4]);
+        check(&buf, &arr, s![NewAxis, 1..1, .., ..]);
+        check(&buf, &arr, s![.., 2..2, 0]);
+        check(&buf, &arr, s![3, 2, 4]);
+    }
+}
+
+#[test]
+fn dynamic_slice() {
+    let arr = Array::from_shape_fn((3, 4), |(i, j)| (i * 10 + j) as u8);
+    let info = [
+        SliceInfoElem::from(1..),
+        SliceInfoElem::Index(-2),
+        SliceInfoElem::NewAxis,
+    ];
+    let actual: ArrayD<u8> = read_npy_slice(Cursor::new(npy(&arr)), &info[..]).unwrap();
+    assert_eq!(actual, array![[12], [22]].into_dyn());
+}
+
+#[test]
+fn minimal_reads() {
+    let arr = Array::from_shape_fn((10, 6, 7), |(t, i, j)| (t * 100 + i * 10 + j) as f64);
+    let buf = npy(&arr);
+    let header_len = buf.len() - arr.len() * 8;
+
+    let mut file = NpyFile::new(RecordingReader::new(buf.clone())).unwrap();
+    let frame: Array2<f64> = file.read_slice(s![4, .., ..]).unwrap();
+    assert_eq!(frame, arr.slice(s![4, .., ..]));
+    let reader = file.into_inner();
+    let data_reads: Vec<_> = reader
+        .reads
+        .iter()
+        .filter(|&&(position, _)| position >= header_len as u64)
+        .collect();
+    assert_eq!(data_reads.len(), 1);
+    assert_eq!(*data_reads[0], ((header_len + 4 * 42 * 8) as u64, 42 * 8));
+
+    // Runs of whole rows are merged, and the rest of the data is skipped.
+    let mut file = NpyFile::new(RecordingReader::new(buf.clone())).unwrap();
+    let sub: Array3<f64> = file.read_slice(s![2..4, 1..3, ..]).unwrap();
+    assert_eq!(sub, arr.slice(s![2..4, 1..3, ..]));
+    let reader = file.into_inner();
+    assert_eq!(reader.bytes_read() - header_len, sub.len() * 8);
+
+    // Strided selections read only the selected elements.
+    let mut file = NpyFile::new(RecordingReader::new(buf)).unwrap();
+    let sub: Array2<f64> = file.read_slice(s![.., 0, ..;3]).unwrap();
+    assert_eq!(sub, arr.slice(s![.., 0, ..;3]));
+    let reader = file.into_inner();
+    assert_eq!(reader.bytes_read() - header_len, sub.len() * 8);
+}
+
+#[test]
+fn big_endian() {
+    let path = "resources/example_f64_big_endian_fortran.npy";
+    let arr = Array3::<f64>::read_npy(File::open(path).unwrap()).unwrap();
+    let sub: Array2<f64> = read_npy_slice(File::open(path).unwrap(), s![.., 1, 1..]).unwrap();
+    assert_eq!(sub, arr.slice(s![.., 1, 1..]));
+    let sub: Array1<f64> = read_npy_slice(File::open(path).unwrap(), s![1, 2, ..;-1]).unwrap();
+    assert_eq!(sub, arr.slice(s![1, 2, ..;-1]));
+}
+
+#[test]
+fn strings() {
+    let arr = array![
+        ["a".to_string(), "bc".to_string()],
+        ["def".into(), "".into()]
+    ];
+    let sub: Array1<String> = read_npy_slice(Cursor::new(npy(&arr)), s![.., 0]).unwrap();
+    assert_eq!(sub, array!["a".to_string(), "def".to_string()]);
+}
+
+#[test]
+fn subarray() {
+    let header = Header {
+        type_descriptor: PyValue::Tuple(vec![
+            PyValue::String("<u2".into()),
+            PyValue::Tuple(vec![PyValue::Integer(3.into())]),
+        ]),
+        layout: Layout::Fortran,
+        shape: vec![2, 2],
+    };
+    let mut buf = header.to_bytes().unwrap();
+    for (i, j) in [(0u16, 0u16), (1, 0), (0, 1), (1, 1)] {
+        for k in 0..3 {
+            buf.extend_from_slice(&(i * 100 + j * 10 + k).to_le_bytes());
+        }
+    }
+    let arr = Array::from_shape_fn((2, 2, 3), |(i, j, k)| (i * 100 + j * 10 + k) as u16);
+    let sub: Array2<u16> = read_npy_slice(Cursor::new(&buf), s![.., 1, 1..]).unwrap();
+    assert_eq!(sub, arr.slice(s![.., 1, 1..]));
+    let sub: Array1<[u16; 3]> = read_npy_slice(Cursor::new(&buf), s![1, ..]).unwrap();
+    assert_eq!(sub, Array1::from(vec![[100, 101, 102], [110, 111, 112]]));
+}
+
+#[test]
+fn wrong_ndim() {
+    let buf = npy(&array![[1u8, 2]]);
+    assert!(matches!(
+        read_npy_slice::<u8, _, _>(Cursor::new(&buf), s![.., .., ..]),
+        Err(ReadNpyError::WrongNdim(Some(3), 2)),
+    ));
+}
+
+#[test]
+fn unsized_descriptor() {
+    let header = Header {
+        type_descriptor: PyValue::String("|O".into()),
+        layout: Layout::Standard,
+        shape: vec![2],
+    };
+    let buf = header.to_bytes().unwrap();
+    assert!(matches!(
+        read_npy_slice::<u8, _, _>(Cursor::new(&buf), s![..]),
+        Err(ReadNpyError::WrongDescriptor(_)),
+    ));
+}
+
+#[test]
+#[should_panic]
+fn out_of_bounds() {
+    let buf = npy(&array![[1u8, 2]]);
+    let _ = read_npy_slice::<u8, _, _>(Cursor::new(&buf), s![1, ..]);
+}
@@ -0,0 +1,141 @@
+//! Tests for reading and writing several arrays in one stream.
+
+use ndarray::prelude::*;
+use ndarray_npy::{
+    NpyStreamReader, NpyStreamWriter, ReadNpyError, ReadNpyExt, ReadableElement, WriteNpyError,
+    WriteNpyExt,
+};
+use std::fs;
+use std::io::{BufWriter, Read, Write};
+
+#[test]
+fn partial_then_rest() {
+    let mut buf = Vec::new();
+    array![[1u16, 2], [3, 4]].write_npy(&mut buf).unwrap();
+    array!["a".to_string(), "bcd".into()]
+        .write_npy(&mut buf)
+        .unwrap();
+    buf.extend_from_slice(b"trailer");
+
+    let mut reader = &buf[..];
+    let a = Array2::<u16>::read_npy_partial(&mut reader).unwrap();
+    assert_eq!(a, array![[1, 2], [3, 4]]);
+    let b = Array1::<String>::read_npy_partial(&mut reader).unwrap();
+    assert_eq!(b, array!["a".to_string(), "bcd".into()]);
+    assert_eq!(reader, b"trailer");
+}
+
+#[test]
+fn repeated_save() {
+    // Equivalent to calling `np.save(f, a)` twice on the same file.
+    let mut buf = fs::read("resources/example_f64_big_endian_fortran.npy").unwrap();
+    buf.extend(fs::read("resources/example_f64_little_endian_standard.npy").unwrap());
+    let expected = Array3::<f64>::read_npy(
+        fs::File::open("resources/example_f64_little_endian_standard.npy").unwrap(),
+    )
+    .unwrap();
+    let arrays: Vec<Array3<f64>> = NpyStreamReader::new(&buf[..])
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(arrays, [expected.clone(), expected]);
+}
+
+#[test]
+fn writer_round_trip() {
+    let arrays = [
+        array![[1i64, 2, 3], [4, 5, 6]].into_dyn(),
+        Array::zeros(IxDyn(&[0, 4])),
+        arr0(7i64).into_dyn(),
+    ];
+    let mut writer = NpyStreamWriter::new(Vec::new());
+    for arr in &arrays {
+        writer.write(arr).unwrap();
+    }
+    assert!(!writer.get_ref().is_empty());
+    let buf = writer.into_inner();
+    let read: Vec<ArrayD<i64>> = NpyStreamReader::new(&buf[..])
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read, arrays);
+
+    let mut reader = NpyStreamReader::<i64, IxDyn, _>::new(&buf[..]);
+    assert!(reader.next().is_some());
+    let mut rest = reader.into_inner();
+    let second = Array2::<i64>::read_npy_partial(&mut rest).unwrap();
+    assert_eq!(second.shape(), [0, 4]);
+}
+
+#[test]
+fn empty_stream() {
+    assert!(NpyStreamReader::<u8, Ix1, _>::new(&[][..]).next().is_none());
+}
+
+#[test]
+fn truncated_stream() {
+    let mut buf = Vec::new();
+    array![1u32, 2].write_npy(&mut buf).unwrap();
+    array![3u32, 4].write_npy(&mut buf).unwrap();
+    buf.pop();
+    let mut reader = NpyStreamReader::<u32, Ix1, _>::new(&buf[..]);
+    assert_eq!(reader.next().unwrap().unwrap(), array![1, 2]);
+    assert!(matches!(
+        reader.next(),
+        Some(Err(ReadNpyError::MissingData))
+    ));
+    assert!(reader.next().is_none());
+}
+
+#[test]
+fn wrong_type() {
+    let mut buf = Vec::new();
+    array![1u32, 2].write_npy(&mut buf).unwrap();
+    let mut reader = NpyStreamReader::<f32, Ix1, _>::new(&buf[..]);
+    assert!(matches!(
+        reader.next(),
+        Some(Err(ReadNpyError::WrongDescriptor(_)))
+    ));
+    assert!(reader.next().is_none());
+}
+
+/// A type which implements only the required method of `ReadNpyExt`.
+#[derive(Debug, PartialEq)]
+struct Wrapper<A>(Array1<A>);
+
+impl<A: ReadableElement> ReadNpyExt for Wrapper<A> {
+    fn read_npy<R: Read>(reader: R) -> Result<Self, ReadNpyError> {
+        Array1::read_npy(reader).map(Wrapper)
+    }
+}
+
+#[test]
+fn default_read_npy_partial() {
+    let mut buf = Vec::new();
+    array![1.5f32, 2.5].write_npy(&mut buf).unwrap();
+    array![3.5f32].write_npy(&mut buf).unwrap();
+    let mut reader = &buf[..];
+    let a = Wrapper::<f32>::read_npy_partial(&mut reader).unwrap();
+    let b = Wrapper::<f32>::read_npy_partial(&mut reader).unwrap();
+    assert_eq!(a, Wrapper(array![1.5, 2.5]));
+    assert_eq!(b, Wrapper(array![3.5]));
+    assert!(reader.is_empty());
+}
+
+/// A type whose `write_npy` doesn't flush the writer.
+struct Unflushed(Array1<u8>);
+
+impl WriteNpyExt for Unflushed {
+    fn write_npy<W: Write>(&self, mut writer: W) -> Result<(), WriteNpyError> {
+        let mut buf = Vec::new();
+        self.0.write_npy(&mut buf)?;
+        writer.write_all(&buf)?;
+        Ok(())
+    }
+}
+
+#[test]
+fn writer_flushes() {
+    let mut writer = NpyStreamWriter::new(BufWriter::new(Vec::new()));
+    writer.write(&Unflushed(array![1, 2, 3])).unwrap();
+    let buf = writer.get_ref().get_ref();
+    assert_eq!(Array1::<u8>::read_npy(&buf[..]).unwrap(), array![1, 2, 3]);
+}
@@ -0,0 +1,236 @@
+//! Tests for string and void element types.
+
+use ndarray::prelude::*;
+use ndarray_npy::{
+    FixedBytes, ReadDataError, ReadNpyError, ReadNpyExt, ReadableElement, ViewMutNpyExt,
+    ViewNpyExt, Void, WritableElement, WriteNpyExt,
+};
+use py_literal::Value as PyValue;
+use std::convert::TryFrom;
+
+fn fixed<const N: usize>(bytes: &[u8]) -> FixedBytes<N> {
+    FixedBytes::try_from(bytes).unwrap()
+}
+
+#[test]
+fn write_read_byte_strings() {
+    let arr: Array2<Vec<u8>> = array![
+        [b"ab".to_vec(), b"".to_vec()],
+        [b"abcdef".to_vec(), b"x\0y".to_vec()],
+    ];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert!(String::from_utf8_lossy(&buf).contains("'descr': '|S6'"));
+
+    let read = Array2::<Vec<u8>>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read, arr);
+
+    // Wider fixed-width elements are zero-padded.
+    let read = Array2::<FixedBytes<8>>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read[[1, 0]], fixed(b"abcdef"));
+    assert_eq!(read[[1, 1]].as_bytes(), b"x\0y");
+    assert_eq!(read[[0, 1]].as_bytes(), b"");
+
+    // Narrower fixed-width elements can't hold the data.
+    let res = Array2::<FixedBytes<4>>::read_npy(&buf[..]);
+    assert!(matches!(res, Err(ReadNpyError::WrongDescriptor(_))));
+}
+
+#[test]
+fn write_byte_strings_discontiguous() {
+    let arr: Array2<Vec<u8>> = array![
+        [b"a".to_vec(), b"bcd".to_vec()],
+        [b"ef".to_vec(), b"".to_vec()],
+    ];
+    let mut buf = Vec::new();
+    arr.slice(s![.., ..;-1]).write_npy(&mut buf).unwrap();
+    let read = Array2::<Vec<u8>>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read, arr.slice(s![.., ..;-1]));
+}
+
+#[test]
+fn write_empty_byte_strings() {
+    let arr = Array1::<Vec<u8>>::from(vec![]);
+    assert_eq!(
+        <Vec<u8>>::array_type_descriptor(arr.iter()),
+        PyValue::String("|S1".into()),
+    );
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    let read = Array1::<Vec<u8>>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read, arr);
+}
+
+#[test]
+fn write_byte_string_element() {
+    // A single element is written with the width of `type_descriptor`.
+    let mut buf = Vec::new();
+    b"a".to_vec().write(&mut buf).unwrap();
+    Vec::new().write(&mut buf).unwrap();
+    assert_eq!(buf, b"a\0");
+    assert!(b"ab".to_vec().write(&mut buf).is_err());
+    // So is a slice.
+    let mut buf = Vec::new();
+    <Vec<u8>>::write_slice(&[b"a".to_vec(), Vec::new()], &mut buf).unwrap();
+    assert_eq!(buf, b"a\0");
+    assert!(<Vec<u8>>::write_slice(&[b"ab".to_vec()], &mut buf).is_err());
+
+    let type_desc = <Vec<u8>>::array_type_descriptor([&b"ab".to_vec()]);
+    let mut buf = Vec::new();
+    b"ab"
+        .to_vec()
+        .write_with_descriptor(&type_desc, &mut buf)
+        .unwrap();
+    assert_eq!(buf, b"ab");
+    let mut buf = Vec::new();
+    <Vec<u8>>::write_slice_with_descriptor(&[b"ab".to_vec(), b"c".to_vec()], &type_desc, &mut buf)
+        .unwrap();
+    assert_eq!(buf, b"abc\0");
+}
+
+#[test]
+fn write_unicode_element() {
+    // A single element is written with the width of `type_descriptor`.
+    let mut buf = Vec::new();
+    "é".to_string().write(&mut buf).unwrap();
+    assert_eq!(buf, u32::from('é').to_ne_bytes());
+    assert!("ab".to_string().write(&mut buf).is_err());
+    // So is a slice.
+    let mut buf = Vec::new();
+    String::write_slice(&["é".to_string(), String::new()], &mut buf).unwrap();
+    assert_eq!(buf, ucs4_bytes(&["é", ""], 1, cfg!(target_endian = "big")));
+    assert!(String::write_slice(&["ab".to_string()], &mut buf).is_err());
+
+    let type_desc = String::array_type_descriptor([&"ab".to_string()]);
+    let mut buf = Vec::new();
+    "a".to_string()
+        .write_with_descriptor(&type_desc, &mut buf)
+        .unwrap();
+    assert_eq!(buf.len(), 8);
+    // The padding of the scratch buffer is cleared between elements.
+    let mut buf = Vec::new();
+    String::write_slice_with_descriptor(&["ab".to_string(), "c".to_string()], &type_desc, &mut buf)
+        .unwrap();
+    assert_eq!(
+        buf,
+        ucs4_bytes(&["ab", "c"], 2, cfg!(target_endian = "big"))
+    );
+}
+
+#[test]
+fn view_fixed_bytes() {
+    let arr = array![fixed::<3>(b"abc"), fixed(b"d"), fixed(b"")];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert!(String::from_utf8_lossy(&buf).contains("'descr': '|S3'"));
+
+    let view = ArrayView1::<FixedBytes<3>>::view_npy(&buf).unwrap();
+    assert_eq!(view, arr);
+    assert!(ArrayView1::<FixedBytes<4>>::view_npy(&buf).is_err());
+
+    let mut view_mut = ArrayViewMut1::<FixedBytes<3>>::view_mut_npy(&mut buf).unwrap();
+    view_mut[2] = fixed(b"xy");
+    let read = Array1::<Vec<u8>>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read, array![b"abc".to_vec(), b"d".to_vec(), b"xy".to_vec()]);
+}
+
+#[test]
+fn fixed_bytes_too_long() {
+    assert!(FixedBytes::<2>::try_from(&b"abc"[..]).is_err());
+}
+
+/// Encodes the strings as UCS-4 with the specified width.
+fn ucs4_bytes(strings: &[&str], width: usize, big_endian: bool) -> Vec<u8> {
+    let mut out = Vec::new();
+    for s in strings {
+        let mut code_units: Vec<u32> = s.chars().map(u32::from).collect();
+        code_units.resize(width, 0);
+        for c in code_units {
+            if big_endian {
+                out.extend_from_slice(&c.to_be_bytes());
+            } else {
+                out.extend_from_slice(&c.to_le_bytes());
+            }
+        }
+    }
+    out
+}
+
+#[test]
+fn read_unicode_both_endians() {
+    let strings = ["héllo", "", "日本語", "a\u{1F600}"];
+    for (desc, big_endian) in [("<U6", false), (">U6", true)] {
+        let data = ucs4_bytes(&strings, 6, big_endian);
+        let read = <String>::read_to_end_exact_vec(
+            &data[..],
+            &PyValue::String(desc.into()),
+            strings.len(),
+        )
+        .unwrap();
+        assert_eq!(read, strings);
+    }
+}
+
+#[test]
+fn read_unicode_invalid_code_point() {
+    let mut data = ucs4_bytes(&["ab", "cd"], 2, false);
+    data[12..16].copy_from_slice(&0xd800u32.to_le_bytes());
+    match <String>::read_to_end_exact_vec(&data[..], &PyValue::String("<U2".into()), 2) {
+        Err(ReadDataError::ParseData(err)) => assert_eq!(
+            err.to_string(),
+            "error parsing value 0x0000d800 as a Unicode code point in element 1",
+        ),
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn write_read_unicode() {
+    let arr: Array2<String> = array![
+        ["a".to_string(), "日本語".to_string()],
+        ["".to_string(), "xyz".to_string()],
+    ];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    #[cfg(target_endian = "little")]
+    assert!(String::from_utf8_lossy(&buf).contains("'descr': '<U3'"));
+    #[cfg(target_endian = "big")]
+    assert!(String::from_utf8_lossy(&buf).contains("'descr': '>U3'"));
+    let read = Array2::<String>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read, arr);
+
+    let mut buf = Vec::new();
+    arr.t().write_npy(&mut buf).unwrap();
+    let read = Array2::<String>::read_npy(&buf[..]).unwrap();
+    assert_eq!(read, arr.t());
+}
+
+#[test]
+fn void_round_trip_and_view() {
+    let arr = array![Void([1, 0, 0, 0]), Void([0xff, 0xfe, 0, 0])];
+    assert_eq!(Void::<4>::type_descriptor(), PyValue::String("|V4".into()));
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert!(String::from_utf8_lossy(&buf).contains("'descr': '|V4'"));
+
+    // Trailing NUL bytes are preserved.
+    assert_eq!(Array1::<Void<4>>::read_npy(&buf[..]).unwrap(), arr);
+    assert_eq!(ArrayView1::<Void<4>>::view_npy(&buf).unwrap(), arr);
+    let mut view_mut = ArrayViewMut1::<Void<4>>::view_mut_npy(&mut buf).unwrap();
+    view_mut[0] = Void(*b"uuid");
+    assert_eq!(
+        Array1::<Void<4>>::read_npy(&buf[..]).unwrap()[0].as_bytes(),
+        b"uuid"
+    );
+}
+
+#[test]
+fn void_wrong_width() {
+    let data = [0u8; 6];
+    let read =
+        |desc: &str| Void::<2>::read_to_end_exact_vec(&data[..], &PyValue::String(desc.into()), 3);
+    assert_eq!(read("|V2").unwrap(), [Void([0; 2]); 3]);
+    for desc in ["|V3", "|S2"] {
+        assert!(matches!(read(desc), Err(ReadDataError::WrongDescriptor(_))));
+    }
+}
@@ -0,0 +1,152 @@
+//! Tests for subarray descriptors.
+
+use ndarray::{array, Array1, Array2, Array3, ArrayD, ArrayView3, ArrayViewMut3};
+use ndarray_npy::npy::header::{Header, Layout};
+use ndarray_npy::{ReadNpyExt, ViewMutNpyExt, ViewNpyExt, WritableElement, WriteNpyExt};
+use py_literal::Value as PyValue;
+
+/// Returns an `.npy` file with the given header and little-endian `f32` data.
+fn npy_f32(type_desc: &str, layout: Layout, shape: &[usize], data: &[f32]) -> Vec<u8> {
+    let header = Header {
+        type_descriptor: type_desc.parse().unwrap(),
+        layout,
+        shape: shape.to_vec(),
+    };
+    let mut buf = header.to_bytes().unwrap();
+    buf.extend(data.iter().flat_map(|x| x.to_le_bytes()));
+    buf
+}
+
+#[test]
+fn read_subarray_as_extra_axes() {
+    let data: Vec<f32> = (0..12).map(|x| x as f32).collect();
+    let buf = npy_f32("('<f4', (3,))", Layout::Standard, &[2, 2], &data);
+    let arr = Array3::<f32>::read_npy(&buf[..]).unwrap();
+    assert_eq!(
+        arr,
+        Array3::from_shape_vec((2, 2, 3), data.clone()).unwrap()
+    );
+    let arr = ArrayD::<f32>::read_npy(&buf[..]).unwrap();
+    assert_eq!(arr.shape(), [2, 2, 3]);
+
+    // The subarrays can also be read as elements.
+    let arr = Array2::<[f32; 3]>::read_npy(&buf[..]).unwrap();
+    assert_eq!(arr[[1, 0]], [6., 7., 8.]);
+}
+
+#[test]
+fn read_subarray_fortran() {
+    // The outer axes are in Fortran order, but each subarray is in C order.
+    let data: Vec<f32> = (0..12).map(|x| x as f32).collect();
+    let buf = npy_f32("('<f4', (3,))", Layout::Fortran, &[2, 2], &data);
+    let arr = Array3::<f32>::read_npy(&buf[..]).unwrap();
+    assert_eq!(arr.shape(), [2, 2, 3]);
+    assert_eq!(arr.slice(ndarray::s![1, 0, ..]), array![3., 4., 5.]);
+    assert_eq!(arr.slice(ndarray::s![0, 1, ..]), array![6., 7., 8.]);
+
+    let start = buf.len() - data.len() * 4;
+    let mut aligned = crate::MaybeAlignedBytes::aligned_from_bytes(buf, 64);
+    let view = ArrayView3::<f32>::view_npy(&aligned).unwrap();
+    assert_eq!(view, arr);
+    let mut view = ArrayViewMut3::<f32>::view_mut_npy(&mut aligned).unwrap();
+    view[[0, 1, 2]] = -1.;
+    assert_eq!(
+        f32::from_le_bytes(aligned[start + 8 * 4..start + 9 * 4].try_into().unwrap()),
+        -1.,
+    );
+}
+
+#[test]
+fn read_multidimensional_subarray() {
+    let data: Vec<f32> = (0..12).map(|x| x as f32).collect();
+    let buf = npy_f32("(('<f4', (2,)), (3,))", Layout::Standard, &[2], &data);
+    let arr = Array3::<f32>::read_npy(&buf[..]).unwrap();
+    assert_eq!(
+        arr,
+        Array3::from_shape_vec((2, 3, 2), data.clone()).unwrap()
+    );
+    let arr = Array1::<[[f32; 2]; 3]>::read_npy(&buf[..]).unwrap();
+    assert_eq!(arr[1], [[6., 7.], [8., 9.], [10., 11.]]);
+}
+
+#[test]
+fn write_arrays_as_subarrays() {
+    #[cfg(target_endian = "little")]
+    assert_eq!(
+        <[[u16; 2]; 3]>::type_descriptor(),
+        "('<u2', (3, 2))".parse::<PyValue>().unwrap(),
+    );
+    let arr = Array1::from(vec![
+        [[1u16, 2], [3, 4], [5, 6]],
+        [[7, 8], [9, 10], [11, 12]],
+    ]);
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(Array1::<[[u16; 2]; 3]>::read_npy(&buf[..]).unwrap(), arr);
+    assert_eq!(
+        Array3::<u16>::read_npy(&buf[..]).unwrap(),
+        Array3::from_shape_fn((2, 3, 2), |(i, j, k)| (i * 6 + j * 2 + k + 1) as u16),
+    );
+}
+
+#[test]
+fn write_byte_string_subarrays() {
+    // Without a descriptor, elements are written with the width of
+    // `type_descriptor`, i.e. `('|S1', (2,))`.
+    let elems = [[b"a".to_vec(), Vec::new()], [b"b".to_vec(), b"c".to_vec()]];
+    let mut buf = Vec::new();
+    <[Vec<u8>; 2]>::write_slice(&elems, &mut buf).unwrap();
+    assert_eq!(buf, b"a\0bc");
+    assert!(<[Vec<u8>; 2]>::write_slice(&[[b"ab".to_vec(), Vec::new()]], &mut buf).is_err());
+
+    // Arrays are written with the width of the longest element.
+    let arr = Array1::from(vec![
+        [b"ab".to_vec(), Vec::new()],
+        [b"c".to_vec(), b"d".to_vec()],
+    ]);
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(Array1::<[Vec<u8>; 2]>::read_npy(&buf[..]).unwrap(), arr);
+}
+
+#[cfg(feature = "derive")]
+#[test]
+fn record_with_subarray_field() {
+    use ndarray_npy::{NpyRecord, ReadableElement};
+
+    #[derive(Clone, Debug, PartialEq, NpyRecord)]
+    #[repr(C)]
+    struct Point {
+        pos: [f64; 3],
+        id: i32,
+    }
+
+    #[cfg(target_endian = "little")]
+    assert_eq!(
+        Point::type_descriptor(),
+        "[('pos', '<f8', (3,)), ('id', '<i4'), ('', '|V4')]"
+            .parse::<PyValue>()
+            .unwrap(),
+    );
+    let type_desc: PyValue = "[('pos', '<f8', (3,)), ('id', '>i4')]".parse().unwrap();
+    let mut data = Vec::new();
+    for x in [1f64, 2., 3.] {
+        data.extend_from_slice(&x.to_le_bytes());
+    }
+    data.extend_from_slice(&7i32.to_be_bytes());
+    assert_eq!(
+        Point::read_to_end_exact_vec(&data[..], &type_desc, 1).unwrap(),
+        [Point {
+            pos: [1., 2., 3.],
+            id: 7,
+        }],
+    );
+
+    let arr = array![Point {
+        pos: [4., 5., 6.],
+        id: -1,
+    }];
+    let mut buf = Vec::new();
+    arr.write_npy(&mut buf).unwrap();
+    assert_eq!(Array1::<Point>::read_npy(&buf[..]).unwrap(), arr);
+}